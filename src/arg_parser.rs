@@ -1,3 +1,4 @@
+use std::fmt::{Display, Formatter};
 use std::ops::RangeInclusive;
 
 use clap::ArgEnum;
@@ -7,8 +8,8 @@ use clap::Parser;
 #[derive(Parser, Debug)]
 pub struct Args {
     /// Set compiler optimize level
-    #[clap(short = 'O', default_value_t = 0, value_name = "OPTIMIZE_LEVEL", parse(try_from_str = port_in_range))]
-    pub optimization: usize,
+    #[clap(short = 'O', default_value_t = OptimizeLevel::O0, value_name = "OPTIMIZE_LEVEL", parse(try_from_str = port_in_range))]
+    pub optimization: OptimizeLevel,
 
     /// Set compiler output format
     #[clap(short = 'S', default_value_t = OutputFormatEnum::OBJ, arg_enum)]
@@ -18,6 +19,14 @@ pub struct Args {
     #[clap(short = 'o', default_value = "./a.out", parse(from_os_str), value_name = "OUTPUT_FILE_PATH", value_hint = clap::ValueHint::DirPath)]
     pub output_path: std::path::PathBuf,
 
+    /// Set relocation model of generated code
+    #[clap(long = "relocation-model", default_value_t = RelocModeEnum::DEFAULT, arg_enum)]
+    pub relocation_model: RelocModeEnum,
+
+    /// Set code model of generated code
+    #[clap(long = "code-model", default_value_t = CodeModelEnum::DEFAULT, arg_enum)]
+    pub code_model: CodeModelEnum,
+
     /// Set compiler source file path
     #[clap(short = 'i', parse(from_os_str), value_name = "SOURCE_FILE_PATH", value_hint = clap::ValueHint::DirPath,)]
     pub input_path: std::path::PathBuf,
@@ -33,20 +42,86 @@ pub enum OutputFormatEnum {
     EXE,
 }
 
+#[allow(non_camel_case_types)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ArgEnum, Debug)]
+pub enum RelocModeEnum {
+    DEFAULT,
+    STATIC,
+    PIC,
+    DYNAMIC_NO_PIC,
+}
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ArgEnum, Debug)]
+pub enum CodeModelEnum {
+    DEFAULT,
+    SMALL,
+    MEDIUM,
+    LARGE,
+}
+
+/// 优化等级，除了0-3的速度优化等级外，s和z分别对应针对代码体积的优化
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum OptimizeLevel {
+    O0,
+    O1,
+    O2,
+    O3,
+    Os,
+    Oz,
+}
+
+impl OptimizeLevel {
+    /// 对应的速度优化等级，体积优化使用与O2相同的速度优化等级
+    pub fn speed_level(&self) -> usize {
+        match self {
+            OptimizeLevel::O0 => { 0 }
+            OptimizeLevel::O1 => { 1 }
+            OptimizeLevel::O2 | OptimizeLevel::Os | OptimizeLevel::Oz => { 2 }
+            OptimizeLevel::O3 => { 3 }
+        }
+    }
+
+    /// 对应的体积优化等级，0表示不针对体积优化
+    pub fn size_level(&self) -> u32 {
+        match self {
+            OptimizeLevel::Os => { 1 }
+            OptimizeLevel::Oz => { 2 }
+            _ => { 0 }
+        }
+    }
+}
+
+impl Display for OptimizeLevel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OptimizeLevel::Os => { write!(f, "s") }
+            OptimizeLevel::Oz => { write!(f, "z") }
+            _ => { write!(f, "{}", self.speed_level()) }
+        }
+    }
+}
 
 const OPTIMIZE_LEVEL_RANGE: RangeInclusive<usize> = 0..=3;
 
-fn port_in_range(s: &str) -> Result<usize, String> {
+fn port_in_range(s: &str) -> Result<OptimizeLevel, String> {
+    match s {
+        "s" => { return Ok(OptimizeLevel::Os); }
+        "z" => { return Ok(OptimizeLevel::Oz); }
+        _ => {}
+    }
     let port: usize = s
         .parse()
         .map_err(|_| format!("`{}` isn't a valid optimize level number", s))?;
-    if OPTIMIZE_LEVEL_RANGE.contains(&port) {
-        Ok(port)
-    } else {
-        Err(format!(
-            "Optimize level can only in range {}-{}",
-            OPTIMIZE_LEVEL_RANGE.start(),
-            OPTIMIZE_LEVEL_RANGE.end()
-        ))
+    match port {
+        0 => { Ok(OptimizeLevel::O0) }
+        1 => { Ok(OptimizeLevel::O1) }
+        2 => { Ok(OptimizeLevel::O2) }
+        3 => { Ok(OptimizeLevel::O3) }
+        _ => {
+            Err(format!(
+                "Optimize level can only in range {}-{}, or be s/z",
+                OPTIMIZE_LEVEL_RANGE.start(),
+                OPTIMIZE_LEVEL_RANGE.end()
+            ))
+        }
     }
 }
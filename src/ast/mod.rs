@@ -1,4 +1,68 @@
 pub use nodes::Ast;
 
+use crate::error::Result;
+use crate::lexer::LELexer;
+
 pub mod parser;
 pub mod nodes;
+mod printer;
+
+/// 解析整个源文件并返回完整的语法树，供格式化、静态检查等不需要生成代码的工具使用
+pub fn parse_program(source: &str) -> Result<Ast> {
+    //LELexer::new总是返回Some
+    Ast::from_lexer(LELexer::new(source).unwrap())
+}
+
+/// 与`parse_program`相同，但语法树中所有位置都加上`offset`，合并多个源文件时用来区分各文件中的位置
+pub fn parse_program_at(source: &str, offset: usize) -> Result<Ast> {
+    Ast::from_lexer(LELexer::with_offset(source, offset).unwrap())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ast::parse_program;
+
+    #[test]
+    fn test_parse_program() {
+        let ast = parse_program("decl le puts(ref i8)->i32;
+            var counter = 0;
+            struct Point{ x:i32, y:i32 }
+            le first()->i32;
+            le first()->i32{ ret 1; }
+            le second(p:Point)->i32{ ret p.x; }").unwrap();
+        assert_eq!(ast.extern_functions.len(), 1);
+        assert_eq!(ast.globals_variables.len(), 1);
+        assert_eq!(ast.globals_structures.len(), 1);
+        assert_eq!(ast.forward_declarations.len(), 1);
+        assert_eq!(ast.function_definitions.len(), 2);
+        assert!(parse_program("le broken(").is_err());
+    }
+
+    #[test]
+    fn test_print_and_reparse() {
+        let source = "decl { le puts(ref i8)->i32; le abs(i32)->i32; }
+            var counter:i64 = 1 + 2 * 3;
+            @repr(C) struct Point{ x:i32, y:i32, data:[f64; 2] }
+            impl Point{ le sum(self)->i32{ ret self.x + self.y; } }
+            le first(a:i32)->i32;
+            @fastcc le first(a:i32)->i32{ a - -1 }
+            le second(p:ref Point, n:i32)->f64{
+                var q = Point{ x: (1 + 2) * 3, y: p.y, data: [0.5, 1e20] };
+                for(var i = 0; i < n; i = i + 1;){ if(i == 2){ continue; } el{ ; } }
+                while(n > 0){ n = n - 1; break; } el{ puts(\"done\"); }
+                var m = if(n > 0){ var t = n * 2; t }el{ 0 };
+                var k = { var t = m; t + 1 } ? -m : (q.0).1 as i32;
+                ret q.data[1];
+            }";
+        let ast = parse_program(source).unwrap();
+        let printed = ast.to_source_string();
+        let reparsed = parse_program(&printed).unwrap();
+        assert_eq!(reparsed.extern_functions.len(), ast.extern_functions.len());
+        assert_eq!(reparsed.globals_variables.len(), ast.globals_variables.len());
+        assert_eq!(reparsed.globals_structures.len(), ast.globals_structures.len());
+        assert_eq!(reparsed.forward_declarations.len(), ast.forward_declarations.len());
+        assert_eq!(reparsed.function_definitions.len(), ast.function_definitions.len());
+        //再次输出得到相同的源代码，说明两次解析的语法树结构一致
+        assert_eq!(reparsed.to_source_string(), printed);
+    }
+}
@@ -7,7 +7,7 @@ use std::io::Write;
 use ptree::{Style, TreeBuilder, TreeItem};
 
 use crate::ast::parser::{
-    parse_extern_function_prototype, parse_function, parse_structure, parse_variable_declaration,
+    FunctionItem, parse_attributes, parse_extern_declarations, parse_function_item, parse_implement, parse_structure, parse_variable_declaration,
 };
 use crate::error::{LEError, Result, SyntaxError, TokenType};
 use crate::lexer::{KeyWord, LELexer, LEToken, Position};
@@ -34,6 +34,15 @@ pub struct BinaryOpExpression {
     pub pos: Position,
 }
 
+/// 复合赋值`a op= b`，左值只求值一次
+#[derive(Debug, Clone)]
+pub struct CompoundAssignExpression {
+    pub op: Operator,
+    pub target: Box<Expr>,
+    pub value: Box<Expr>,
+    pub pos: Position,
+}
+
 #[derive(Debug, Clone)]
 pub struct FunctionCall {
     pub function_name: Identifier,
@@ -43,10 +52,11 @@ pub struct FunctionCall {
 
 #[derive(Debug, Clone)]
 pub struct ForLoop {
-    pub init_statement: Box<Statement>,
+    pub init_statements: Vec<Statement>,
     pub condition: Box<Statement>,
-    pub iterate: Box<Statement>,
+    pub iterate: Vec<Statement>,
     pub code_block: CodeBlock,
+    pub else_block: Option<CodeBlock>,
     pub pos: Position,
 }
 
@@ -54,6 +64,7 @@ pub struct ForLoop {
 pub struct WhileLoop {
     pub condition: Box<Expr>,
     pub code_block: CodeBlock,
+    pub else_block: Option<CodeBlock>,
     pub pos: Position,
 }
 
@@ -62,6 +73,15 @@ pub struct FunctionPrototype {
     pub identifier: Identifier,
     pub param_types: Vec<TypeDeclarator>,
     pub return_type: Option<TypeDeclarator>,
+    pub attributes: Vec<Attribute>,
+    pub pos: Position,
+}
+
+/// 函数或结构体的属性，如`@fastcc`、`@repr(C)`
+#[derive(Debug, Clone)]
+pub struct Attribute {
+    pub name: Identifier,
+    pub params: Vec<Identifier>,
     pub pos: Position,
 }
 
@@ -87,6 +107,43 @@ pub struct IfStatement {
     pub pos: Position,
 }
 
+/// 作为表达式使用的if，两个分支都必须存在，分支中最后一个没有分号的表达式为该分支的值
+/// ```text
+/// var m = if(c){ a }el{ b };
+/// ```
+#[derive(Debug, Clone)]
+pub struct IfExpression {
+    pub cond: Box<Expr>,
+    pub then_block: CodeBlock,
+    pub then_value: Box<Expr>,
+    pub else_block: CodeBlock,
+    pub else_value: Box<Expr>,
+    pub pos: Position,
+}
+
+/// 三元条件表达式，两个分支都没有副作用时生成select指令而不是分支
+/// ```text
+/// var m = c ? a : b;
+/// ```
+#[derive(Debug, Clone)]
+pub struct TernaryExpression {
+    pub cond: Box<Expr>,
+    pub then_value: Box<Expr>,
+    pub else_value: Box<Expr>,
+    pub pos: Position,
+}
+
+/// 块表达式，块中最后一个没有分号的表达式为块的值，块内定义的变量只在块中可见
+/// ```text
+/// var x = { var t = a * 2; t + 1 };
+/// ```
+#[derive(Debug, Clone)]
+pub struct BlockExpression {
+    pub code_block: CodeBlock,
+    pub value: Box<Expr>,
+    pub pos: Position,
+}
+
 #[derive(Debug, Clone)]
 pub struct NumberLiteral {
     pub number: Number,
@@ -104,6 +161,8 @@ pub struct UnaryOpExpression {
 pub struct Variable {
     pub prototype: VariablePrototype,
     pub value: Box<Expr>,
+    /// 全局变量的属性，如`@const`
+    pub attributes: Vec<Attribute>,
     pub pos: Position,
 }
 
@@ -126,6 +185,39 @@ pub struct ArrayInitializer {
     pub pos: Position,
 }
 
+/// 将一个元素重复多次的数组，元素只求值一次
+/// ```text
+/// [value; N]
+/// ```
+#[derive(Debug, Clone)]
+pub struct ArrayRepeat {
+    pub element: Box<Expr>,
+    pub count: u32,
+    pub pos: Position,
+}
+
+/// 数组下标访问
+/// ```text
+/// arr[i]
+/// ```
+#[derive(Debug, Clone)]
+pub struct IndexExpression {
+    pub target: Box<Expr>,
+    pub index: Box<Expr>,
+    pub pos: Position,
+}
+
+/// 通过函数指针调用函数
+/// ```text
+/// table[i](x)
+/// ```
+#[derive(Debug, Clone)]
+pub struct IndirectCall {
+    pub callee: Box<Expr>,
+    pub params: Vec<Expr>,
+    pub pos: Position,
+}
+
 #[derive(Debug, Clone)]
 pub struct ArrayDeclarator {
     pub element_type: TypeDeclarator,
@@ -133,10 +225,19 @@ pub struct ArrayDeclarator {
     pub pos: Position,
 }
 
+/// 向量类型`vec<T, N>`，元素类型只能是整数或浮点数
+#[derive(Debug, Clone)]
+pub struct VectorDeclarator {
+    pub element_type: TypeDeclarator,
+    pub len: u64,
+    pub pos: Position,
+}
+
 #[derive(Debug, Clone)]
 pub struct Structure {
     pub identifier: Identifier,
     pub members: Vec<(String, TypeDeclarator)>,
+    pub attributes: Vec<Attribute>,
     pub pos: Position,
 }
 
@@ -144,6 +245,8 @@ pub struct Structure {
 pub struct StructureInitializer {
     pub structure_name: Identifier,
     pub member_initial_values: Vec<(String, Box<Expr>)>,
+    /// `Point{ ..base, x: 5 }`中的`base`，未被显式初始化的成员从该值复制
+    pub base: Option<Box<Expr>>,
     pub pos: Position,
 }
 
@@ -153,11 +256,36 @@ pub struct StringLiteral {
     pub pos: Position,
 }
 
+/// 内联汇编表达式
+/// ```text
+/// asm("nop", "")
+/// asm("mov $1, $0", "=r,r", a) -> i32
+/// ```
+#[derive(Debug, Clone)]
+pub struct InlineAsm {
+    pub assembly: String,
+    pub constraints: String,
+    pub args: Vec<Expr>,
+    pub return_type: Option<TypeDeclarator>,
+    pub pos: Position,
+}
+
+/// volatile修饰的左值表达式，对其的读写不会被优化
+/// ```text
+/// volatile x = 1;
+/// ```
+#[derive(Debug, Clone)]
+pub struct VolatileExpression {
+    pub expr: Box<Expr>,
+    pub pos: Position,
+}
+
 #[derive(Debug, Clone)]
 pub enum TypeDeclarator {
     TypeIdentifier(Identifier),
     Array(Box<ArrayDeclarator>),
     Reference(Box<TypeDeclarator>),
+    Vector(Box<VectorDeclarator>),
 }
 
 #[derive(Debug, Clone)]
@@ -166,20 +294,38 @@ pub enum Expr {
     UnaryOperator(UnaryOpExpression),
     NumberLiteral(NumberLiteral),
     ArrayInitializer(ArrayInitializer),
+    ArrayRepeat(ArrayRepeat),
     StructureInitializer(StructureInitializer),
     StringLiteral(StringLiteral),
     Identifier(Identifier),
     CallExpression(FunctionCall),
+    InlineAsm(InlineAsm),
+    Volatile(VolatileExpression),
+    If(IfExpression),
+    Ternary(TernaryExpression),
+    Block(BlockExpression),
+    Index(IndexExpression),
+    IndirectCall(IndirectCall),
+    /// 类型转换的目标类型，只出现在`as`的右侧
+    Type(TypeDeclarator),
+    /// 作为表达式的while循环，值由`break value;`给出
+    While(WhileLoop),
+    CompoundAssign(CompoundAssignExpression),
 }
 
 #[derive(Debug, Clone)]
 pub enum Statement {
     Expressions(Box<Expr>),
     VariableDefinition(Variable),
-    Return(Box<Expr>),
+    Return(Option<Box<Expr>>, Position),
     If(IfStatement),
     ForLoop(ForLoop),
     WhileLoop(WhileLoop),
+    /// 循环表达式中的`break value;`带有循环的值
+    Break(Option<Box<Expr>>, Position),
+    Continue(Position),
+    /// 标记执行不会到达的位置，生成LLVM的`unreachable`指令
+    Unreachable(Position),
     Void(Position),
 }
 
@@ -193,6 +339,40 @@ impl ASTNode for StringLiteral {
     }
 }
 
+impl ASTNode for InlineAsm {
+    fn pos(&self) -> Position {
+        self.pos.clone()
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        builder.add_empty_child(format!("assembly: `{}`", self.assembly));
+        builder.add_empty_child(format!("constraints: `{}`", self.constraints));
+        builder.begin_child("args".to_string());
+        for arg in &self.args {
+            arg.build_tree_format(builder);
+        }
+        builder.end_child();
+        builder.begin_child("return_type".to_string());
+        if let Some(ret) = &self.return_type {
+            ret.build_tree_format(builder);
+        } else {
+            builder.add_empty_child("void".to_string());
+        }
+        builder.end_child();
+    }
+}
+
+impl ASTNode for VolatileExpression {
+    fn pos(&self) -> Position {
+        self.pos.clone()
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        self.expr.build_tree_format(builder);
+    }
+}
+
+
 impl ASTNode for ArrayInitializer {
     fn pos(&self) -> Position {
         self.pos.clone()
@@ -207,20 +387,63 @@ impl ASTNode for ArrayInitializer {
     }
 }
 
+impl ASTNode for ArrayRepeat {
+    fn pos(&self) -> Position {
+        self.pos.clone()
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        builder.begin_child("element".to_string());
+        self.element.build_tree_format(builder);
+        builder.end_child();
+
+        builder.begin_child("count".to_string());
+        builder.add_empty_child(self.count.to_string());
+        builder.end_child();
+    }
+}
+
+impl ASTNode for IndexExpression {
+    fn pos(&self) -> Position {
+        self.pos.clone()
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        builder.begin_child("target".to_string());
+        self.target.build_tree_format(builder);
+        builder.end_child();
+
+        builder.begin_child("index".to_string());
+        self.index.build_tree_format(builder);
+        builder.end_child();
+    }
+}
+
+impl ASTNode for IndirectCall {
+    fn pos(&self) -> Position {
+        self.pos.clone()
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        builder.begin_child("callee".to_string());
+        self.callee.build_tree_format(builder);
+        builder.end_child();
+
+        builder.begin_child("params".to_string());
+        for param in self.params.iter() {
+            param.build_tree_format(builder);
+        }
+        builder.end_child();
+    }
+}
+
 impl ASTNode for NumberLiteral {
     fn pos(&self) -> Position {
         self.pos.clone()
     }
 
     fn build_tree_format(&self, builder: &mut TreeBuilder) {
-        builder.add_empty_child(match self.number {
-            Number::Integer(v) => {
-                format!("`{}`", v)
-            }
-            Number::Float(v) => {
-                format!("`{}`", v)
-            }
-        });
+        builder.add_empty_child(format!("`{}`", self.number));
     }
 }
 
@@ -241,6 +464,12 @@ impl ASTNode for StructureInitializer {
             builder.end_child();
         }
         builder.end_child();
+
+        if let Some(base) = &self.base {
+            builder.begin_child("base".to_string());
+            base.build_tree_format(builder);
+            builder.end_child();
+        }
     }
 }
 
@@ -255,16 +484,26 @@ impl ASTNode for ForLoop {
         builder.end_child();
 
         builder.begin_child("init".to_string());
-        self.init_statement.build_tree_format(builder);
+        for statement in &self.init_statements {
+            statement.build_tree_format(builder);
+        }
         builder.end_child();
 
         builder.begin_child("iterate".to_string());
-        self.iterate.build_tree_format(builder);
+        for statement in &self.iterate {
+            statement.build_tree_format(builder);
+        }
         builder.end_child();
 
         builder.begin_child("code_block".to_string());
         self.code_block.build_tree_format(builder);
         builder.end_child();
+
+        if let Some(else_block) = &self.else_block {
+            builder.begin_child("else_block".to_string());
+            else_block.build_tree_format(builder);
+            builder.end_child();
+        }
     }
 }
 
@@ -304,6 +543,22 @@ impl ASTNode for ArrayDeclarator {
     }
 }
 
+impl ASTNode for VectorDeclarator {
+    fn pos(&self) -> Position {
+        self.pos.clone()
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        builder.begin_child("element_type".to_string());
+        self.element_type.build_tree_format(builder);
+        builder.end_child();
+
+        builder.begin_child("length".to_string());
+        builder.add_empty_child(self.len.to_string());
+        builder.end_child();
+    }
+}
+
 impl ASTNode for Identifier {
     fn pos(&self) -> Position {
         self.pos.clone()
@@ -343,6 +598,14 @@ impl ASTNode for Variable {
         builder.begin_child("value".to_string());
         self.value.build_tree_format(builder);
         builder.end_child();
+
+        if !self.attributes.is_empty() {
+            builder.begin_child("attributes".to_string());
+            for attribute in &self.attributes {
+                attribute.build_tree_format(builder);
+            }
+            builder.end_child();
+        }
     }
 }
 
@@ -384,6 +647,61 @@ impl ASTNode for IfStatement {
     }
 }
 
+impl ASTNode for IfExpression {
+    fn pos(&self) -> Position {
+        self.pos.clone()
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        builder.begin_child("condition".to_string());
+        self.cond.build_tree_format(builder);
+        builder.end_child();
+
+        builder.begin_child("then_block".to_string());
+        self.then_block.build_tree_format(builder);
+        self.then_value.build_tree_format(builder);
+        builder.end_child();
+
+        builder.begin_child("else_block".to_string());
+        self.else_block.build_tree_format(builder);
+        self.else_value.build_tree_format(builder);
+        builder.end_child();
+    }
+}
+
+impl ASTNode for TernaryExpression {
+    fn pos(&self) -> Position {
+        self.pos.clone()
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        builder.begin_child("condition".to_string());
+        self.cond.build_tree_format(builder);
+        builder.end_child();
+
+        builder.begin_child("then_value".to_string());
+        self.then_value.build_tree_format(builder);
+        builder.end_child();
+
+        builder.begin_child("else_value".to_string());
+        self.else_value.build_tree_format(builder);
+        builder.end_child();
+    }
+}
+
+impl ASTNode for BlockExpression {
+    fn pos(&self) -> Position {
+        self.pos.clone()
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        self.code_block.build_tree_format(builder);
+        builder.begin_child("value".to_string());
+        self.value.build_tree_format(builder);
+        builder.end_child();
+    }
+}
+
 impl ASTNode for CodeBlock {
     fn pos(&self) -> Position {
         self.pos.clone()
@@ -443,6 +761,52 @@ impl ASTNode for FunctionPrototype {
             builder.add_empty_child("void".to_string());
         }
         builder.end_child();
+
+        if !self.attributes.is_empty() {
+            builder.begin_child("attributes".to_string());
+            for attribute in &self.attributes {
+                attribute.build_tree_format(builder);
+            }
+            builder.end_child();
+        }
+    }
+}
+
+impl ASTNode for Attribute {
+    fn pos(&self) -> Position {
+        self.pos.clone()
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        if self.params.is_empty() {
+            builder.add_empty_child(format!("@{}", self.name.name));
+        } else {
+            builder.begin_child(format!("@{}", self.name.name));
+            for param in &self.params {
+                param.build_tree_format(builder);
+            }
+            builder.end_child();
+        }
+    }
+}
+
+impl ASTNode for CompoundAssignExpression {
+    fn pos(&self) -> Position {
+        self.pos.clone()
+    }
+
+    fn build_tree_format(&self, builder: &mut TreeBuilder) {
+        builder.begin_child("target".to_string());
+        self.target.build_tree_format(builder);
+        builder.end_child();
+
+        builder.begin_child("operator".to_string());
+        builder.add_empty_child(format!("` {}= `", self.op));
+        builder.end_child();
+
+        builder.begin_child("value".to_string());
+        self.value.build_tree_format(builder);
+        builder.end_child();
     }
 }
 
@@ -497,6 +861,12 @@ impl ASTNode for WhileLoop {
         builder.begin_child("body".to_string());
         self.code_block.build_tree_format(builder);
         builder.end_child();
+
+        if let Some(else_block) = &self.else_block {
+            builder.begin_child("else_block".to_string());
+            else_block.build_tree_format(builder);
+            builder.end_child();
+        }
     }
 }
 
@@ -506,6 +876,7 @@ impl ASTNode for TypeDeclarator {
             TypeDeclarator::TypeIdentifier(e) => e.pos(),
             TypeDeclarator::Array(e) => e.pos(),
             TypeDeclarator::Reference(e) => e.pos(),
+            TypeDeclarator::Vector(e) => e.pos(),
         }
     }
 
@@ -526,6 +897,11 @@ impl ASTNode for TypeDeclarator {
                 t.build_tree_format(builder);
                 builder.end_child();
             }
+            TypeDeclarator::Vector(t) => {
+                builder.begin_child("vector_type".to_string());
+                t.build_tree_format(builder);
+                builder.end_child();
+            }
         };
     }
 }
@@ -537,10 +913,21 @@ impl ASTNode for Expr {
             Expr::UnaryOperator(e) => e.pos(),
             Expr::NumberLiteral(e) => e.pos(),
             Expr::ArrayInitializer(e) => e.pos(),
+            Expr::ArrayRepeat(e) => e.pos(),
             Expr::StructureInitializer(e) => e.pos(),
             Expr::StringLiteral(e) => e.pos(),
             Expr::Identifier(e) => e.pos(),
             Expr::CallExpression(e) => e.pos(),
+            Expr::InlineAsm(e) => e.pos(),
+            Expr::Volatile(e) => e.pos(),
+            Expr::If(e) => e.pos(),
+            Expr::Ternary(e) => e.pos(),
+            Expr::Block(e) => e.pos(),
+            Expr::Index(e) => e.pos(),
+            Expr::IndirectCall(e) => e.pos(),
+            Expr::Type(e) => e.pos(),
+            Expr::While(e) => e.pos(),
+            Expr::CompoundAssign(e) => e.pos(),
         }
     }
 
@@ -566,6 +953,11 @@ impl ASTNode for Expr {
                 e.build_tree_format(builder);
                 builder.end_child();
             }
+            Expr::ArrayRepeat(e) => {
+                builder.begin_child("array_repeat".to_string());
+                e.build_tree_format(builder);
+                builder.end_child();
+            }
             Expr::StructureInitializer(e) => {
                 builder.begin_child("struct_initializer".to_string());
                 e.build_tree_format(builder);
@@ -586,6 +978,56 @@ impl ASTNode for Expr {
                 e.build_tree_format(builder);
                 builder.end_child();
             }
+            Expr::InlineAsm(e) => {
+                builder.begin_child("inline_asm".to_string());
+                e.build_tree_format(builder);
+                builder.end_child();
+            }
+            Expr::Volatile(e) => {
+                builder.begin_child("volatile".to_string());
+                e.build_tree_format(builder);
+                builder.end_child();
+            }
+            Expr::If(e) => {
+                builder.begin_child("if_expr".to_string());
+                e.build_tree_format(builder);
+                builder.end_child();
+            }
+            Expr::Ternary(e) => {
+                builder.begin_child("ternary_expr".to_string());
+                e.build_tree_format(builder);
+                builder.end_child();
+            }
+            Expr::Block(e) => {
+                builder.begin_child("block_expr".to_string());
+                e.build_tree_format(builder);
+                builder.end_child();
+            }
+            Expr::Index(e) => {
+                builder.begin_child("index".to_string());
+                e.build_tree_format(builder);
+                builder.end_child();
+            }
+            Expr::IndirectCall(e) => {
+                builder.begin_child("indirect_call".to_string());
+                e.build_tree_format(builder);
+                builder.end_child();
+            }
+            Expr::Type(e) => {
+                builder.begin_child("type".to_string());
+                e.build_tree_format(builder);
+                builder.end_child();
+            }
+            Expr::While(e) => {
+                builder.begin_child("while_expr".to_string());
+                e.build_tree_format(builder);
+                builder.end_child();
+            }
+            Expr::CompoundAssign(e) => {
+                builder.begin_child("compound_assign_expr".to_string());
+                e.build_tree_format(builder);
+                builder.end_child();
+            }
         }
     }
 }
@@ -595,10 +1037,13 @@ impl ASTNode for Statement {
         match self {
             Statement::Expressions(e) => e.pos(),
             Statement::VariableDefinition(e) => e.pos(),
-            Statement::Return(e) => e.pos(),
+            Statement::Return(_, p) => p.clone(),
             Statement::If(e) => e.pos(),
             Statement::ForLoop(e) => e.pos(),
             Statement::WhileLoop(e) => e.pos(),
+            Statement::Break(_, p) => p.clone(),
+            Statement::Continue(p) => p.clone(),
+            Statement::Unreachable(p) => p.clone(),
             Statement::Void(p) => p.clone(),
         }
     }
@@ -615,9 +1060,11 @@ impl ASTNode for Statement {
                 s.build_tree_format(builder);
                 builder.end_child();
             }
-            Statement::Return(s) => {
+            Statement::Return(s, _) => {
                 builder.begin_child("return_expr".to_string());
-                s.build_tree_format(builder);
+                if let Some(s) = s {
+                    s.build_tree_format(builder);
+                }
                 builder.end_child();
             }
             Statement::If(s) => {
@@ -635,6 +1082,20 @@ impl ASTNode for Statement {
                 s.build_tree_format(builder);
                 builder.end_child();
             }
+            Statement::Break(None, _) => {
+                builder.add_empty_child("break".to_string());
+            }
+            Statement::Break(Some(value), _) => {
+                builder.begin_child("break".to_string());
+                value.build_tree_format(builder);
+                builder.end_child();
+            }
+            Statement::Continue(_) => {
+                builder.add_empty_child("continue".to_string());
+            }
+            Statement::Unreachable(_) => {
+                builder.add_empty_child("unreachable".to_string());
+            }
             Statement::Void(_) => {
                 builder.add_empty_child("void statement".to_string());
             }
@@ -642,12 +1103,14 @@ impl ASTNode for Statement {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Ast {
     pub globals_variables: Vec<Variable>,
     pub globals_structures: Vec<Structure>,
     pub function_definitions: Vec<FunctionDefinition>,
     pub extern_functions: Vec<FunctionPrototype>,
+    /// 文件内没有函数体的函数声明，定义可以出现在其后
+    pub forward_declarations: Vec<FunctionPrototype>,
 }
 
 impl Ast {
@@ -657,11 +1120,21 @@ impl Ast {
             globals_structures: vec![],
             function_definitions: vec![],
             extern_functions: vec![],
+            forward_declarations: vec![],
         };
         ast.parse(tokens)?;
         Ok(ast)
     }
 
+    /// 将另一个源文件的顶层定义合并进来，重复的定义在生成代码时与同一文件中的重复定义相同地报错
+    pub fn merge(&mut self, other: Ast) {
+        self.globals_variables.extend(other.globals_variables);
+        self.globals_structures.extend(other.globals_structures);
+        self.function_definitions.extend(other.function_definitions);
+        self.extern_functions.extend(other.extern_functions);
+        self.forward_declarations.extend(other.forward_declarations);
+    }
+
     fn parse(&mut self, mut lexer: LELexer) -> Result<()> {
         loop {
             let next_token = lexer.current();
@@ -669,19 +1142,64 @@ impl Ast {
                 None => {
                     break;
                 }
+                Some(LEToken::At) => {
+                    //属性只能用于函数定义、函数声明、结构体和全局变量
+                    let attributes = parse_attributes(&mut lexer)?;
+                    match lexer.current() {
+                        Some(LEToken::KeyWord(KeyWord::VariableDeclare)) => {
+                            let mut variable = parse_variable_declaration(&mut lexer)?;
+                            lexer.consume_semicolon()?;
+                            variable.attributes = attributes;
+                            self.globals_variables.push(variable);
+                        }
+                        Some(LEToken::KeyWord(KeyWord::StructureDeclare)) => {
+                            let mut structure = parse_structure(&mut lexer)?;
+                            structure.attributes = attributes;
+                            self.globals_structures.push(structure);
+                        }
+                        Some(LEToken::KeyWord(KeyWord::Declare)) => {
+                            //声明块前的属性作用于块内所有声明
+                            for mut function_prototype in parse_extern_declarations(&mut lexer)? {
+                                function_prototype.attributes = attributes.clone();
+                                self.extern_functions.push(function_prototype);
+                            }
+                        }
+                        Some(LEToken::KeyWord(KeyWord::FunctionDefine)) => {
+                            match parse_function_item(&mut lexer)? {
+                                FunctionItem::Definition(mut function) => {
+                                    function.prototype.attributes = attributes;
+                                    self.function_definitions.push(function);
+                                }
+                                FunctionItem::ForwardDeclaration(mut function_prototype) => {
+                                    function_prototype.attributes = attributes;
+                                    self.forward_declarations.push(function_prototype);
+                                }
+                            }
+                        }
+                        Some(token) => {
+                            return Err(SyntaxError::unexpect_token(
+                                vec![TokenType::FunctionDefine, TokenType::FunctionDeclare],
+                                token,
+                            ).to_leerror(lexer.pos()));
+                        }
+                        None => {
+                            return Err(SyntaxError::missing_token(
+                                vec![TokenType::FunctionDefine, TokenType::FunctionDeclare],
+                            ).to_leerror(lexer.pos()));
+                        }
+                    }
+                }
                 Some(token) => {
                     if let LEToken::KeyWord(keyword) = token {
                         match keyword {
                             KeyWord::Declare => {
-                                lexer.consume_keyword()?;
-                                let function_prototype =
-                                    parse_extern_function_prototype(&mut lexer)?;
-                                lexer.consume_semicolon()?;
-                                self.extern_functions.push(function_prototype);
+                                self.extern_functions.extend(parse_extern_declarations(&mut lexer)?);
                             }
                             KeyWord::FunctionDefine => {
-                                let function = parse_function(&mut lexer)?;
-                                self.function_definitions.push(function);
+                                match parse_function_item(&mut lexer)? {
+                                    FunctionItem::Definition(function) => { self.function_definitions.push(function) }
+                                    FunctionItem::ForwardDeclaration(function_prototype) => { self.forward_declarations.push(function_prototype) }
+                                }
                             }
                             KeyWord::VariableDeclare => {
                                 let variable = parse_variable_declaration(&mut lexer)?;
@@ -692,6 +1210,10 @@ impl Ast {
                                 let structure = parse_structure(&mut lexer)?;
                                 self.globals_structures.push(structure);
                             }
+                            KeyWord::Implement => {
+                                let methods = parse_implement(&mut lexer)?;
+                                self.function_definitions.extend(methods);
+                            }
                             _ => {
                                 return Err(LEError::new_syntax_error(
                                     SyntaxError::unexpect_token(
@@ -727,6 +1249,14 @@ impl Ast {
         }
         builder_ref.end_child();
 
+        builder_ref.begin_child("forward declarations".to_string());
+        for (index, f) in self.forward_declarations.iter().enumerate() {
+            builder_ref.begin_child(index.to_string());
+            f.build_tree_format(builder_ref);
+            builder_ref.end_child();
+        }
+        builder_ref.end_child();
+
         builder_ref.begin_child("function_definitions".to_string());
         for (index, f) in self.function_definitions.iter().enumerate() {
             builder_ref.begin_child(index.to_string());
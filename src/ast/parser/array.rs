@@ -1,4 +1,4 @@
-use crate::ast::nodes::{ArrayDeclarator, ArrayInitializer, Expr, FunctionCall};
+use crate::ast::nodes::{ArrayDeclarator, ArrayInitializer, ArrayRepeat, Expr, FunctionCall};
 use crate::ast::parser::{parse_call_expression, parse_expression};
 use crate::ast::parser::type_declarator::parse_type_declarator;
 use crate::error::{LEError, Result};
@@ -23,21 +23,24 @@ pub fn parse_array_initializer(lexer: &mut LELexer) -> Result<Box<Expr>> {
             LEToken::Comma => {
                 lexer.consume();
             }
-            // LEToken::Semicolon=>{
-            //     lexer.consume();
-            //     let number = lexer.consume_number_literal()?;
-            //     lexer.consume_right_bracket()?;
-            //     let elements =  match number {
-            //         Number::Integer(i) => {vec![elements[0]; i as usize]}
-            //         Number::Float(f) => {
-            //            return Err(LEError::new_syntax_error(
-            //                SyntaxError::ArraySizeMustBeInteger,
-            //                lexer.pos(),
-            //            ));
-            //         }
-            //     };
-            //     return Ok(Box::new(Expr::ArrayInitializer(ArrayInitializer { elements, pos: start_pos.sum(&lexer.pos()) })))
-            // }
+            //`[value; N]`将value重复N次
+            LEToken::Semicolon if elements.len() == 1 => {
+                lexer.consume();
+                let count_pos = lexer.pos();
+                let count = lexer.consume_number_literal()?;
+                lexer.consume_right_bracket()?;
+                let count = match count {
+                    Number::Integer(count, None) if count <= u32::MAX as u64 => { count as u32 }
+                    other => {
+                        return Err(LEError::new_syntax_error(
+                            SyntaxError::unexpect_token(vec![TokenType::NumberLiteral], LEToken::NumberLiteral(other)),
+                            count_pos,
+                        ));
+                    }
+                };
+                let element = Box::new(elements.pop().unwrap());
+                return Ok(Box::new(Expr::ArrayRepeat(ArrayRepeat { element, count, pos: start_pos.sum(&lexer.pos()) })));
+            }
             _ => {
                 elements.push(*parse_expression(lexer)?);
             }
@@ -53,7 +56,7 @@ pub fn parse_array_declarator(lexer: &mut LELexer) -> Result<ArrayDeclarator> {
     let element_type = parse_type_declarator(lexer)?;
     lexer.consume_semicolon()?;
     let len = lexer.consume_number_literal()?;
-    if let Number::Integer(len) = len {
+    if let Number::Integer(len, _) = len {
         lexer.consume_right_bracket()?;
         Ok(ArrayDeclarator {
             element_type,
@@ -0,0 +1,45 @@
+use crate::ast::nodes::{Attribute, Identifier};
+use crate::error::{LEError, Result};
+use crate::error::{SyntaxError, TokenType};
+use crate::lexer::{LELexer, LEToken};
+
+/// 解析函数定义或声明前的属性列表，没有属性时返回空列表
+/// ```text
+/// @fastcc
+/// @cfg(debug)
+/// le foo(){}
+/// ```
+pub fn parse_attributes(lexer: &mut LELexer) -> Result<Vec<Attribute>> {
+    let mut attributes = vec![];
+    while let Some(LEToken::At) = lexer.current() {
+        let start_pos = lexer.pos();
+        lexer.consume();
+        let name_pos = lexer.pos();
+        let name = Identifier { name: lexer.consume_identifier()?, pos: name_pos };
+        let mut params = vec![];
+        if let Some(LEToken::LeftPar) = lexer.current() {
+            lexer.consume_left_par()?;
+            loop {
+                let current_token = lexer.current().ok_or_else(|| LEError::new_syntax_error(
+                    SyntaxError::missing_token(vec![TokenType::RightPar, TokenType::Comma]),
+                    lexer.pos(),
+                ))?;
+                match current_token {
+                    LEToken::RightPar => {
+                        lexer.consume_right_par()?;
+                        break;
+                    }
+                    LEToken::Comma => {
+                        lexer.consume();
+                    }
+                    _ => {
+                        let param_pos = lexer.pos();
+                        params.push(Identifier { name: lexer.consume_identifier()?, pos: param_pos });
+                    }
+                }
+            }
+        }
+        attributes.push(Attribute { name, params, pos: start_pos.sum(&lexer.last_pos()) });
+    }
+    Ok(attributes)
+}
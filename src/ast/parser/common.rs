@@ -1,11 +1,26 @@
-use crate::ast::nodes::{ASTNode, BinaryOpExpression, CodeBlock, Expr, FunctionCall, FunctionDefinition, Identifier, NumberLiteral, Structure, StructureInitializer, TypeDeclarator, UnaryOpExpression};
+use crate::ast::nodes::{ASTNode, BinaryOpExpression, CodeBlock, CompoundAssignExpression, Expr, FunctionCall, FunctionDefinition, Identifier, IndexExpression, IndirectCall, NumberLiteral, Statement, StringLiteral, Structure, StructureInitializer, TernaryExpression, TypeDeclarator, UnaryOpExpression, VolatileExpression};
 use crate::ast::parser::{parse_anonymous_function, parse_structure_initializer};
 use crate::ast::parser::array::parse_array_initializer;
+use crate::ast::parser::if_statement::{parse_block_expression, parse_if_expression};
+use crate::ast::parser::inline_asm::parse_inline_asm;
 use crate::ast::parser::statement::parse_statement;
 use crate::ast::parser::type_declarator::parse_type_declarator;
+use crate::ast::parser::while_loop::parse_while_loop;
 use crate::error::{LEError, SyntaxError, TokenType};
 use crate::error::Result;
-use crate::lexer::{KeyWord, LELexer, LEToken, Operator, Position};
+use crate::lexer::{KeyWord, LELexer, LEToken, Number, Operator, Position};
+
+/// 表达式允许的最大嵌套层数，过深的AST在解析、生成代码和析构时都会耗尽栈空间
+pub const MAX_EXPRESSION_NESTING: usize = 256;
+
+/// 进入一层表达式嵌套，超过上限时报告语法错误
+fn enter_nesting(lexer: &mut LELexer) -> Result<()> {
+    if lexer.nesting_depth() >= MAX_EXPRESSION_NESTING {
+        return Err(SyntaxError::ExpressionTooDeep { limit: MAX_EXPRESSION_NESTING }.to_leerror(lexer.pos()));
+    }
+    lexer.set_nesting_depth(lexer.nesting_depth() + 1);
+    Ok(())
+}
 
 fn get_operator_precedence(op: &Operator) -> usize {
     match op {
@@ -14,7 +29,7 @@ fn get_operator_precedence(op: &Operator) -> usize {
         Operator::Mul => { 40 }
         Operator::Mod => { 40 }
         Operator::Div => { 40 }
-        Operator::Assign => { 10 }
+        Operator::Assign => { 1 }
         Operator::Equal => { 10 }
         Operator::NotEqual => { 10 }
         Operator::GreaterThan => { 10 }
@@ -24,13 +39,20 @@ fn get_operator_precedence(op: &Operator) -> usize {
         Operator::Dot => { 60 }
         Operator::And => { 5 }
         Operator::Or => { 5 }
-        Operator::Xor => { 5 }
+        Operator::Xor => { 8 }
         Operator::Not => { 5 }
         Operator::Rev => { 5 }
         Operator::Cast => { 50 }
+        Operator::Concat => { 20 }
+        Operator::ShiftLeft => { 15 }
+        Operator::ShiftRight => { 15 }
+        Operator::BitAnd => { 9 }
+        Operator::BitOr => { 7 }
     }
 }
 
+/// 三元表达式的优先级，只高于赋值
+const TERNARY_PRECEDENCE: usize = 2;
 
 pub fn parse_annotation(lexer: &mut LELexer) -> Result<(String, TypeDeclarator)> {
     let identifier = lexer.consume_identifier()?;
@@ -41,6 +63,16 @@ pub fn parse_annotation(lexer: &mut LELexer) -> Result<(String, TypeDeclarator)>
 
 pub fn parse_call_expression(lexer: &mut LELexer, function_name: Identifier) -> Result<Box<Expr>> {
     let start_pos = lexer.pos();
+    let params = parse_call_arguments(lexer)?;
+    Ok(Box::new(Expr::CallExpression(FunctionCall {
+        function_name,
+        params,
+        pos: start_pos.sum(&lexer.pos()),
+    })))
+}
+
+/// 解析以括号包围、逗号分隔的实参列表
+fn parse_call_arguments(lexer: &mut LELexer) -> Result<Vec<Expr>> {
     lexer.consume();
     let mut params = vec![];
     loop {
@@ -49,11 +81,7 @@ pub fn parse_call_expression(lexer: &mut LELexer, function_name: Identifier) ->
         match current_token {
             LEToken::RightPar => {
                 lexer.consume();
-                return Ok(Box::new(Expr::CallExpression(FunctionCall {
-                    function_name,
-                    params,
-                    pos: start_pos.sum(&lexer.pos()),
-                })));
+                return Ok(params);
             }
             LEToken::Comma => {
                 lexer.consume();
@@ -66,20 +94,81 @@ pub fn parse_call_expression(lexer: &mut LELexer, function_name: Identifier) ->
 }
 
 
-pub fn parse_binary_ops(lexer: &mut LELexer, mut lhs: Box<Expr>, expression_precedence: usize) -> Result<Box<Expr>> {
+pub fn parse_binary_ops(lexer: &mut LELexer, lhs: Box<Expr>, expression_precedence: usize) -> Result<Box<Expr>> {
+    //每合并一个运算符，左侧的AST就深一层，结束时恢复进入时的层数
+    let depth = lexer.nesting_depth();
+    let result = fold_binary_ops(lexer, lhs, expression_precedence);
+    lexer.set_nesting_depth(depth);
+    result
+}
+
+fn fold_binary_ops(lexer: &mut LELexer, mut lhs: Box<Expr>, expression_precedence: usize) -> Result<Box<Expr>> {
     let lhs_pos = lhs.pos();
     loop {
         let current_token = lexer.current().ok_or(
             LEError::new_syntax_error(SyntaxError::missing_token(vec![TokenType::RightPar, TokenType::Comma]), lexer.pos())
         )?;
+        //三元表达式为右结合，a ? b : c ? d : e 解析为 a ? b : (c ? d : e)
+        if let LEToken::Question = current_token {
+            if TERNARY_PRECEDENCE < expression_precedence {
+                return Ok(lhs);
+            }
+            lexer.consume();
+            enter_nesting(lexer)?;
+            let then_value = parse_expression(lexer)?;
+            lexer.consume_colon()?;
+            let else_value = parse_primary_expression(lexer)?;
+            let else_value = parse_binary_ops(lexer, else_value, TERNARY_PRECEDENCE)?;
+            let else_pos = else_value.pos();
+            lhs = Box::new(Expr::Ternary(TernaryExpression {
+                cond: lhs,
+                then_value,
+                else_value,
+                pos: lhs_pos.sum(&else_pos),
+            }));
+            continue;
+        }
+        //复合赋值与赋值的优先级相同，也是右结合
+        if let LEToken::CompoundAssign(op) = current_token {
+            if get_operator_precedence(&Operator::Assign) < expression_precedence {
+                return Ok(lhs);
+            }
+            lexer.consume();
+            enter_nesting(lexer)?;
+            let rhs = parse_primary_expression(lexer)?;
+            let rhs = parse_binary_ops(lexer, rhs, get_operator_precedence(&Operator::Assign))?;
+            let pos = lhs.pos().sum(&rhs.pos());
+            lhs = Box::new(Expr::CompoundAssign(CompoundAssignExpression { op, target: lhs, value: rhs, pos }));
+            continue;
+        }
+        //`a.0`中的`.0`被词法分析为浮点数字面量，作为按下标访问成员处理
+        if let Some(index) = member_index_literal(lexer) {
+            if get_operator_precedence(&Operator::Dot) < expression_precedence {
+                return Ok(lhs);
+            }
+            let index_pos = lexer.pos();
+            lexer.consume();
+            enter_nesting(lexer)?;
+            let rhs = Box::new(Expr::NumberLiteral(NumberLiteral { number: Number::Integer(index, None), pos: index_pos.clone() }));
+            lhs = Box::new(Expr::BinaryOperator(BinaryOpExpression {
+                op: Operator::Dot,
+                left: lhs,
+                right: rhs,
+                pos: lhs_pos.sum(&index_pos),
+            }));
+            continue;
+        }
         if let LEToken::Operator(op) = current_token {
             let precedence = get_operator_precedence(&op);
             if precedence < expression_precedence {
                 return Ok(lhs);
             }
             lexer.consume();
-            let mut rhs = parse_primary_expression(lexer)?;
-            rhs = parse_binary_ops(lexer, rhs, precedence + 1)?;
+            enter_nesting(lexer)?;
+            let mut rhs = if let Operator::Cast = op { parse_cast_target(lexer)? } else { parse_primary_expression(lexer)? };
+            //赋值运算符为右结合，a = b = c 解析为 a = (b = c)
+            let rhs_precedence = if let Operator::Assign = op { precedence } else { precedence + 1 };
+            rhs = parse_binary_ops(lexer, rhs, rhs_precedence)?;
             let rhs_pos = rhs.pos();
             lhs = Box::new(Expr::BinaryOperator(BinaryOpExpression {
                 op: op.clone(),
@@ -93,6 +182,22 @@ pub fn parse_binary_ops(lexer: &mut LELexer, mut lhs: Box<Expr>, expression_prec
     }
 }
 
+/// 当前token为`.0`这样以点开头的数字字面量时，返回点之后的整数
+fn member_index_literal(lexer: &LELexer) -> Option<u64> {
+    match lexer.current() {
+        Some(LEToken::NumberLiteral(Number::Float(_, None))) => { lexer.current_slice().strip_prefix('.')?.parse().ok() }
+        _ => { None }
+    }
+}
+
+/// 解析`as`右侧的目标类型，类型名仍作为标识符，`ref T`作为类型表达式
+fn parse_cast_target(lexer: &mut LELexer) -> Result<Box<Expr>> {
+    if let Some(LEToken::KeyWord(KeyWord::Ref)) = lexer.current() {
+        return Ok(Box::new(Expr::Type(parse_type_declarator(lexer)?)));
+    }
+    parse_primary_expression(lexer)
+}
+
 pub fn parse_identifier_expression(lexer: &mut LELexer) -> Result<Box<Expr>> {
     let start_pos = lexer.pos();
     let identifier = Identifier { name: lexer.consume_identifier()?, pos: start_pos.clone() };
@@ -104,10 +209,11 @@ pub fn parse_identifier_expression(lexer: &mut LELexer) -> Result<Box<Expr>> {
             Ok(parse_call_expression(lexer, identifier)?)
         }
         LEToken::LeftBrace => {
-            let (initializer, pos) = parse_structure_initializer(lexer)?;
+            let (initializer, base, pos) = parse_structure_initializer(lexer)?;
             Ok(Box::new(Expr::StructureInitializer(StructureInitializer {
                 structure_name: identifier,
                 member_initial_values: initializer,
+                base,
                 pos: start_pos.sum(&pos),
             })))
         }
@@ -123,6 +229,12 @@ pub fn parse_number_expression(lexer: &mut LELexer) -> Result<Box<Expr>> {
     Ok(Box::new(Expr::NumberLiteral(NumberLiteral { number, pos: start_pos })))
 }
 
+pub fn parse_string_expression(lexer: &mut LELexer) -> Result<Box<Expr>> {
+    let start_pos = lexer.pos();
+    let content = lexer.consume_string_literal()?;
+    Ok(Box::new(Expr::StringLiteral(StringLiteral { content, pos: start_pos })))
+}
+
 pub fn parse_little_par_expression(lexer: &mut LELexer) -> Result<Box<Expr>> {
     lexer.consume_left_par()?;
     let expression = parse_expression(lexer)?;
@@ -134,13 +246,53 @@ pub fn parse_unary_ops(lexer: &mut LELexer) -> Result<Box<Expr>> {
     let start_pos = lexer.pos();
     let op = lexer.consume_operator()?;
     Ok(Box::new(Expr::UnaryOperator(UnaryOpExpression {
-        op: Operator::Sub,
+        op,
         expr: parse_primary_expression(lexer)?,
         pos: start_pos.sum(&lexer.pos()),
     })))
 }
 
+/// 解析`volatile`表达式，操作数包括其后的成员访问，`volatile p.x`作用于成员`p.x`
+pub fn parse_volatile_expression(lexer: &mut LELexer) -> Result<Box<Expr>> {
+    let start_pos = lexer.pos();
+    lexer.consume_keyword()?;
+    let expr = parse_primary_expression(lexer)?;
+    let expr = parse_binary_ops(lexer, expr, get_operator_precedence(&Operator::Dot))?;
+    let pos = start_pos.sum(&expr.pos());
+    Ok(Box::new(Expr::Volatile(VolatileExpression { expr, pos })))
+}
+
 pub fn parse_primary_expression(lexer: &mut LELexer) -> Result<Box<Expr>> {
+    let depth = lexer.nesting_depth();
+    let result = enter_nesting(lexer).and_then(|_| parse_postfix_expression(lexer));
+    lexer.set_nesting_depth(depth);
+    result
+}
+
+fn parse_postfix_expression(lexer: &mut LELexer) -> Result<Box<Expr>> {
+    let start_pos = lexer.pos();
+    let mut expr = parse_primary_operand(lexer)?;
+    //处理后缀的下标访问和通过函数指针的调用，如`arr[i][j]`、`table[i](x)`
+    loop {
+        match lexer.current() {
+            Some(LEToken::LeftBracket) => {
+                enter_nesting(lexer)?;
+                lexer.consume_left_bracket()?;
+                let index = parse_expression(lexer)?;
+                lexer.consume_right_bracket()?;
+                expr = Box::new(Expr::Index(IndexExpression { target: expr, index, pos: start_pos.sum(&lexer.last_pos()) }));
+            }
+            Some(LEToken::LeftPar) => {
+                enter_nesting(lexer)?;
+                let params = parse_call_arguments(lexer)?;
+                expr = Box::new(Expr::IndirectCall(IndirectCall { callee: expr, params, pos: start_pos.sum(&lexer.last_pos()) }));
+            }
+            _ => { return Ok(expr); }
+        }
+    }
+}
+
+fn parse_primary_operand(lexer: &mut LELexer) -> Result<Box<Expr>> {
     let current_token = lexer.current().ok_or(
         LEError::new_syntax_error(SyntaxError::missing_token(vec![TokenType::LeftPar, TokenType::LeftBrace]), lexer.pos())
     )?;
@@ -148,9 +300,12 @@ pub fn parse_primary_expression(lexer: &mut LELexer) -> Result<Box<Expr>> {
         LEToken::Operator(op) => {
             parse_unary_ops(lexer)
         }
-        LEToken::NumberLiteral(_) => {
+        LEToken::NumberLiteral(_) | LEToken::OverflowNumberLiteral(_) => {
             parse_number_expression(lexer)
         }
+        LEToken::StringLiteral(_) => {
+            parse_string_expression(lexer)
+        }
         LEToken::Identifier(_) => {
             parse_identifier_expression(lexer)
         }
@@ -158,6 +313,11 @@ pub fn parse_primary_expression(lexer: &mut LELexer) -> Result<Box<Expr>> {
             parse_array_initializer(lexer)
         }
         LEToken::LeftPar => { parse_little_par_expression(lexer) }
+        LEToken::KeyWord(KeyWord::Asm) => { parse_inline_asm(lexer) }
+        LEToken::KeyWord(KeyWord::Volatile) => { parse_volatile_expression(lexer) }
+        LEToken::KeyWord(KeyWord::If) => { parse_if_expression(lexer) }
+        LEToken::KeyWord(KeyWord::While) => { Ok(Box::new(Expr::While(parse_while_loop(lexer)?))) }
+        LEToken::LeftBrace => { parse_block_expression(lexer) }
         // LEToken::KeyWord(KeyWord::FunctionDefine)=>{ parse_anonymous_function(lexer) }
         _ => {
             Err(LEError::new_syntax_error(SyntaxError::unexpect_token(
@@ -172,15 +332,43 @@ pub fn parse_expression(lexer: &mut LELexer) -> Result<Box<Expr>> {
     parse_binary_ops(lexer, primary, 0)
 }
 
-pub fn parse_code_block(lexer: &mut LELexer) -> Result<CodeBlock> {
+/// 解析循环之后可选的el块，el块只在循环没有被break时执行
+pub fn parse_loop_else_block(lexer: &mut LELexer) -> Result<Option<CodeBlock>> {
+    if let Some(LEToken::KeyWord(KeyWord::Else)) = lexer.current() {
+        lexer.consume();
+        Ok(Some(parse_code_block(lexer, false)?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// 解析`{}`包围的代码块，`tail_return`为true时块中最后一个没有分号的表达式作为返回值，等价于`ret`语句
+pub fn parse_code_block(lexer: &mut LELexer, tail_return: bool) -> Result<CodeBlock> {
     let start_pos = lexer.pos();
     lexer.consume_left_brace()?;
     let mut statements = vec![];
     while let Some(current) = lexer.current() {
-        if current == LEToken::RightBrace {
-            break;
+        match current {
+            LEToken::RightBrace => { break; }
+            _ if !tail_return => {
+                statements.push(parse_statement(lexer)?);
+                continue;
+            }
+            LEToken::KeyWord(KeyWord::Asm) | LEToken::KeyWord(KeyWord::Volatile) => {}
+            LEToken::KeyWord(_) | LEToken::Semicolon => {
+                statements.push(parse_statement(lexer)?);
+                continue;
+            }
+            _ => {}
+        }
+        let expr = parse_expression(lexer)?;
+        if let Some(LEToken::RightBrace) = lexer.current() {
+            let expr_pos = expr.pos();
+            statements.push(Statement::Return(Some(expr), expr_pos));
+        } else {
+            lexer.consume_semicolon()?;
+            statements.push(Statement::Expressions(expr));
         }
-        statements.push(parse_statement(lexer)?);
     }
     lexer.consume_right_brace()?;
     Ok(CodeBlock {
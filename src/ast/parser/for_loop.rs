@@ -1,23 +1,81 @@
-use crate::ast::nodes::ForLoop;
-use crate::ast::parser::common::parse_code_block;
+use crate::ast::nodes::{ForLoop, Statement};
+use crate::ast::parser::common::{parse_code_block, parse_expression, parse_loop_else_block};
 use crate::ast::parser::statement::parse_statement;
-use crate::error::Result;
-use crate::lexer::LELexer;
+use crate::ast::parser::variable_parser::{parse_variable_declaration, parse_variable_declarator};
+use crate::error::{LEError, Result};
+use crate::error::{SyntaxError, TokenType};
+use crate::lexer::{KeyWord, LELexer, LEToken};
+
+/// 解析for循环头部中以逗号分隔，分号结尾的语句列表，`var`之后的各项也是变量定义
+/// ```text
+/// for(var i = 0, j = 10; i < j; i = i + 1, j = j - 1;){}
+/// ```
+pub fn parse_for_statement_list(lexer: &mut LELexer) -> Result<Vec<Statement>> {
+    let mut statements = vec![];
+    if let Some(LEToken::Semicolon) = lexer.current() {
+        lexer.consume_semicolon()?;
+        return Ok(statements);
+    }
+    let mut declaring = false;
+    loop {
+        let statement = match lexer.current() {
+            Some(LEToken::KeyWord(KeyWord::VariableDeclare)) => {
+                declaring = true;
+                Statement::VariableDefinition(parse_variable_declaration(lexer)?)
+            }
+            _ if declaring => {
+                let start_pos = lexer.pos();
+                Statement::VariableDefinition(parse_variable_declarator(lexer, start_pos)?)
+            }
+            _ => {
+                Statement::Expressions(parse_expression(lexer)?)
+            }
+        };
+        statements.push(statement);
+        //各项之间必须以逗号分隔
+        let current_token = lexer.current().ok_or_else(|| LEError::new_syntax_error(
+            SyntaxError::missing_token(vec![TokenType::Semicolon, TokenType::Comma]),
+            lexer.pos(),
+        ))?;
+        match current_token {
+            LEToken::Semicolon => {
+                lexer.consume_semicolon()?;
+                return Ok(statements);
+            }
+            LEToken::Comma => {
+                lexer.consume();
+            }
+            other => {
+                return Err(LEError::new_syntax_error(SyntaxError::unexpect_token(vec![TokenType::Semicolon, TokenType::Comma], other), lexer.pos()));
+            }
+        }
+    }
+}
 
 pub fn parse_for_loop(lexer: &mut LELexer) -> Result<ForLoop> {
     let start_pos = lexer.pos();
     lexer.consume_keyword()?;
     lexer.consume_left_par()?;
-    let initial = parse_statement(lexer)?;
-    let cond = parse_statement(lexer)?;
-    let step = parse_statement(lexer)?;
+    let initial = parse_for_statement_list(lexer)?;
+    //条件只能是表达式或者省略，省略时为空语句
+    let cond = match lexer.current() {
+        Some(LEToken::Semicolon) => { parse_statement(lexer)? }
+        _ => {
+            let cond_expr = parse_expression(lexer)?;
+            lexer.consume_semicolon()?;
+            Statement::Expressions(cond_expr)
+        }
+    };
+    let step = parse_for_statement_list(lexer)?;
     lexer.consume_right_par()?;
-    let code_block = parse_code_block(lexer)?;
+    let code_block = parse_code_block(lexer, false)?;
+    let else_block = parse_loop_else_block(lexer)?;
     Ok(ForLoop {
-        init_statement: Box::from(initial),
+        init_statements: initial,
         condition: Box::from(cond),
-        iterate: Box::new(step),
+        iterate: step,
         code_block,
+        else_block,
         pos: start_pos.sum(&lexer.pos()),
     })
-}
\ No newline at end of file
+}
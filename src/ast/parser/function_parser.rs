@@ -22,6 +22,19 @@ pub fn parse_function_params(lexer: &mut LELexer) -> Result<Vec<(String, TypeDec
             LEToken::Comma => {
                 lexer.consume();
             }
+            //方法的第一个参数可以省略类型写作`self`，类型为所属结构体的引用
+            LEToken::Identifier(ref name) if name == "self" && params.is_empty() => {
+                let self_pos = lexer.pos();
+                lexer.consume();
+                if let Some(LEToken::Colon) = lexer.current() {
+                    lexer.consume_colon()?;
+                    params.push(("self".into(), parse_type_declarator(lexer)?));
+                } else {
+                    params.push(("self".into(), TypeDeclarator::Reference(Box::new(TypeDeclarator::TypeIdentifier(
+                        Identifier { name: "Self".into(), pos: self_pos }
+                    )))));
+                }
+            }
             _ => {
                 params.push(parse_annotation(lexer)?);
             }
@@ -63,10 +76,36 @@ pub fn parse_extern_function_prototype(lexer: &mut LELexer) -> Result<FunctionPr
         identifier,
         param_types,
         return_type,
+        attributes: vec![],
         pos: start_pos.sum(&lexer.pos()),
     })
 }
 
+/// 解析`decl`之后的外部函数声明，可以是单个声明，也可以是大括号包围的一组声明
+/// ```text
+/// decl le a();
+/// decl { le a(); le b(i32)->i32; }
+/// ```
+pub fn parse_extern_declarations(lexer: &mut LELexer) -> Result<Vec<FunctionPrototype>> {
+    lexer.consume_keyword()?;
+    if let Some(LEToken::LeftBrace) = lexer.current() {
+        lexer.consume_left_brace()?;
+        let mut prototypes = vec![];
+        while let Some(current) = lexer.current() {
+            if current == LEToken::RightBrace {
+                break;
+            }
+            prototypes.push(parse_extern_function_prototype(lexer)?);
+            lexer.consume_semicolon()?;
+        }
+        lexer.consume_right_brace()?;
+        Ok(prototypes)
+    } else {
+        let prototype = parse_extern_function_prototype(lexer)?;
+        lexer.consume_semicolon()?;
+        Ok(vec![prototype])
+    }
+}
 
 pub fn parse_function_return_type(lexer: &mut LELexer) -> Result<Option<TypeDeclarator>> {
     let current_token = lexer.current().ok_or(
@@ -81,7 +120,33 @@ pub fn parse_function_return_type(lexer: &mut LELexer) -> Result<Option<TypeDecl
 }
 
 
+/// 函数定义，或没有函数体的前置声明
+pub enum FunctionItem {
+    Definition(FunctionDefinition),
+    ForwardDeclaration(FunctionPrototype),
+}
+
 pub fn parse_function(lexer: &mut LELexer) -> Result<FunctionDefinition> {
+    let start_pos = lexer.pos();
+    let (prototype, param_names) = parse_function_header(lexer)?;
+    parse_function_body(lexer, start_pos, prototype, param_names)
+}
+
+/// 解析函数定义，函数头后直接跟分号时视为前置声明
+/// ```text
+/// le f(a:i32)->i32;
+/// ```
+pub fn parse_function_item(lexer: &mut LELexer) -> Result<FunctionItem> {
+    let start_pos = lexer.pos();
+    let (prototype, param_names) = parse_function_header(lexer)?;
+    if let Some(LEToken::Semicolon) = lexer.current() {
+        lexer.consume_semicolon()?;
+        return Ok(FunctionItem::ForwardDeclaration(prototype));
+    }
+    Ok(FunctionItem::Definition(parse_function_body(lexer, start_pos, prototype, param_names)?))
+}
+
+fn parse_function_header(lexer: &mut LELexer) -> Result<(FunctionPrototype, Vec<String>)> {
     let start_pos = lexer.pos();
     lexer.consume_keyword()?;
     let identifier_pos = lexer.pos();
@@ -89,21 +154,27 @@ pub fn parse_function(lexer: &mut LELexer) -> Result<FunctionDefinition> {
     let params = parse_function_params(lexer)?;
     let return_type = parse_function_return_type(lexer)?;
     let proto_type_pos = start_pos.sum(&lexer.pos());
-    let code_block = parse_code_block(lexer)?;
-    let function_pos = start_pos.sum(&lexer.pos());
     let mut param_names = Vec::with_capacity(params.len());
     let mut param_types = Vec::with_capacity(params.len());
     params.into_iter().for_each(|anno| {
         param_types.push(anno.1);
         param_names.push(anno.0);
     });
+    let prototype = FunctionPrototype {
+        identifier,
+        param_types,
+        return_type,
+        attributes: vec![],
+        pos: proto_type_pos,
+    };
+    Ok((prototype, param_names))
+}
+
+fn parse_function_body(lexer: &mut LELexer, start_pos: Position, prototype: FunctionPrototype, param_names: Vec<String>) -> Result<FunctionDefinition> {
+    let code_block = parse_code_block(lexer, true)?;
+    let function_pos = start_pos.sum(&lexer.pos());
     let function = FunctionDefinition {
-        prototype: FunctionPrototype {
-            identifier,
-            param_types,
-            return_type,
-            pos: proto_type_pos,
-        },
+        prototype,
         param_names,
         code_block,
         pos: function_pos,
@@ -1,18 +1,19 @@
-use crate::ast::nodes::IfStatement;
-use crate::ast::parser::common::{parse_code_block, parse_little_par_expression};
-use crate::error::Result;
-use crate::error::SyntaxError;
+use crate::ast::nodes::{BlockExpression, CodeBlock, Expr, IfExpression, IfStatement, Statement};
+use crate::ast::parser::common::{parse_code_block, parse_expression, parse_little_par_expression};
+use crate::ast::parser::statement::parse_statement;
+use crate::error::{LEError, Result};
+use crate::error::{SyntaxError, TokenType};
 use crate::lexer::{KeyWord, LELexer, LEToken};
 
 pub fn parse_if_statement(lexer: &mut LELexer) -> Result<IfStatement> {
     let start_pos = lexer.pos();
     lexer.consume();
     let cond_value = parse_little_par_expression(lexer)?;
-    let then_block = parse_code_block(lexer)?;
+    let then_block = parse_code_block(lexer, false)?;
     let current = lexer.current();
     if let Some(LEToken::KeyWord(KeyWord::Else)) = current {
         lexer.consume();
-        let else_block = parse_code_block(lexer)?;
+        let else_block = parse_code_block(lexer, false)?;
         Ok(IfStatement {
             cond: cond_value,
             then_block,
@@ -29,3 +30,71 @@ pub fn parse_if_statement(lexer: &mut LELexer) -> Result<IfStatement> {
     }
 }
 
+
+/// 解析if表达式的分支或块表达式，以一个没有分号的表达式结尾，该表达式为块的值
+/// ```text
+/// { var t = a * 2; t + 1 }
+/// ```
+fn parse_value_block(lexer: &mut LELexer) -> Result<(CodeBlock, Box<Expr>)> {
+    let start_pos = lexer.pos();
+    lexer.consume_left_brace()?;
+    let mut statements = vec![];
+    loop {
+        let current = lexer.current().ok_or_else(|| LEError::new_syntax_error(
+            SyntaxError::missing_token(vec![TokenType::RightBrace]),
+            lexer.pos(),
+        ))?;
+        match current {
+            LEToken::KeyWord(KeyWord::Asm) | LEToken::KeyWord(KeyWord::Volatile) => {}
+            LEToken::KeyWord(_) | LEToken::Semicolon => {
+                statements.push(parse_statement(lexer)?);
+                continue;
+            }
+            _ => {}
+        }
+        let expr = parse_expression(lexer)?;
+        if let Some(LEToken::Semicolon) = lexer.current() {
+            lexer.consume_semicolon()?;
+            statements.push(Statement::Expressions(expr));
+        } else {
+            lexer.consume_right_brace()?;
+            let code_block = CodeBlock { statements, pos: start_pos.sum(&lexer.pos()) };
+            return Ok((code_block, expr));
+        }
+    }
+}
+
+pub fn parse_if_expression(lexer: &mut LELexer) -> Result<Box<Expr>> {
+    let start_pos = lexer.pos();
+    lexer.consume();
+    let cond = parse_little_par_expression(lexer)?;
+    let (then_block, then_value) = parse_value_block(lexer)?;
+    match lexer.current() {
+        Some(LEToken::KeyWord(KeyWord::Else)) => { lexer.consume(); }
+        Some(token) => {
+            return Err(LEError::new_syntax_error(SyntaxError::unexpect_token(vec![TokenType::Else], token), lexer.pos()));
+        }
+        None => {
+            return Err(LEError::new_syntax_error(SyntaxError::missing_token(vec![TokenType::Else]), lexer.pos()));
+        }
+    }
+    let (else_block, else_value) = parse_value_block(lexer)?;
+    Ok(Box::new(Expr::If(IfExpression {
+        cond,
+        then_block,
+        then_value,
+        else_block,
+        else_value,
+        pos: start_pos.sum(&lexer.pos()),
+    })))
+}
+
+pub fn parse_block_expression(lexer: &mut LELexer) -> Result<Box<Expr>> {
+    let start_pos = lexer.pos();
+    let (code_block, value) = parse_value_block(lexer)?;
+    Ok(Box::new(Expr::Block(BlockExpression {
+        code_block,
+        value,
+        pos: start_pos.sum(&lexer.last_pos()),
+    })))
+}
@@ -0,0 +1,44 @@
+use crate::ast::nodes::{FunctionDefinition, TypeDeclarator};
+use crate::ast::parser::function_parser::parse_function;
+use crate::error::{LEError, Result};
+use crate::error::{SyntaxError, TokenType};
+use crate::lexer::{LELexer, LEToken};
+
+/// 解析结构体的方法定义，方法会被重命名为`结构体名.方法名`
+/// ```text
+/// impl Point{
+///     le len(self)->f64{}
+/// }
+/// ```
+pub fn parse_implement(lexer: &mut LELexer) -> Result<Vec<FunctionDefinition>> {
+    lexer.consume_keyword()?;
+    let structure_name = lexer.consume_identifier()?;
+    lexer.consume_left_brace()?;
+    let mut methods = vec![];
+    loop {
+        let current_token = lexer.current()
+            .ok_or_else(|| LEError::new_syntax_error(
+                SyntaxError::missing_token(vec![TokenType::RightBrace, TokenType::FunctionDefine]),
+                lexer.pos()))?;
+        match current_token {
+            LEToken::RightBrace => {
+                lexer.consume();
+                break;
+            }
+            _ => {
+                let mut method = parse_function(lexer)?;
+                //将省略类型的self参数替换为结构体的引用
+                if let Some(TypeDeclarator::Reference(self_type)) = method.prototype.param_types.first_mut() {
+                    if let TypeDeclarator::TypeIdentifier(identifier) = self_type.as_mut() {
+                        if identifier.name == "Self" {
+                            identifier.name = structure_name.clone();
+                        }
+                    }
+                }
+                method.prototype.identifier.name = format!("{}.{}", structure_name, method.prototype.identifier.name);
+                methods.push(method);
+            }
+        }
+    }
+    Ok(methods)
+}
@@ -0,0 +1,50 @@
+use crate::ast::nodes::{Expr, InlineAsm};
+use crate::ast::parser::common::parse_expression;
+use crate::ast::parser::function_parser::parse_function_return_type;
+use crate::error::{LEError, Result};
+use crate::error::{SyntaxError, TokenType};
+use crate::lexer::{LELexer, LEToken};
+
+/// 解析内联汇编表达式，可选的返回类型写在参数列表之后
+/// ```text
+/// asm("mov $1, $0", "=r,r", a) -> i32
+/// ```
+pub fn parse_inline_asm(lexer: &mut LELexer) -> Result<Box<Expr>> {
+    let start_pos = lexer.pos();
+    lexer.consume_keyword()?;
+    lexer.consume_left_par()?;
+    let assembly = lexer.consume_string_literal()?;
+    lexer.consume_comma()?;
+    let constraints = lexer.consume_string_literal()?;
+    let mut args = vec![];
+    loop {
+        let current_token = lexer.current().ok_or_else(|| LEError::new_syntax_error(
+            SyntaxError::missing_token(vec![TokenType::RightPar, TokenType::Comma]),
+            lexer.pos(),
+        ))?;
+        match current_token {
+            LEToken::RightPar => {
+                lexer.consume_right_par()?;
+                break;
+            }
+            LEToken::Comma => {
+                lexer.consume();
+                args.push(*parse_expression(lexer)?);
+            }
+            _ => {
+                return Err(LEError::new_syntax_error(
+                    SyntaxError::unexpect_token(vec![TokenType::RightPar, TokenType::Comma], current_token),
+                    lexer.pos(),
+                ));
+            }
+        }
+    }
+    let return_type = parse_function_return_type(lexer)?;
+    Ok(Box::new(Expr::InlineAsm(InlineAsm {
+        assembly,
+        constraints,
+        args,
+        return_type,
+        pos: start_pos.sum(&lexer.last_pos()),
+    })))
+}
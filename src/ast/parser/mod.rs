@@ -1,9 +1,12 @@
 pub use anonymous_function::*;
 pub use array::*;
+pub use attribute::*;
 pub use common::*;
 pub use for_loop::*;
 pub use function_parser::*;
 pub use if_statement::*;
+pub use implement::*;
+pub use inline_asm::*;
 pub use statement::*;
 pub use structure::*;
 pub use type_declarator::*;
@@ -20,4 +23,7 @@ mod while_loop;
 mod structure;
 mod array;
 mod type_declarator;
-mod anonymous_function;
\ No newline at end of file
+mod anonymous_function;
+mod attribute;
+mod inline_asm;
+mod implement;
\ No newline at end of file
@@ -17,10 +17,16 @@ pub fn parse_statement(lexer: &mut LELexer) -> Result<Statement> {
         LEToken::KeyWord(ref keyword) => {
             match keyword {
                 KeyWord::Return => {
+                    let start_pos = lexer.pos();
                     lexer.consume_keyword()?;
-                    let return_expression = parse_expression(lexer)?;
+                    //`ret;`不带返回值，用于无返回值函数的提前返回
+                    let return_expression = if let Some(LEToken::Semicolon) = lexer.current() {
+                        None
+                    } else {
+                        Some(parse_expression(lexer)?)
+                    };
                     lexer.consume_semicolon()?;
-                    Ok(Statement::Return(return_expression))
+                    Ok(Statement::Return(return_expression, start_pos.sum(&lexer.last_pos())))
                 }
                 KeyWord::VariableDeclare => {
                     let variable_node = parse_variable_declaration(lexer)?;
@@ -30,6 +36,30 @@ pub fn parse_statement(lexer: &mut LELexer) -> Result<Statement> {
                 KeyWord::If => Ok(Statement::If(parse_if_statement(lexer)?)),
                 KeyWord::For => Ok(Statement::ForLoop(parse_for_loop(lexer)?)),
                 KeyWord::While => Ok(Statement::WhileLoop(parse_while_loop(lexer)?)),
+                KeyWord::Break => {
+                    let break_pos = lexer.pos();
+                    lexer.consume_keyword()?;
+                    //`break value;`只能用于循环表达式
+                    let break_value = if let Some(LEToken::Semicolon) = lexer.current() {
+                        None
+                    } else {
+                        Some(parse_expression(lexer)?)
+                    };
+                    lexer.consume_semicolon()?;
+                    Ok(Statement::Break(break_value, break_pos))
+                }
+                KeyWord::Continue => {
+                    let continue_pos = lexer.pos();
+                    lexer.consume_keyword()?;
+                    lexer.consume_semicolon()?;
+                    Ok(Statement::Continue(continue_pos))
+                }
+                KeyWord::Unreachable => {
+                    let unreachable_pos = lexer.pos();
+                    lexer.consume_keyword()?;
+                    lexer.consume_semicolon()?;
+                    Ok(Statement::Unreachable(unreachable_pos))
+                }
                 _ => {
                     Err(LEError::new_syntax_error(
                         SyntaxError::unexpect_token(
@@ -39,8 +69,9 @@ pub fn parse_statement(lexer: &mut LELexer) -> Result<Statement> {
             }
         }
         LEToken::Semicolon => {
+            let void_pos = lexer.pos();
             lexer.consume_semicolon()?;
-            Ok(Statement::Void(lexer.pos()))
+            Ok(Statement::Void(void_pos))
         }
         _ => {
             let expr = parse_expression(lexer)?;
@@ -34,7 +34,7 @@ pub fn parse_structure(lexer: &mut LELexer) -> Result<Structure> {
             }
         }
     }
-    Ok(Structure { identifier: Identifier { name: structure_name, pos: structure_pos }, members, pos: start_pos.sum(&lexer.pos()) })
+    Ok(Structure { identifier: Identifier { name: structure_name, pos: structure_pos }, members, attributes: vec![], pos: start_pos.sum(&lexer.pos()) })
 }
 
 
@@ -46,9 +46,14 @@ fn parse_member_initializer(lexer: &mut LELexer) -> Result<(String, Box<Expr>)>
 }
 
 
-pub fn parse_structure_initializer(lexer: &mut LELexer) -> Result<(Vec<(String, Box<Expr>)>, Position)> {
+/// 解析结构体初始化器，`..base`表示其余成员从base复制
+/// ```text
+/// Point{ ..base, x: 5 }
+/// ```
+pub fn parse_structure_initializer(lexer: &mut LELexer) -> Result<(Vec<(String, Box<Expr>)>, Option<Box<Expr>>, Position)> {
     lexer.consume_left_brace()?;
     let mut members = vec![];
+    let mut base = None;
     loop {
         let current_token = lexer.current()
             .ok_or_else(|| LEError::new_syntax_error(
@@ -62,6 +67,10 @@ pub fn parse_structure_initializer(lexer: &mut LELexer) -> Result<(Vec<(String,
             LEToken::Identifier(_) => {
                 members.push(parse_member_initializer(lexer)?);
             }
+            LEToken::DoubleDot if base.is_none() => {
+                lexer.consume();
+                base = Some(parse_expression(lexer)?);
+            }
             LEToken::Comma => {
                 lexer.consume();
             }
@@ -72,5 +81,5 @@ pub fn parse_structure_initializer(lexer: &mut LELexer) -> Result<(Vec<(String,
             }
         }
     }
-    Ok((members, lexer.pos()))
+    Ok((members, base, lexer.pos()))
 }
\ No newline at end of file
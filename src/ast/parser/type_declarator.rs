@@ -1,8 +1,8 @@
-use crate::ast::nodes::{Identifier, TypeDeclarator};
+use crate::ast::nodes::{Identifier, TypeDeclarator, VectorDeclarator};
 use crate::ast::parser::array::parse_array_declarator;
 use crate::error::{LEError, Result};
 use crate::error::{SyntaxError, TokenType};
-use crate::lexer::{KeyWord, LELexer, LEToken, Position};
+use crate::lexer::{KeyWord, LELexer, LEToken, Number, Operator, Position};
 
 pub fn parse_type_declarator(lexer: &mut LELexer) -> Result<TypeDeclarator> {
     let current_token = lexer.current().ok_or_else(|| LEError::new_syntax_error(
@@ -13,6 +13,9 @@ pub fn parse_type_declarator(lexer: &mut LELexer) -> Result<TypeDeclarator> {
         LEToken::Identifier(identifier) => {
             let pos = lexer.pos();
             let identifier = lexer.consume_identifier()?;
+            if identifier == "vec" && matches!(lexer.current(), Some(LEToken::Operator(Operator::LessThan))) {
+                return Ok(TypeDeclarator::Vector(Box::new(parse_vector_declarator(lexer, pos)?)));
+            }
             Ok(TypeDeclarator::TypeIdentifier(Identifier { name: identifier, pos }))
         }
         LEToken::KeyWord(KeyWord::Ref) => {
@@ -30,4 +33,25 @@ pub fn parse_type_declarator(lexer: &mut LELexer) -> Result<TypeDeclarator> {
             ))
         }
     }
-}
\ No newline at end of file
+}
+/// 解析`vec`之后的`<T, N>`
+fn parse_vector_declarator(lexer: &mut LELexer, start_pos: Position) -> Result<VectorDeclarator> {
+    lexer.consume_operator()?;
+    let element_type = parse_type_declarator(lexer)?;
+    lexer.consume_comma()?;
+    let len_pos = lexer.pos();
+    let len = match lexer.consume_number_literal()? {
+        Number::Integer(len, None) => { len }
+        other => {
+            return Err(LEError::new_syntax_error(SyntaxError::unexpect_token(vec![TokenType::NumberLiteral], LEToken::NumberLiteral(other)), len_pos));
+        }
+    };
+    let close_pos = lexer.pos();
+    match lexer.consume_operator()? {
+        Operator::GreaterThan => {}
+        other => {
+            return Err(LEError::new_syntax_error(SyntaxError::unexpect_token(vec![TokenType::Operator], LEToken::Operator(other)), close_pos));
+        }
+    }
+    Ok(VectorDeclarator { element_type, len, pos: start_pos.sum(&lexer.pos()) })
+}
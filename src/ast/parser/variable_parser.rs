@@ -6,11 +6,16 @@ use crate::ast::parser::common::parse_expression;
 use crate::error::{LEError, Result};
 use crate::error::SyntaxError;
 use crate::error::TokenType;
-use crate::lexer::{LELexer, LEToken, Operator};
+use crate::lexer::{LELexer, LEToken, Operator, Position};
 
 pub fn parse_variable_declaration(lexer: &mut LELexer) -> Result<Variable> {
     let start_pos = lexer.pos();
     lexer.consume_keyword()?;
+    parse_variable_declarator(lexer, start_pos)
+}
+
+/// 解析`var`之后的`name[:type] = value`部分，start_pos为整个定义的起始位置
+pub fn parse_variable_declarator(lexer: &mut LELexer, start_pos: Position) -> Result<Variable> {
     let prototype_start_pos = lexer.pos();
     let identifier = Identifier {
         name: lexer.consume_identifier()?,
@@ -37,6 +42,7 @@ pub fn parse_variable_declaration(lexer: &mut LELexer) -> Result<Variable> {
                 pos: prototype_start_pos.sum(&prototype_end_pos),
             },
             value: initial_value,
+            attributes: vec![],
             pos: start_pos.sum(&lexer.pos()),
         })
     } else {
@@ -1,5 +1,5 @@
 use crate::ast::nodes::WhileLoop;
-use crate::ast::parser::common::{parse_code_block, parse_expression};
+use crate::ast::parser::common::{parse_code_block, parse_expression, parse_loop_else_block};
 use crate::error::Result;
 use crate::lexer::LELexer;
 
@@ -9,10 +9,12 @@ pub fn parse_while_loop(lexer: &mut LELexer) -> Result<WhileLoop> {
     lexer.consume_left_par()?;
     let cond = parse_expression(lexer)?;
     lexer.consume_right_par()?;
-    let code_block = parse_code_block(lexer)?;
+    let code_block = parse_code_block(lexer, false)?;
+    let else_block = parse_loop_else_block(lexer)?;
     Ok(WhileLoop {
         condition: cond,
         code_block,
+        else_block,
         pos: start_pos.sum(&lexer.pos()),
     })
 }
\ No newline at end of file
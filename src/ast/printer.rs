@@ -0,0 +1,282 @@
+use std::fmt::{Display, Formatter};
+
+use crate::ast::nodes::*;
+use crate::lexer::{Number, Operator};
+
+/// 将语法树还原为源代码，输出的代码重新解析后得到结构相同的语法树
+/// 二元运算的子表达式总是加上括号，不依赖运算符优先级
+struct SourcePrinter {
+    output: String,
+    indent: usize,
+}
+
+impl SourcePrinter {
+    fn new() -> Self {
+        Self { output: String::new(), indent: 0 }
+    }
+
+    fn line(&mut self, content: &str) {
+        self.output.push_str(&"    ".repeat(self.indent));
+        self.output.push_str(content);
+        self.output.push('\n');
+    }
+
+    fn print_ast(&mut self, ast: &Ast) {
+        for structure in ast.globals_structures.iter() {
+            self.line(&format!("{}struct {}{{ {} }}", attributes(&structure.attributes), structure.identifier.name,
+                               structure.members.iter().map(|(name, ty)| format!("{}:{}", name, type_declarator(ty))).collect::<Vec<_>>().join(", ")));
+        }
+        for prototype in ast.extern_functions.iter() {
+            self.line(&format!("{}decl le {}({}){};", attributes(&prototype.attributes), prototype.identifier.name,
+                               prototype.param_types.iter().map(type_declarator).collect::<Vec<_>>().join(", "), return_type(&prototype.return_type)));
+        }
+        for variable in ast.globals_variables.iter() {
+            self.line(&format!("{}{};", attributes(&variable.attributes), variable_definition(variable)));
+        }
+        //前置声明没有保存参数名，使用按位置生成的参数名
+        for prototype in ast.forward_declarations.iter() {
+            let param_names = (0..prototype.param_types.len()).map(|index| format!("arg{}", index)).collect::<Vec<_>>();
+            self.line(&format!("{};", function_header(prototype, &prototype.identifier.name, &param_names)));
+        }
+        for function in ast.function_definitions.iter() {
+            //方法在解析时被重命名为`结构体名.方法名`，还原为impl块
+            match function.prototype.identifier.name.split_once('.') {
+                Some((structure_name, method_name)) => {
+                    self.line(&format!("impl {}{{", structure_name));
+                    self.indent += 1;
+                    self.print_function(function, method_name);
+                    self.indent -= 1;
+                    self.line("}");
+                }
+                None => { self.print_function(function, &function.prototype.identifier.name) }
+            }
+        }
+    }
+
+    fn print_function(&mut self, function: &FunctionDefinition, name: &str) {
+        let header = function_header(&function.prototype, name, &function.param_names);
+        self.print_code_block(&header, &function.code_block);
+    }
+
+    /// 输出以head开头的代码块
+    fn print_code_block(&mut self, head: &str, code_block: &CodeBlock) {
+        self.line(&format!("{}{{", head));
+        self.indent += 1;
+        for statement in code_block.statements.iter() {
+            self.print_statement(statement);
+        }
+        self.indent -= 1;
+        self.line("}");
+    }
+
+    fn print_statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Expressions(expr) => { self.line(&format!("{};", expression(expr))) }
+            Statement::VariableDefinition(variable) => { self.line(&format!("{};", variable_definition(variable))) }
+            Statement::Return(Some(expr), _) => { self.line(&format!("ret {};", expression(expr))) }
+            Statement::Return(None, _) => { self.line("ret;") }
+            Statement::If(if_statement) => {
+                self.print_code_block(&format!("if({})", expression(&if_statement.cond)), &if_statement.then_block);
+                if let Some(else_block) = &if_statement.else_block {
+                    self.print_code_block("el", else_block);
+                }
+            }
+            Statement::ForLoop(for_loop) => {
+                let condition = match for_loop.condition.as_ref() {
+                    Statement::Expressions(cond) => { expression(cond) }
+                    _ => { String::new() }
+                };
+                let head = format!("for({}; {}; {};)", statement_list(&for_loop.init_statements), condition, statement_list(&for_loop.iterate));
+                self.print_code_block(&head, &for_loop.code_block);
+                if let Some(else_block) = &for_loop.else_block {
+                    self.print_code_block("el", else_block);
+                }
+            }
+            Statement::WhileLoop(while_loop) => {
+                self.print_code_block(&format!("while({})", expression(&while_loop.condition)), &while_loop.code_block);
+                if let Some(else_block) = &while_loop.else_block {
+                    self.print_code_block("el", else_block);
+                }
+            }
+            Statement::Break(Some(value), _) => { self.line(&format!("break {};", expression(value))) }
+            Statement::Break(None, _) => { self.line("break;") }
+            Statement::Continue(_) => { self.line("continue;") }
+            Statement::Unreachable(_) => { self.line("unreachable;") }
+            Statement::Void(_) => { self.line(";") }
+        }
+    }
+}
+
+fn attributes(attributes: &[Attribute]) -> String {
+    attributes.iter().map(|attribute| {
+        if attribute.params.is_empty() {
+            format!("@{} ", attribute.name.name)
+        } else {
+            format!("@{}({}) ", attribute.name.name, attribute.params.iter().map(|p| p.name.as_str()).collect::<Vec<_>>().join(", "))
+        }
+    }).collect()
+}
+
+fn type_declarator(ty: &TypeDeclarator) -> String {
+    match ty {
+        TypeDeclarator::TypeIdentifier(identifier) => { identifier.name.clone() }
+        TypeDeclarator::Array(array) => { format!("[{}; {}]", type_declarator(&array.element_type), array.len) }
+        TypeDeclarator::Reference(ty) => { format!("ref {}", type_declarator(ty)) }
+        TypeDeclarator::Vector(vector) => { format!("vec<{}, {}>", type_declarator(&vector.element_type), vector.len) }
+    }
+}
+
+fn return_type(ty: &Option<TypeDeclarator>) -> String {
+    ty.as_ref().map_or(String::new(), |ty| format!("->{}", type_declarator(ty)))
+}
+
+fn function_header(prototype: &FunctionPrototype, name: &str, param_names: &[String]) -> String {
+    let params = param_names.iter().zip(prototype.param_types.iter())
+        .map(|(name, ty)| format!("{}:{}", name, type_declarator(ty)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("{}le {}({}){}", attributes(&prototype.attributes), name, params, return_type(&prototype.return_type))
+}
+
+fn variable_definition(variable: &Variable) -> String {
+    match &variable.prototype.type_declarator {
+        Some(ty) => { format!("var {}:{} = {}", variable.prototype.identifier.name, type_declarator(ty), expression(&variable.value)) }
+        None => { format!("var {} = {}", variable.prototype.identifier.name, expression(&variable.value)) }
+    }
+}
+
+/// for循环头部以逗号分隔的语句列表
+fn statement_list(statements: &[Statement]) -> String {
+    statements.iter().filter_map(|statement| match statement {
+        Statement::Expressions(expr) => { Some(expression(expr)) }
+        Statement::VariableDefinition(variable) => { Some(variable_definition(variable)) }
+        _ => { None }
+    }).collect::<Vec<_>>().join(", ")
+}
+
+/// 单行输出代码块中的所有语句
+fn inline_statements(code_block: &CodeBlock) -> String {
+    let mut printer = SourcePrinter::new();
+    for statement in code_block.statements.iter() {
+        printer.print_statement(statement);
+    }
+    printer.output.lines().map(str::trim).collect::<Vec<_>>().join(" ")
+}
+
+/// 单行输出的代码块，用于表达式中的块
+fn inline_block(code_block: &CodeBlock, value: &Expr) -> String {
+    let statements = inline_statements(code_block);
+    if statements.is_empty() {
+        format!("{{ {} }}", expression(value))
+    } else {
+        format!("{{ {} {} }}", statements, expression(value))
+    }
+}
+
+fn number(number: &Number) -> String {
+    match number {
+        Number::Integer(..) => { number.to_string() }
+        //浮点数总是带小数点或指数，避免重新解析为整数
+        Number::Float(float, suffix) => { format!("{:?}{}", float, suffix.as_deref().unwrap_or("")) }
+    }
+}
+
+/// 作为二元运算或三元表达式的操作数时，除成员访问外的二元运算和三元表达式需要加括号
+fn operand(expr: &Expr) -> String {
+    match expr {
+        Expr::BinaryOperator(binary) if !matches!(binary.op, Operator::Dot) => { format!("({})", expression(expr)) }
+        Expr::Ternary(_) | Expr::CompoundAssign(_) => { format!("({})", expression(expr)) }
+        _ => { expression(expr) }
+    }
+}
+
+/// 一元运算、volatile和下标只作用于紧跟的基本表达式，其余表达式都需要加括号
+fn primary(expr: &Expr) -> String {
+    match expr {
+        Expr::BinaryOperator(_) | Expr::Ternary(_) | Expr::UnaryOperator(_) | Expr::CompoundAssign(_) => { format!("({})", expression(expr)) }
+        _ => { expression(expr) }
+    }
+}
+
+/// 成员访问的左侧，`p.0.1`会被解析为`p`后跟浮点数`0.1`，需要写作`(p.0).1`
+fn dot_left(expr: &Expr) -> String {
+    match expr {
+        Expr::BinaryOperator(BinaryOpExpression { op: Operator::Dot, right, .. }) if matches!(right.as_ref(), Expr::NumberLiteral(_)) => {
+            format!("({})", expression(expr))
+        }
+        _ => { operand(expr) }
+    }
+}
+
+fn expression(expr: &Expr) -> String {
+    match expr {
+        Expr::BinaryOperator(binary) => {
+            match binary.op {
+                Operator::Dot => { format!("{}.{}", dot_left(&binary.left), primary(&binary.right)) }
+                _ => { format!("{} {} {}", operand(&binary.left), binary.op, operand(&binary.right)) }
+            }
+        }
+        Expr::UnaryOperator(unary) => { format!("{}{}", unary.op, primary(&unary.expr)) }
+        Expr::NumberLiteral(n) => { number(&n.number) }
+        Expr::ArrayInitializer(array) => {
+            format!("[{}]", array.elements.iter().map(expression).collect::<Vec<_>>().join(", "))
+        }
+        Expr::ArrayRepeat(repeat) => { format!("[{}; {}]", expression(&repeat.element), repeat.count) }
+        Expr::StructureInitializer(initializer) => {
+            let mut members = initializer.base.iter().map(|base| format!("..{}", expression(base))).collect::<Vec<_>>();
+            members.extend(initializer.member_initial_values.iter().map(|(name, value)| format!("{}: {}", name, expression(value))));
+            format!("{}{{ {} }}", initializer.structure_name.name, members.join(", "))
+        }
+        Expr::StringLiteral(s) => { format!("\"{}\"", s.content) }
+        Expr::Identifier(identifier) => { identifier.name.clone() }
+        Expr::CallExpression(call) => {
+            format!("{}({})", call.function_name.name, call.params.iter().map(expression).collect::<Vec<_>>().join(", "))
+        }
+        Expr::InlineAsm(asm) => {
+            let args = asm.args.iter().map(|arg| format!(", {}", expression(arg))).collect::<String>();
+            format!("asm(\"{}\", \"{}\"{}){}", asm.assembly, asm.constraints, args, return_type(&asm.return_type))
+        }
+        Expr::Volatile(volatile) => { format!("volatile {}", primary(&volatile.expr)) }
+        //以if开头的语句会被解析为if语句，if表达式总是加括号
+        Expr::If(if_expr) => {
+            format!("(if({}){}el{})", expression(&if_expr.cond),
+                    inline_block(&if_expr.then_block, &if_expr.then_value),
+                    inline_block(&if_expr.else_block, &if_expr.else_value))
+        }
+        Expr::Ternary(ternary) => {
+            format!("{} ? {} : {}", operand(&ternary.cond), operand(&ternary.then_value), operand(&ternary.else_value))
+        }
+        Expr::Block(block) => { inline_block(&block.code_block, &block.value) }
+        Expr::Index(index) => { format!("{}[{}]", primary(&index.target), expression(&index.index)) }
+        //被调用的是标识符时会被解析为普通的函数调用，需要加括号
+        Expr::IndirectCall(call) => {
+            let callee = match call.callee.as_ref() {
+                Expr::Identifier(identifier) => { format!("({})", identifier.name) }
+                callee => { primary(callee) }
+            };
+            format!("{}({})", callee, call.params.iter().map(expression).collect::<Vec<_>>().join(", "))
+        }
+        Expr::Type(ty) => { type_declarator(ty) }
+        Expr::CompoundAssign(assign) => { format!("{} {}= {}", operand(&assign.target), assign.op, operand(&assign.value)) }
+        //以while开头的语句会被解析为while语句，while表达式总是加括号
+        Expr::While(while_loop) => {
+            let else_block = while_loop.else_block.as_ref().map_or(String::new(), |block| format!("el{{ {} }}", inline_statements(block)));
+            format!("(while({}){{ {} }}{})", expression(&while_loop.condition), inline_statements(&while_loop.code_block), else_block)
+        }
+    }
+}
+
+impl Display for Ast {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut printer = SourcePrinter::new();
+        printer.print_ast(self);
+        f.write_str(&printer.output)
+    }
+}
+
+impl Ast {
+    /// 将语法树还原为格式化后的源代码
+    pub fn to_source_string(&self) -> String {
+        self.to_string()
+    }
+}
@@ -1,6 +1,7 @@
 use inkwell::builder::Builder;
+use inkwell::IntPredicate;
 
-use crate::code_generator::builder::{LEBasicType, LEBasicTypeEnum, LEBasicValue, LEBasicValueEnum, LEBoolValue, LEFloatType, LEFloatValue, LEIntegerType, LEIntegerValue, LEType, LEValue};
+use crate::code_generator::builder::{LEBasicType, LEBasicTypeEnum, LEBasicValue, LEBasicValueEnum, LEBoolValue, LEFloatType, LEFloatValue, LEIntegerType, LEIntegerValue, LEPointerType, LEPointerValue, LEStructValue, LEType, LEValue};
 use crate::code_generator::builder::binary_operator_builder::{LogicBinaryOperator, ModOperateValue};
 use crate::code_generator::builder::binary_operator_builder::traits::{BasicMathOperateValue, CompareBinaryOperator};
 use crate::code_generator::context::LEContext;
@@ -39,12 +40,15 @@ impl<'a, 'ctx> MathOperateBuilder<'a, 'ctx> {
         Ok(LEIntegerValue { ty: rhs.clone(), llvm_value: self.llvm_builder.build_int_cast(lhs.llvm_value, rhs.get_llvm_type(), "") })
     }
     pub fn build_bool_to_integer(&self, le_context: &LEContext<'ctx>, lhs: LEBoolValue<'ctx>, rhs: LEIntegerType<'ctx>) -> Result<LEIntegerValue<'ctx>> {
-        Ok(LEIntegerValue { ty: rhs.clone(), llvm_value: self.llvm_builder.build_int_cast(lhs.llvm_value, rhs.get_llvm_type(), "") })
+        //true转换为1而不是全1，需要零扩展
+        Ok(LEIntegerValue { ty: rhs.clone(), llvm_value: self.llvm_builder.build_int_z_extend(lhs.llvm_value, rhs.get_llvm_type(), "") })
     }
 
     pub fn build_integer_to_bool(&self, le_context: &LEContext<'ctx>, lhs: LEIntegerValue<'ctx>) -> Result<LEBoolValue<'ctx>> {
+        //非零即为true，不能直接截断到最低位
         let le_bool_type = le_context.bool_type();
-        Ok(LEBoolValue { ty: le_bool_type.clone(), llvm_value: self.llvm_builder.build_int_cast(lhs.llvm_value, le_bool_type.get_llvm_type(), "") })
+        let zero = lhs.ty.get_llvm_type().const_zero();
+        Ok(LEBoolValue { ty: le_bool_type.clone(), llvm_value: self.llvm_builder.build_int_compare(IntPredicate::NE, lhs.llvm_value, zero, "") })
     }
 
     pub fn build_float_to_bool(&self, le_context: &LEContext<'ctx>, lhs: LEFloatValue<'ctx>) -> Result<LEBoolValue<'ctx>> {
@@ -53,6 +57,18 @@ impl<'a, 'ctx> MathOperateBuilder<'a, 'ctx> {
         Ok(LEBoolValue { ty: le_bool_type.clone(), llvm_value: self.llvm_builder.build_bitcast(lhs.get_llvm_value(), le_bool_type.get_llvm_type(), "").into_int_value() })
     }
 
+    pub fn build_pointer_to_pointer(&self, le_context: &LEContext<'ctx>, lhs: LEPointerValue<'ctx>, rhs: LEPointerType<'ctx>) -> Result<LEPointerValue<'ctx>> {
+        Ok(LEPointerValue { ty: rhs.clone(), llvm_value: self.llvm_builder.build_pointer_cast(lhs.llvm_value, rhs.get_llvm_type(), "") })
+    }
+
+    pub fn build_pointer_to_integer(&self, le_context: &LEContext<'ctx>, lhs: LEPointerValue<'ctx>, rhs: LEIntegerType<'ctx>) -> Result<LEIntegerValue<'ctx>> {
+        Ok(LEIntegerValue { ty: rhs.clone(), llvm_value: self.llvm_builder.build_ptr_to_int(lhs.llvm_value, rhs.get_llvm_type(), "") })
+    }
+
+    pub fn build_integer_to_pointer(&self, le_context: &LEContext<'ctx>, lhs: LEIntegerValue<'ctx>, rhs: LEPointerType<'ctx>) -> Result<LEPointerValue<'ctx>> {
+        Ok(LEPointerValue { ty: rhs.clone(), llvm_value: self.llvm_builder.build_int_to_ptr(lhs.llvm_value, rhs.get_llvm_type(), "") })
+    }
+
     pub fn build_float_to_float(&self, le_context: &LEContext<'ctx>, lhs: LEFloatValue<'ctx>, rhs: LEFloatType<'ctx>) -> Result<LEFloatValue<'ctx>> {
         Ok(LEFloatValue { ty: rhs.clone(), llvm_value: self.llvm_builder.build_float_cast(lhs.llvm_value, rhs.get_llvm_type(), "") })
     }
@@ -68,6 +84,9 @@ impl<'a, 'ctx> MathOperateBuilder<'a, 'ctx> {
                 (LEBasicValueEnum::Float(left), LEBasicValueEnum::Float(right)) => {
                     Ok(left.build_add_unchecked(le_context, self.llvm_builder, right).to_le_value_enum())
                 }
+                (LEBasicValueEnum::Vector(left), LEBasicValueEnum::Vector(right)) => {
+                    Ok(left.build_elementwise_unchecked(self.llvm_builder, Operator::Plus, right).to_le_value_enum())
+                }
                 _ => {
                     Err(CompileError::NoSuitableBinaryOperator {
                         op: Operator::Plus,
@@ -96,6 +115,9 @@ impl<'a, 'ctx> MathOperateBuilder<'a, 'ctx> {
                 (LEBasicValueEnum::Float(left), LEBasicValueEnum::Float(right)) => {
                     Ok(left.build_sub_unchecked(le_context, self.llvm_builder, right).to_le_value_enum())
                 }
+                (LEBasicValueEnum::Vector(left), LEBasicValueEnum::Vector(right)) => {
+                    Ok(left.build_elementwise_unchecked(self.llvm_builder, Operator::Sub, right).to_le_value_enum())
+                }
                 _ => {
                     Err(CompileError::NoSuitableBinaryOperator {
                         op: Operator::Plus,
@@ -123,6 +145,9 @@ impl<'a, 'ctx> MathOperateBuilder<'a, 'ctx> {
                 (LEBasicValueEnum::Float(left), LEBasicValueEnum::Float(right)) => {
                     Ok(left.build_mul_unchecked(le_context, self.llvm_builder, right).to_le_value_enum())
                 }
+                (LEBasicValueEnum::Vector(left), LEBasicValueEnum::Vector(right)) => {
+                    Ok(left.build_elementwise_unchecked(self.llvm_builder, Operator::Mul, right).to_le_value_enum())
+                }
                 _ => {
                     Err(CompileError::NoSuitableBinaryOperator {
                         op: Operator::Plus,
@@ -150,6 +175,9 @@ impl<'a, 'ctx> MathOperateBuilder<'a, 'ctx> {
                 (LEBasicValueEnum::Float(left), LEBasicValueEnum::Float(right)) => {
                     Ok(left.build_div_unchecked(le_context, self.llvm_builder, right).to_le_value_enum())
                 }
+                (LEBasicValueEnum::Vector(left), LEBasicValueEnum::Vector(right)) => {
+                    Ok(left.build_elementwise_unchecked(self.llvm_builder, Operator::Div, right).to_le_value_enum())
+                }
                 _ => {
                     Err(CompileError::NoSuitableBinaryOperator {
                         op: Operator::Plus,
@@ -177,6 +205,18 @@ impl<'a, 'ctx> MathOperateBuilder<'a, 'ctx> {
                 (LEBasicValueEnum::Float(left), LEBasicValueEnum::Float(right)) => {
                     Ok(left.build_cmp_unchecked(le_context, self.llvm_builder, op, right))
                 }
+                (LEBasicValueEnum::Bool(left), LEBasicValueEnum::Bool(right)) if matches!(op, CompareBinaryOperator::Equal | CompareBinaryOperator::NotEqual) => {
+                    let predicate = if let CompareBinaryOperator::Equal = op { IntPredicate::EQ } else { IntPredicate::NE };
+                    Ok(LEBoolValue { ty: left.ty.clone(), llvm_value: self.llvm_builder.build_int_compare(predicate, left.llvm_value, right.llvm_value, "") })
+                }
+                (LEBasicValueEnum::Struct(left), LEBasicValueEnum::Struct(right)) if matches!(op, CompareBinaryOperator::Equal | CompareBinaryOperator::NotEqual) => {
+                    let equal = self.build_struct_equal(le_context, left, right)?;
+                    if let CompareBinaryOperator::NotEqual = op {
+                        Ok(LEBoolValue { ty: equal.ty.clone(), llvm_value: self.llvm_builder.build_not(equal.llvm_value, "") })
+                    } else {
+                        Ok(equal)
+                    }
+                }
                 _ => {
                     Err(CompileError::NoSuitableBinaryOperator {
                         op: Operator::Plus,
@@ -194,6 +234,23 @@ impl<'a, 'ctx> MathOperateBuilder<'a, 'ctx> {
         }
     }
 
+    /// 逐个比较结构体的成员并将结果相与，成员为结构体时递归比较，位域成员按其存储的整数整体比较
+    fn build_struct_equal(&self, le_context: &LEContext<'ctx>, lhs: LEStructValue<'ctx>, rhs: LEStructValue<'ctx>) -> Result<LEBoolValue<'ctx>> {
+        let mut result = LEBoolValue { ty: le_context.bool_type(), llvm_value: le_context.bool_type().get_llvm_type().const_int(1, false) };
+        for (index, ty) in lhs.ty.get_storage_types().into_iter().enumerate() {
+            let left = self.llvm_builder.build_extract_value(lhs.llvm_value, index as u32, "").unwrap();
+            let right = self.llvm_builder.build_extract_value(rhs.llvm_value, index as u32, "").unwrap();
+            let equal = self.build_compare(
+                le_context,
+                LEBasicValueEnum::from_type_and_llvm_value(ty.clone(), left)?,
+                LEBasicValueEnum::from_type_and_llvm_value(ty, right)?,
+                CompareBinaryOperator::Equal,
+            )?;
+            result = if index == 0 { equal } else { self.build_logic(le_context, result, equal, LogicBinaryOperator::And) };
+        }
+        Ok(result)
+    }
+
     pub fn build_cast(&self, le_context: &LEContext<'ctx>, lhs: LEBasicValueEnum<'ctx>, rhs: LEBasicTypeEnum<'ctx>) -> Result<LEBasicValueEnum<'ctx>> {
         let left_type = LEBasicValue::get_le_type(&lhs);
         if left_type == rhs {
@@ -221,6 +278,16 @@ impl<'a, 'ctx> MathOperateBuilder<'a, 'ctx> {
                 (LEBasicValueEnum::Float(left), LEBasicTypeEnum::Bool(_)) => {
                     Ok(self.build_float_to_bool(le_context, left)?.to_le_value_enum())
                 }
+                //指针之间的转换不改变地址，指针与浮点数之间不能直接转换
+                (LEBasicValueEnum::Pointer(left), LEBasicTypeEnum::Pointer(right)) => {
+                    Ok(self.build_pointer_to_pointer(le_context, left, right)?.to_le_value_enum())
+                }
+                (LEBasicValueEnum::Pointer(left), LEBasicTypeEnum::Integer(right)) => {
+                    Ok(self.build_pointer_to_integer(le_context, left, right)?.to_le_value_enum())
+                }
+                (LEBasicValueEnum::Integer(left), LEBasicTypeEnum::Pointer(right)) => {
+                    Ok(self.build_integer_to_pointer(le_context, left, right)?.to_le_value_enum())
+                }
                 _ => { Err(CompileError::InvalidTypeCast { from: left_type.to_string(), to: rhs.to_string() }) }
             }
         }
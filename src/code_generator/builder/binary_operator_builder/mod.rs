@@ -7,3 +7,4 @@ mod struct_builder;
 mod array_builder;
 mod traits;
 mod pointer_builder;
+mod vector_builder;
@@ -13,6 +13,16 @@ use crate::code_generator::Result;
 
 use super::traits::{BasicMathOperateValue, CompareBinaryOperator};
 
+/// 值为2的正整数次幂的常量时返回指数
+fn power_of_two_exponent(value: &LEIntegerValue) -> Option<u32> {
+    let constant = value.llvm_value.get_zero_extended_constant()?;
+    if constant > 1 && constant.is_power_of_two() {
+        Some(constant.trailing_zeros())
+    } else {
+        None
+    }
+}
+
 impl<'ctx> ModOperateValue<'ctx> for LEIntegerValue<'ctx> {
     fn build_mod_unchecked(self, le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, rhs: Self) -> Self {
         if self.ty.signed() {
@@ -36,10 +46,26 @@ impl<'ctx> BasicMathOperateValue<'ctx> for LEIntegerValue<'ctx> {
     }
 
     fn build_mul_unchecked(self, le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, rhs: Self) -> Self {
+        //乘以2的幂时生成左移
+        if let Some(exponent) = power_of_two_exponent(&rhs) {
+            let shift = self.ty.get_llvm_type().const_int(exponent as u64, false);
+            return LEIntegerValue { ty: self.ty.clone(), llvm_value: llvm_builder.build_left_shift(self.llvm_value, shift, "") };
+        }
+        if let Some(exponent) = power_of_two_exponent(&self) {
+            let shift = rhs.ty.get_llvm_type().const_int(exponent as u64, false);
+            return LEIntegerValue { ty: rhs.ty.clone(), llvm_value: llvm_builder.build_left_shift(rhs.llvm_value, shift, "") };
+        }
         LEIntegerValue { ty: self.ty.clone(), llvm_value: llvm_builder.build_int_mul(self.llvm_value, rhs.llvm_value, "") }
     }
 
     fn build_div_unchecked(self, le_context: &LEContext<'ctx>, llvm_builder: &Builder<'ctx>, rhs: Self) -> Self {
+        //无符号数除以2的幂时生成逻辑右移，有符号数的算术右移对负数向下取整，与除法的向零取整不同，不做替换
+        if !self.ty.signed() {
+            if let Some(exponent) = power_of_two_exponent(&rhs) {
+                let shift = self.ty.get_llvm_type().const_int(exponent as u64, false);
+                return LEIntegerValue { ty: self.ty.clone(), llvm_value: llvm_builder.build_right_shift(self.llvm_value, shift, false, "") };
+            }
+        }
         if self.ty.signed() {
             LEIntegerValue { ty: self.ty.clone(), llvm_value: llvm_builder.build_int_signed_div(self.llvm_value, rhs.llvm_value, "") }
         } else {
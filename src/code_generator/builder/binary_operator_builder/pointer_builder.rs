@@ -19,7 +19,7 @@ impl<'ctx> MemberAccessOperateValue<'ctx> for LEPointerValue<'ctx> {
 
             Ok(LEPointerValue { ty: member_pointer_type, llvm_value: member_pointer_value })
         } else {
-            Err(CompileError::TypeMismatched { expect: self.ty.name().into(), found: pointed_type.name().into() })
+            Err(CompileError::TypeMismatched { expect: "Struct".into(), found: pointed_type.to_string() })
         }
     }
 
@@ -0,0 +1,23 @@
+use inkwell::builder::Builder;
+
+use crate::code_generator::builder::{LEBasicTypeEnum, LEVectorValue};
+use crate::lexer::Operator;
+
+impl<'ctx> LEVectorValue<'ctx> {
+    /// 对两个类型相同的向量逐元素进行四则运算，生成的是LLVM的向量指令
+    pub fn build_elementwise_unchecked(self, llvm_builder: &Builder<'ctx>, op: Operator, rhs: Self) -> Self {
+        let (lhs, rhs) = (self.llvm_value, rhs.llvm_value);
+        let llvm_value = match (self.ty.element_type(), op) {
+            (LEBasicTypeEnum::Integer(_), Operator::Plus) => { llvm_builder.build_int_add(lhs, rhs, "") }
+            (LEBasicTypeEnum::Integer(_), Operator::Sub) => { llvm_builder.build_int_sub(lhs, rhs, "") }
+            (LEBasicTypeEnum::Integer(_), Operator::Mul) => { llvm_builder.build_int_mul(lhs, rhs, "") }
+            (LEBasicTypeEnum::Integer(t), Operator::Div) if t.signed() => { llvm_builder.build_int_signed_div(lhs, rhs, "") }
+            (LEBasicTypeEnum::Integer(_), Operator::Div) => { llvm_builder.build_int_unsigned_div(lhs, rhs, "") }
+            (_, Operator::Plus) => { llvm_builder.build_float_add(lhs, rhs, "") }
+            (_, Operator::Sub) => { llvm_builder.build_float_sub(lhs, rhs, "") }
+            (_, Operator::Mul) => { llvm_builder.build_float_mul(lhs, rhs, "") }
+            (_, _) => { llvm_builder.build_float_div(lhs, rhs, "") }
+        };
+        LEVectorValue { ty: self.ty, llvm_value }
+    }
+}
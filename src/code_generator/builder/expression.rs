@@ -1,4 +1,4 @@
-use crate::code_generator::builder::{LEBasicValueEnum, LEPointerValue};
+use crate::code_generator::builder::{LEBasicType, LEBasicTypeEnum, LEBasicValue, LEBasicValueEnum, LEBitField, LEPointerValue};
 use crate::code_generator::Result;
 use crate::error::CompileError;
 
@@ -6,6 +6,8 @@ use crate::error::CompileError;
 pub enum Expression<'ctx> {
     Left(LEPointerValue<'ctx>),
     Right(LEBasicValueEnum<'ctx>),
+    /// 结构体位域成员的左值，由存储位域的整数成员的指针和位域信息组成
+    BitField(LEPointerValue<'ctx>, LEBitField<'ctx>),
     Unit,
 }
 
@@ -22,6 +24,16 @@ impl<'ctx> Expression<'ctx> {
         matches!(self, Self::Unit)
     }
 
+    /// 获取表达式值的类型，空表达式没有类型
+    pub fn get_le_type(&self) -> Option<LEBasicTypeEnum<'ctx>> {
+        match self {
+            Self::Left(v) => { Some(v.ty.get_point_type()) }
+            Self::Right(v) => { Some(v.get_le_type()) }
+            Self::BitField(_, field) => { Some(field.ty.to_le_type_enum()) }
+            Self::Unit => { None }
+        }
+    }
+
     pub fn to_left_value(&self) -> Result<LEPointerValue<'ctx>> {
         if let Self::Left(v) = self {
             Ok(v.clone())
@@ -1,11 +1,14 @@
-use inkwell::AddressSpace;
+use inkwell::{AddressSpace, AtomicOrdering, AtomicRMWBinOp};
+use inkwell::attributes::AttributeLoc;
+use inkwell::basic_block::BasicBlock;
 use inkwell::builder::Builder;
 use inkwell::context::Context;
 use inkwell::module::Module;
-use inkwell::values::{BasicMetadataValueEnum, BasicValueEnum, FunctionValue, InstructionValue};
+use inkwell::types::FunctionType;
+use inkwell::values::{BasicMetadataValueEnum, BasicValueEnum, CallableValue, FunctionValue, InstructionValue};
 
 use crate::ast::nodes::TypeDeclarator;
-use crate::code_generator::builder::{LEBasicType, LEBasicTypeEnum, LEBasicValue, LEBasicValueEnum, LEBoolType, LEBoolValue, LEFloatType, LEFloatValue, LEFunctionValue, LEIntegerType, LEIntegerValue, LEPointerType, LEPointerValue, LEType, LEValue};
+use crate::code_generator::builder::{LEBasicType, LEBasicTypeEnum, LEBasicValue, LEBasicValueEnum, LEBitField, LEBoolType, LEBoolValue, LEFloatType, LEFloatValue, LEFunctionPointerValue, LEFunctionType, LEFunctionValue, LEIntegerType, LEIntegerValue, LEPointerType, LEPointerValue, LEType, LEValue};
 use crate::code_generator::builder::binary_operator_builder::{CompareBinaryOperator, LogicBinaryOperator, MathOperateBuilder, MemberAccessOperateValue, ModOperateValue};
 use crate::code_generator::builder::expression::Expression;
 use crate::code_generator::context::LEContext;
@@ -33,6 +36,9 @@ impl<'ctx> LEBuilder<'ctx> {
             Expression::Right(right_value) => {
                 Ok(right_value)
             }
+            Expression::BitField(storage, field) => {
+                Ok(self.build_bit_field_load(storage, field).to_le_value_enum())
+            }
             Expression::Unit => {
                 Err(CompileError::ExpressionIsNotRightValueExpression)
             }
@@ -110,19 +116,159 @@ impl<'ctx> LEBuilder<'ctx> {
         }
     }
 
-    pub fn build_dot(&self, le_context: &LEContext<'ctx>, lhs: Expression<'ctx>, member_name: &str) -> Result<LEPointerValue<'ctx>> {
+    /// 生成按位与、或、异或和移位运算，两侧必须是相同类型的整数，有符号整数右移时保留符号位
+    pub fn build_bitwise(&self, lhs: LEBasicValueEnum<'ctx>, rhs: LEBasicValueEnum<'ctx>, op: Operator) -> Result<LEBasicValueEnum<'ctx>> {
+        let left_type = LEBasicValue::get_le_type(&lhs);
+        let right_type = LEBasicValue::get_le_type(&rhs);
+        match (lhs, rhs) {
+            (LEBasicValueEnum::Integer(left), LEBasicValueEnum::Integer(right)) if left_type == right_type => {
+                let llvm_value = match op {
+                    Operator::BitAnd => { self.llvm_builder.build_and(left.llvm_value, right.llvm_value, "") }
+                    Operator::BitOr => { self.llvm_builder.build_or(left.llvm_value, right.llvm_value, "") }
+                    Operator::Xor => { self.llvm_builder.build_xor(left.llvm_value, right.llvm_value, "") }
+                    Operator::ShiftLeft => { self.llvm_builder.build_left_shift(left.llvm_value, right.llvm_value, "") }
+                    Operator::ShiftRight => { self.llvm_builder.build_right_shift(left.llvm_value, right.llvm_value, left.ty.signed(), "") }
+                    _ => { unreachable!() }
+                };
+                Ok(LEIntegerValue { ty: left.ty, llvm_value }.to_le_value_enum())
+            }
+            _ => {
+                Err(CompileError::NoSuitableBinaryOperator {
+                    op,
+                    left_type: left_type.to_string(),
+                    right_type: right_type.to_string(),
+                })
+            }
+        }
+    }
+
+    /// 生成select指令，根据条件在两个值中选择一个，两个值都会被求值
+    pub fn build_select(&self, le_context: &LEContext<'ctx>, cond: LEBoolValue<'ctx>, then_value: Expression<'ctx>, else_value: Expression<'ctx>) -> Result<LEBasicValueEnum<'ctx>> {
+        let then_value = self.read_expression(le_context, then_value)?;
+        let else_value = self.read_expression(le_context, else_value)?;
+        let value_type = then_value.get_le_type();
+        if else_value.get_le_type() != value_type {
+            return Err(CompileError::TypeMismatched { expect: value_type.to_string(), found: else_value.get_le_type().to_string() });
+        }
+        let value = self.llvm_builder.build_select(cond.llvm_value, then_value.to_llvm_basic_value_enum(), else_value.to_llvm_basic_value_enum(), "");
+        LEBasicValueEnum::from_type_and_llvm_value(value_type, value)
+    }
+
+    /// 在当前位置生成phi节点，所有来源的值类型必须一致，incoming不能为空
+    pub fn build_phi(&self, incoming: &[(LEBasicValueEnum<'ctx>, BasicBlock<'ctx>)]) -> Result<LEBasicValueEnum<'ctx>> {
+        let value_type = incoming.first().expect("phi node needs at least one incoming value").0.get_le_type();
+        for (value, _) in incoming.iter() {
+            if value.get_le_type() != value_type {
+                return Err(CompileError::TypeMismatched { expect: value_type.to_string(), found: value.get_le_type().to_string() });
+            }
+        }
+        let phi = self.llvm_builder.build_phi(value_type.get_llvm_basic_type(), "");
+        for (value, block) in incoming.iter() {
+            phi.add_incoming(&[(&value.to_llvm_basic_value_enum(), *block)]);
+        }
+        LEBasicValueEnum::from_type_and_llvm_value(value_type, phi.as_basic_value())
+    }
+
+    pub fn build_dot(&self, le_context: &LEContext<'ctx>, lhs: Expression<'ctx>, member_name: &str) -> Result<Expression<'ctx>> {
+        //左侧为指向结构体的指针时自动解引用，与C中的`p->field`相同
+        let lhs = match lhs {
+            Expression::Left(left_expr) if Self::is_struct_pointer(&left_expr.ty.get_point_type()) => {
+                Expression::Left(self.build_load(le_context, left_expr).try_into().unwrap())
+            }
+            Expression::Right(LEBasicValueEnum::Pointer(pointer)) if Self::is_struct_pointer(&pointer.ty.to_le_type_enum()) => {
+                Expression::Left(pointer)
+            }
+            //右值结构体没有地址，存入临时变量后读取成员，结果仍是右值
+            Expression::Right(LEBasicValueEnum::Struct(struct_value)) => {
+                let temporary = self.build_entry_alloca(le_context, struct_value.get_le_type());
+                self.build_store(le_context, temporary.clone(), struct_value.to_le_value_enum())?;
+                let member = self.build_dot(le_context, Expression::Left(temporary), member_name)?;
+                return Ok(Expression::Right(self.read_expression(le_context, member)?));
+            }
+            other => { other }
+        };
         if let Expression::Left(left_expr) = lhs {
-            left_expr.build_dot_unchecked(le_context, &self.llvm_builder, member_name)
+            //位域成员没有独立的地址，返回存储它的整数成员的指针
+            if let LEBasicTypeEnum::Struct(struct_type) = left_expr.ty.get_point_type() {
+                if let Some(field) = struct_type.get_bit_field(member_name) {
+                    let storage = self.llvm_builder.build_struct_gep(left_expr.llvm_value, field.storage_index, "").unwrap();
+                    let storage_pointer_type = LEBasicType::get_pointer_type(&field.storage_type);
+                    return Ok(Expression::BitField(LEPointerValue { ty: storage_pointer_type, llvm_value: storage }, field));
+                }
+            }
+            Ok(Expression::Left(left_expr.build_dot_unchecked(le_context, &self.llvm_builder, member_name)?))
         } else {
             Err(CompileError::ExpressionIsNotRightValueExpression)
         }
     }
 
+    fn is_struct_pointer(ty: &LEBasicTypeEnum<'ctx>) -> bool {
+        if let LEBasicTypeEnum::Pointer(pointer_type) = ty {
+            matches!(pointer_type.get_point_type(), LEBasicTypeEnum::Struct(_))
+        } else {
+            false
+        }
+    }
+
+    /// 读取位域成员，将存储整数右移到位域起始位后用掩码取出，再截断为位域的类型
+    fn build_bit_field_load(&self, storage: LEPointerValue<'ctx>, field: LEBitField<'ctx>) -> LEIntegerValue<'ctx> {
+        let storage_llvm_type = field.storage_type.get_llvm_type();
+        let field_width = field.ty.get_llvm_type().get_bit_width();
+        let mask = storage_llvm_type.const_int((1u64 << field_width) - 1, false);
+        let storage_value = self.llvm_builder.build_load(storage.llvm_value, "").into_int_value();
+        let shifted = self.llvm_builder.build_right_shift(storage_value, storage_llvm_type.const_int(field.bit_offset as u64, false), false, "");
+        let masked = self.llvm_builder.build_and(shifted, mask, "");
+        let llvm_value = self.llvm_builder.build_int_truncate(masked, field.ty.get_llvm_type(), "");
+        LEIntegerValue { ty: field.ty, llvm_value }
+    }
+
+    /// 写入位域成员，读出存储整数后清除位域所在的位，再或上移位后的新值写回
+    fn build_bit_field_store(&self, storage: LEPointerValue<'ctx>, field: LEBitField<'ctx>, value: LEIntegerValue<'ctx>) {
+        let storage_llvm_type = field.storage_type.get_llvm_type();
+        let field_width = field.ty.get_llvm_type().get_bit_width();
+        let clear_mask = storage_llvm_type.const_int(!(((1u64 << field_width) - 1) << field.bit_offset), false);
+        let storage_value = self.llvm_builder.build_load(storage.llvm_value, "").into_int_value();
+        let cleared = self.llvm_builder.build_and(storage_value, clear_mask, "");
+        let extended = self.llvm_builder.build_int_z_extend(value.llvm_value, storage_llvm_type, "");
+        let shifted = self.llvm_builder.build_left_shift(extended, storage_llvm_type.const_int(field.bit_offset as u64, false), "");
+        let new_value = self.llvm_builder.build_or(cleared, shifted, "");
+        self.llvm_builder.build_store(storage.llvm_value, new_value);
+    }
+
 
     pub fn build_call(&self, le_context: &LEContext<'ctx>, function: LEFunctionValue<'ctx>, params: &[Expression<'ctx>]) -> Result<Expression<'ctx>> {
+        self.build_call_with_tail_hint(le_context, function, params, false)
+    }
+
+    /// 生成函数调用，is_tail_call为true时将调用标记为尾调用
+    pub fn build_call_with_tail_hint(&self, le_context: &LEContext<'ctx>, function: LEFunctionValue<'ctx>, params: &[Expression<'ctx>], is_tail_call: bool) -> Result<Expression<'ctx>> {
+        let call_convention = function.llvm_value.get_call_conventions();
+        self.build_call_site(le_context, &function.ty, CallableValue::from(function.llvm_value), call_convention, params, is_tail_call)
+    }
+
+    /// 通过函数指针生成函数调用，只有C调用约定的函数可以取地址，因此调用点总是使用C调用约定
+    pub fn build_indirect_call(&self, le_context: &LEContext<'ctx>, function: LEFunctionPointerValue<'ctx>, params: &[Expression<'ctx>]) -> Result<Expression<'ctx>> {
+        let callee = CallableValue::try_from(function.llvm_value).unwrap();
+        self.build_call_site(le_context, function.ty.function_type(), callee, 0, params, false)
+    }
+
+    fn build_call_site(&self, le_context: &LEContext<'ctx>, function_type: &LEFunctionType<'ctx>, callee: CallableValue<'ctx>, call_convention: u32, params: &[Expression<'ctx>], is_tail_call: bool) -> Result<Expression<'ctx>> {
         let mut args = vec![];
+        //通过sret返回的函数由调用者申请存放返回值的内存，并作为第一个参数传入
+        let struct_return_pointer = if function_type.struct_return() {
+            let return_type = function_type.return_type().unwrap();
+            let pointer = self.build_entry_alloca(le_context, return_type);
+            args.push(BasicMetadataValueEnum::from(pointer.llvm_value));
+            Some(pointer)
+        } else {
+            None
+        };
         let builder = MathOperateBuilder::new(&self.llvm_builder);
-        for (param_type, argument) in function.ty.param_types().iter().zip(params.iter()) {
+        for (param_type, argument) in function_type.param_types().iter().zip(params.iter()) {
+            if let Some(decayed) = self.build_array_decay(le_context, param_type, argument) {
+                args.push(BasicMetadataValueEnum::from(decayed.llvm_value));
+                continue;
+            }
             let argument_value = self.read_expression(le_context, argument.clone())?;
             let argument_type = LEBasicValue::get_le_type(&argument_value);
             if param_type != &argument_type {
@@ -130,20 +276,78 @@ impl<'ctx> LEBuilder<'ctx> {
             }
             args.push(BasicMetadataValueEnum::from(argument_value.to_llvm_basic_value_enum()));
         }
-        let site_value = self.llvm_builder.build_call(function.llvm_value, &args, "");
+        let site_value = self.llvm_builder.build_call(callee, &args, "");
+        //调用点的调用约定需要与函数一致
+        site_value.set_call_convention(call_convention);
+        site_value.set_tail_call(is_tail_call);
+        if let Some(pointer) = struct_return_pointer {
+            site_value.add_attribute(AttributeLoc::Param(0), le_context.struct_return_attribute(pointer.ty.get_point_type()));
+            return Ok(Expression::Left(pointer));
+        }
         if let Some(v) = site_value.try_as_basic_value().left() {
-            Ok(Expression::Right(LEBasicValueEnum::from_type_and_llvm_value(function.ty.return_type().unwrap(), v)?))
+            Ok(Expression::Right(LEBasicValueEnum::from_type_and_llvm_value(function_type.return_type().unwrap(), v)?))
         } else {
             Ok(Expression::Unit)
         }
     }
 
+    /// 参数为指向元素类型的指针而实参为数组时，与C相同将数组退化为指向首元素的指针
+    fn build_array_decay(&self, le_context: &LEContext<'ctx>, param_type: &LEBasicTypeEnum<'ctx>, argument: &Expression<'ctx>) -> Option<LEPointerValue<'ctx>> {
+        let pointer_type = match param_type {
+            LEBasicTypeEnum::Pointer(pointer_type) => { pointer_type }
+            _ => { return None; }
+        };
+        let array_pointer = match argument {
+            Expression::Left(pointer) => { pointer.clone() }
+            //数组右值没有地址，先存入临时变量
+            Expression::Right(LEBasicValueEnum::Array(array)) => {
+                let temporary = self.build_entry_alloca(le_context, array.ty.to_le_type_enum());
+                self.llvm_builder.build_store(temporary.llvm_value, array.llvm_value);
+                temporary
+            }
+            _ => { return None; }
+        };
+        match array_pointer.ty.get_point_type() {
+            LEBasicTypeEnum::Array(array_type) if array_type.element_type() == pointer_type.get_point_type() => {
+                let zero = le_context.i32_type().get_llvm_type().const_zero();
+                let llvm_value = unsafe { self.llvm_builder.build_in_bounds_gep(array_pointer.llvm_value, &[zero, zero], "") };
+                Some(LEPointerValue { ty: pointer_type.clone(), llvm_value })
+            }
+            _ => { None }
+        }
+    }
+
+    /// 生成内联汇编调用，汇编默认带有副作用，避免被优化掉
+    pub fn build_inline_asm(&self, le_context: &LEContext<'ctx>, function_type: FunctionType<'ctx>, return_type: Option<LEBasicTypeEnum<'ctx>>, assembly: String, constraints: String, args: &[LEBasicValueEnum<'ctx>]) -> Result<Expression<'ctx>> {
+        let asm = le_context.llvm_context.create_inline_asm(function_type, assembly, constraints, true, false, None, false);
+        let callable = CallableValue::try_from(asm).unwrap();
+        let args = args.iter().map(|arg| BasicMetadataValueEnum::from(arg.to_llvm_basic_value_enum())).collect::<Vec<_>>();
+        let site_value = self.llvm_builder.build_call(callable, &args, "");
+        match (site_value.try_as_basic_value().left(), return_type) {
+            (Some(v), Some(ty)) => { Ok(Expression::Right(LEBasicValueEnum::from_type_and_llvm_value(ty, v)?)) }
+            _ => { Ok(Expression::Unit) }
+        }
+    }
+
     pub fn build_alloca(&self, le_context: &LEContext<'ctx>, ty: LEBasicTypeEnum<'ctx>) -> LEPointerValue<'ctx> {
         let llvm_pointer_value = self.llvm_builder.build_alloca(ty.get_llvm_type(), "");
         let pointer_type = LEPointerType::new(le_context, ty);
         LEPointerValue { ty: pointer_type, llvm_value: llvm_pointer_value }
     }
 
+    /// 在当前函数的入口块开头申请内存，避免在循环中重复申请栈空间
+    pub fn build_entry_alloca(&self, le_context: &LEContext<'ctx>, ty: LEBasicTypeEnum<'ctx>) -> LEPointerValue<'ctx> {
+        let current_block = self.llvm_builder.get_insert_block().unwrap();
+        let entry_block = current_block.get_parent().unwrap().get_first_basic_block().unwrap();
+        match entry_block.get_first_instruction() {
+            Some(first_instruction) => { self.llvm_builder.position_at(entry_block, &first_instruction) }
+            None => { self.llvm_builder.position_at_end(entry_block) }
+        }
+        let pointer = self.build_alloca(le_context, ty);
+        self.llvm_builder.position_at_end(current_block);
+        pointer
+    }
+
     pub fn build_alloca_with_initial_value(&self, le_context: &LEContext<'ctx>, initial_value: LEBasicValueEnum<'ctx>) -> LEPointerValue<'ctx> {
         let target_type = LEBasicValue::get_le_type(&initial_value);
         let llvm_pointer_value = self.build_alloca(le_context, target_type.clone());
@@ -161,6 +365,7 @@ impl<'ctx> LEBuilder<'ctx> {
             LEBasicTypeEnum::Array(ref t) => { module.add_global(t.get_llvm_type(), address_space, "") }
             LEBasicTypeEnum::Struct(ref t) => { module.add_global(t.get_llvm_type(), address_space, "") }
             LEBasicTypeEnum::Vector(ref t) => { module.add_global(t.get_llvm_type(), address_space, "") }
+            LEBasicTypeEnum::FunctionPointer(ref t) => { module.add_global(t.get_llvm_type(), address_space, "") }
         }.as_pointer_value();
         LEPointerValue::from_type_and_llvm_value(ty, BasicValueEnum::PointerValue(global_ptr)).unwrap()
     }
@@ -175,6 +380,7 @@ impl<'ctx> LEBuilder<'ctx> {
             LEBasicTypeEnum::Array(ref t) => { module.add_global(t.get_llvm_type(), address_space, "") }
             LEBasicTypeEnum::Struct(ref t) => { module.add_global(t.get_llvm_type(), address_space, "") }
             LEBasicTypeEnum::Vector(ref t) => { module.add_global(t.get_llvm_type(), address_space, "") }
+            LEBasicTypeEnum::FunctionPointer(ref t) => { module.add_global(t.get_llvm_type(), address_space, "") }
         }.as_pointer_value();
         self.llvm_builder.build_store(global_ptr, value.get_llvm_value());
         LEPointerValue::from_type_and_llvm_value(target_type, BasicValueEnum::PointerValue(global_ptr)).unwrap()
@@ -186,21 +392,95 @@ impl<'ctx> LEBuilder<'ctx> {
         LEBasicValueEnum::from_type_and_llvm_value(ptr.ty.get_point_type(), value_enum).unwrap()
     }
 
+    /// 生成volatile的load指令
+    pub fn build_volatile_load(&self, le_context: &LEContext<'ctx>, ptr: LEPointerValue<'ctx>) -> LEBasicValueEnum<'ctx> {
+        let value_enum = self.llvm_builder.build_load(ptr.llvm_value, "");
+        value_enum.as_instruction_value().unwrap().set_volatile(true).unwrap();
+        LEBasicValueEnum::from_type_and_llvm_value(ptr.ty.get_point_type(), value_enum).unwrap()
+    }
+
     pub(crate) fn build_store(&self, le_context: &LEContext<'ctx>, ptr: LEPointerValue<'ctx>, value: LEBasicValueEnum<'ctx>) -> Result<()> {
         self.llvm_builder.build_store(ptr.llvm_value, value.to_llvm_basic_value_enum());
         Ok(())
     }
 
-    pub fn build_assign(&self, le_context: &LEContext<'ctx>, target: Expression<'ctx>, value: Expression<'ctx>) -> Result<LEPointerValue<'ctx>> {
+    /// 赋值表达式的值为赋值后的值，以支持连续赋值
+    pub fn build_assign(&self, le_context: &LEContext<'ctx>, target: Expression<'ctx>, value: Expression<'ctx>) -> Result<LEBasicValueEnum<'ctx>> {
+        match target {
+            Expression::Left(left_value) => {
+                let value = self.read_expression(le_context, value)?;
+                //数组之间赋值时元素类型和长度都必须一致
+                if let (LEBasicTypeEnum::Array(target_type), LEBasicValueEnum::Array(array_value)) = (left_value.ty.get_point_type(), &value) {
+                    if target_type.element_type() != array_value.ty.element_type() {
+                        return Err(CompileError::TypeMismatched { expect: target_type.to_string(), found: array_value.ty.to_string() });
+                    }
+                    if target_type.len() != array_value.ty.len() {
+                        return Err(CompileError::ArrayLengthMismatch { expect: target_type.len(), found: array_value.ty.len() });
+                    }
+                }
+                let casted_value = self.build_cast(le_context, Expression::Right(value), left_value.ty.get_point_type())?;
+                self.llvm_builder.build_store(left_value.llvm_value, casted_value.to_llvm_basic_value_enum());
+                Ok(casted_value)
+            }
+            Expression::BitField(storage, field) => {
+                let casted_value = self.build_cast(le_context, value, field.ty.to_le_type_enum())?;
+                self.build_bit_field_store(storage, field, casted_value.clone().try_into().unwrap());
+                Ok(casted_value)
+            }
+            _ => { Err(CompileError::ExpressionIsNotLeftValueExpression) }
+        }
+    }
+
+    /// 生成volatile的赋值，与build_assign相同，但store指令带有volatile标记
+    pub fn build_volatile_assign(&self, le_context: &LEContext<'ctx>, target: Expression<'ctx>, value: Expression<'ctx>) -> Result<LEBasicValueEnum<'ctx>> {
         if let Expression::Left(left_value) = target {
             let casted_value = self.build_cast(le_context, value, left_value.ty.get_point_type())?;
-            self.llvm_builder.build_store(left_value.llvm_value, casted_value.to_llvm_basic_value_enum());
-            Ok(left_value)
+            let store = self.llvm_builder.build_store(left_value.llvm_value, casted_value.to_llvm_basic_value_enum());
+            store.set_volatile(true).unwrap();
+            Ok(casted_value)
         } else {
             Err(CompileError::ExpressionIsNotLeftValueExpression)
         }
     }
 
+    /// 检查原子操作的指针参数，只允许指向整数的指针
+    fn read_atomic_pointer(&self, le_context: &LEContext<'ctx>, ptr: Expression<'ctx>) -> Result<(LEPointerValue<'ctx>, LEIntegerType<'ctx>)> {
+        let pointer_value = self.read_expression(le_context, ptr)?;
+        if let LEBasicValueEnum::Pointer(pointer) = pointer_value {
+            if let LEBasicTypeEnum::Integer(element_type) = pointer.ty.get_point_type() {
+                return Ok((pointer, element_type));
+            }
+            Err(CompileError::TypeMismatched { expect: "ref integer".into(), found: pointer.ty.to_string() })
+        } else {
+            Err(CompileError::TypeMismatched { expect: "ref integer".into(), found: pointer_value.get_le_type().to_string() })
+        }
+    }
+
+    /// 原子加法，返回指针指向的旧值
+    pub fn build_atomic_add(&self, le_context: &LEContext<'ctx>, ptr: Expression<'ctx>, value: Expression<'ctx>) -> Result<LEBasicValueEnum<'ctx>> {
+        let (pointer, element_type) = self.read_atomic_pointer(le_context, ptr)?;
+        let value: LEIntegerValue = self.build_cast(le_context, value, element_type.to_le_type_enum())?.try_into().unwrap();
+        let old_value = self.llvm_builder.build_atomicrmw(AtomicRMWBinOp::Add, pointer.llvm_value, value.llvm_value, AtomicOrdering::SequentiallyConsistent).unwrap();
+        Ok(LEIntegerValue { ty: element_type, llvm_value: old_value }.to_le_value_enum())
+    }
+
+    /// 原子读取
+    pub fn build_atomic_load(&self, le_context: &LEContext<'ctx>, ptr: Expression<'ctx>) -> Result<LEBasicValueEnum<'ctx>> {
+        let (pointer, element_type) = self.read_atomic_pointer(le_context, ptr)?;
+        let value = self.llvm_builder.build_load(pointer.llvm_value, "");
+        value.as_instruction_value().unwrap().set_atomic_ordering(AtomicOrdering::SequentiallyConsistent).unwrap();
+        Ok(LEIntegerValue { ty: element_type, llvm_value: value.into_int_value() }.to_le_value_enum())
+    }
+
+    /// 原子写入
+    pub fn build_atomic_store(&self, le_context: &LEContext<'ctx>, ptr: Expression<'ctx>, value: Expression<'ctx>) -> Result<()> {
+        let (pointer, element_type) = self.read_atomic_pointer(le_context, ptr)?;
+        let value = self.build_cast(le_context, value, element_type.to_le_type_enum())?;
+        let store = self.llvm_builder.build_store(pointer.llvm_value, value.to_llvm_basic_value_enum());
+        store.set_atomic_ordering(AtomicOrdering::SequentiallyConsistent).unwrap();
+        Ok(())
+    }
+
     pub fn build_neg(&self, le_context: &LEContext<'ctx>, value: Expression<'ctx>) -> Result<LEBasicValueEnum<'ctx>> {
         let target_value = self.read_expression(le_context, value)?;
         match target_value {
@@ -1,12 +1,12 @@
 use std::collections::HashMap;
-use std::fmt::{Display, Formatter, Write};
+use std::fmt::{Display, Formatter};
 use std::rc::Rc;
 
 use enum_dispatch::enum_dispatch;
 use inkwell::AddressSpace;
 use inkwell::types::{ArrayType, BasicTypeEnum, FloatType, IntType, PointerType, StructType, VectorType};
 
-use crate::code_generator::builder::{LEArrayValue, LEBoolValue, LEFloatValue, LEIntegerValue, LEPointerValue, LEStructValue, LEType, LEVectorValue};
+use crate::code_generator::builder::{LEArrayValue, LEBoolValue, LEFloatValue, LEFunctionPointerValue, LEFunctionType, LEIntegerValue, LEPointerValue, LEStructValue, LEType, LEVectorValue};
 use crate::code_generator::builder::le_wrapper::LEBasicType;
 use crate::code_generator::context::LEContext;
 
@@ -321,14 +321,7 @@ impl<'ctx> LEPointerType<'ctx> {
 
 impl<'ctx> Display for LEPointerType<'ctx> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut pointed_type = self.get_point_type();
-        let mut point_counter = 1;
-        while let LEBasicTypeEnum::Pointer(pointer) = pointed_type {
-            pointed_type = pointer.get_point_type();
-            point_counter += 1;
-        }
-        (0..point_counter).for_each(|_| f.write_char('*').unwrap());
-        write!(f, "{}", pointed_type)
+        write!(f, "ptr<{}>", self.get_point_type())
     }
 }
 
@@ -397,6 +390,14 @@ impl<'ctx> LEArrayType<'ctx> {
         }
     }
 
+    pub fn element_type(&self) -> LEBasicTypeEnum<'ctx> {
+        self.inner.element_type.clone()
+    }
+
+    pub fn len(&self) -> u32 {
+        self.inner.llvm_type.len()
+    }
+
     pub fn const_array(&self, values: &[LEArrayValue<'ctx>]) -> LEArrayValue<'ctx> {
         let llvm_values = values.iter().map(|v| v.llvm_value).collect::<Vec<_>>();
         let array_value = self.get_llvm_type().const_array(&llvm_values);
@@ -406,14 +407,50 @@ impl<'ctx> LEArrayType<'ctx> {
 
 impl<'ctx> Display for LEArrayType<'ctx> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[{};{}]", self.inner.element_type, self.inner.llvm_type.len())
+        write!(f, "[{}; {}]", self.inner.element_type, self.inner.llvm_type.len())
+    }
+}
+
+/// 结构体的位域成员，相邻的位域成员打包存储在同一个整数成员中
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LEBitField<'ctx> {
+    /// 存储该位域的整数成员在结构体中的下标
+    pub storage_index: u32,
+    /// 存储该位域的整数成员的类型
+    pub storage_type: LEIntegerType<'ctx>,
+    /// 位域在存储整数中的起始位
+    pub bit_offset: u32,
+    /// 位域本身的类型，如i3
+    pub ty: LEIntegerType<'ctx>,
+}
+
+/// 位宽不是8、16、32、64的整数成员视为位域
+fn is_bit_field_type(ty: &LEBasicTypeEnum) -> bool {
+    if let LEBasicTypeEnum::Integer(int_type) = ty {
+        !matches!(int_type.get_llvm_type().get_bit_width(), 8 | 16 | 32 | 64)
+    } else {
+        false
+    }
+}
+
+/// 能容纳指定位数的最小的存储整数位宽
+fn storage_bit_width(bits: u32) -> u32 {
+    match bits {
+        0..=8 => { 8 }
+        9..=16 => { 16 }
+        17..=32 => { 32 }
+        _ => { 64 }
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct LEStructTypeInner<'ctx> {
+    pub name: String,
     pub llvm_type: StructType<'ctx>,
     pub member_offset: HashMap<String, (u32, LEBasicTypeEnum<'ctx>)>,
+    /// 按声明顺序排列的成员名，用于按下标访问成员
+    pub member_names: Vec<String>,
+    pub bit_fields: HashMap<String, LEBitField<'ctx>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -465,25 +502,97 @@ impl<'ctx> LEBasicType<'ctx> for LEStructType<'ctx> {
 
 impl<'ctx> Display for LEStructType<'ctx> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self.name())
+        write!(f, "struct {}", self.get_name())
     }
 }
 
 
 impl<'ctx> LEStructType<'ctx> {
-    pub fn from_llvm_type(context: &LEContext<'ctx>, names: &[&str], member_types: &[LEBasicTypeEnum<'ctx>]) -> Self {
+    pub fn from_llvm_type(context: &LEContext<'ctx>, struct_name: &str, names: &[&str], member_types: &[LEBasicTypeEnum<'ctx>]) -> Self {
+        let order = (0..names.len()).collect::<Vec<_>>();
+        Self::from_llvm_type_with_layout(context, struct_name, names, member_types, &order, true)
+    }
+
+    /// 按指定的布局创建结构体类型，order为成员在内存中的排列顺序，元素为成员在声明中的下标
+    /// packed为true时成员之间没有填充，否则按C的规则对齐每个成员
+    pub fn from_llvm_type_with_layout(context: &LEContext<'ctx>, struct_name: &str, names: &[&str], member_types: &[LEBasicTypeEnum<'ctx>], order: &[usize], packed: bool) -> Self {
         let mut offset = HashMap::default();
-        for (index, (name, ty)) in names.iter().zip(member_types.iter()).enumerate() {
-            offset.entry(name.to_string()).or_insert((index as u32, ty.clone()));
+        let mut bit_fields = HashMap::default();
+        let mut storage_types = vec![];
+        //当前正在打包的位域成员及其总位数
+        let mut pending_bit_fields: Vec<(&str, LEIntegerType<'ctx>, u32)> = vec![];
+        let mut pending_bits = 0;
+        for (name, ty) in order.iter().map(|index| (&names[*index], &member_types[*index])) {
+            if is_bit_field_type(ty) {
+                let int_type: LEIntegerType = ty.clone().try_into().unwrap();
+                let bit_width = int_type.get_llvm_type().get_bit_width();
+                if pending_bits + bit_width > 64 {
+                    Self::pack_bit_fields(context, &mut pending_bit_fields, &mut pending_bits, &mut storage_types, &mut bit_fields);
+                }
+                pending_bit_fields.push((*name, int_type, pending_bits));
+                pending_bits += bit_width;
+            } else {
+                Self::pack_bit_fields(context, &mut pending_bit_fields, &mut pending_bits, &mut storage_types, &mut bit_fields);
+                offset.entry(name.to_string()).or_insert((storage_types.len() as u32, ty.clone()));
+                storage_types.push(ty.clone());
+            }
         }
-        let struct_type = context.llvm_context.opaque_struct_type("struct");
-        struct_type.set_body(&member_types
+        Self::pack_bit_fields(context, &mut pending_bit_fields, &mut pending_bits, &mut storage_types, &mut bit_fields);
+        let struct_type = context.llvm_context.opaque_struct_type(struct_name);
+        struct_type.set_body(&storage_types
             .iter()
             .map(|x| x.get_llvm_basic_type())
-            .collect::<Vec<_>>(), true,
+            .collect::<Vec<_>>(), packed,
         );
-        Self { inner: Rc::new(LEStructTypeInner { llvm_type: struct_type, member_offset: offset }) }
+        Self { inner: Rc::new(LEStructTypeInner { name: struct_name.into(), llvm_type: struct_type, member_offset: offset, member_names: names.iter().map(|name| name.to_string()).collect(), bit_fields }) }
+    }
+
+    /// 将已收集的相邻位域成员打包进一个新的整数成员
+    fn pack_bit_fields<'a>(context: &LEContext<'ctx>,
+                           pending_bit_fields: &mut Vec<(&'a str, LEIntegerType<'ctx>, u32)>,
+                           pending_bits: &mut u32,
+                           storage_types: &mut Vec<LEBasicTypeEnum<'ctx>>,
+                           bit_fields: &mut HashMap<String, LEBitField<'ctx>>) {
+        if pending_bit_fields.is_empty() {
+            return;
+        }
+        let storage_type = LEIntegerType::from_llvm_type(context.llvm_context.custom_width_int_type(storage_bit_width(*pending_bits)), false);
+        let storage_index = storage_types.len() as u32;
+        for (name, ty, bit_offset) in pending_bit_fields.drain(..) {
+            bit_fields.entry(name.to_string()).or_insert(LEBitField { storage_index, storage_type: storage_type.clone(), bit_offset, ty });
+        }
+        storage_types.push(storage_type.to_le_type_enum());
+        *pending_bits = 0;
+    }
+
+    /// 获取位域成员的信息，不是位域成员时返回None
+    pub fn get_bit_field(&self, name: &str) -> Option<LEBitField<'ctx>> {
+        self.inner.bit_fields.get(name).cloned()
+    }
+
+    /// 按存储顺序获取每个LLVM成员的类型，打包在一起的位域成员对应其存储用的整数类型
+    pub fn get_storage_types(&self) -> Vec<LEBasicTypeEnum<'ctx>> {
+        (0..self.inner.llvm_type.count_fields()).map(|index| {
+            self.inner.member_offset.values()
+                .find(|(offset, _)| *offset == index)
+                .map(|(_, ty)| ty.clone())
+                .or_else(|| self.inner.bit_fields.values()
+                    .find(|bit_field| bit_field.storage_index == index)
+                    .map(|bit_field| bit_field.storage_type.to_le_type_enum()))
+                .unwrap()
+        }).collect()
+    }
+
+    /// 获取结构体在源码中声明的名字
+    pub fn get_name(&self) -> &str {
+        &self.inner.name
     }
+
+    /// 按声明顺序获取第index个成员的名字，下标越界时返回None
+    pub fn get_member_name(&self, index: usize) -> Option<&str> {
+        self.inner.member_names.get(index).map(|name| name.as_str())
+    }
+
     pub fn get_member_offset(&self, name: &str) -> Option<u32> {
         let offset = self.inner.member_offset.get(name)?;
         Some(offset.0)
@@ -507,8 +616,9 @@ impl<'ctx> LEStructType<'ctx> {
 }
 
 
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct LEVectorTypeInner<'ctx> {
+    pub element_type: LEBasicTypeEnum<'ctx>,
     pub llvm_type: VectorType<'ctx>,
 }
 
@@ -518,6 +628,24 @@ pub struct LEVectorType<'ctx> {
 }
 
 impl<'ctx> LEVectorType<'ctx> {
+    /// 创建元素类型为element_type、长度为len的向量类型，元素类型不是整数或浮点数时返回None
+    pub fn new(element_type: LEBasicTypeEnum<'ctx>, len: u32) -> Option<Self> {
+        let llvm_type = match &element_type {
+            LEBasicTypeEnum::Integer(t) => { t.get_llvm_type().vec_type(len) }
+            LEBasicTypeEnum::Float(t) => { t.get_llvm_type().vec_type(len) }
+            _ => { return None; }
+        };
+        Some(Self { inner: Rc::new(LEVectorTypeInner { element_type, llvm_type }) })
+    }
+
+    pub fn element_type(&self) -> LEBasicTypeEnum<'ctx> {
+        self.inner.element_type.clone()
+    }
+
+    pub fn len(&self) -> u32 {
+        self.inner.llvm_type.get_size()
+    }
+
     pub fn const_array(&self, values: &[LEVectorValue<'ctx>]) -> LEArrayValue<'ctx> {
         let llvm_values = values.iter().map(|v| v.llvm_value).collect::<Vec<_>>();
         let array_value = self.get_llvm_type().const_array(&llvm_values);
@@ -568,7 +696,87 @@ impl<'ctx> LEBasicType<'ctx> for LEVectorType<'ctx> {
 
 impl<'ctx> Display for LEVectorType<'ctx> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        write!(f, "vec<{}, {}>", self.inner.element_type, self.len())
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LEFunctionPointerTypeInner<'ctx> {
+    pub function_type: LEFunctionType<'ctx>,
+    pub llvm_type: PointerType<'ctx>,
+}
+
+/// 指向函数的指针类型，签名相同的函数指针类型相同
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LEFunctionPointerType<'ctx> {
+    inner: Rc<LEFunctionPointerTypeInner<'ctx>>,
+}
+
+impl<'ctx> LEFunctionPointerType<'ctx> {
+    pub fn from_function_type(function_type: LEFunctionType<'ctx>) -> Self {
+        let llvm_type = function_type.get_llvm_type().ptr_type(AddressSpace::Generic);
+        Self { inner: Rc::new(LEFunctionPointerTypeInner { function_type, llvm_type }) }
+    }
+
+    pub fn function_type(&self) -> &LEFunctionType<'ctx> {
+        &self.inner.function_type
+    }
+
+    pub fn const_array(&self, values: &[LEFunctionPointerValue<'ctx>]) -> LEArrayValue<'ctx> {
+        let llvm_values = values.iter().map(|v| v.llvm_value).collect::<Vec<_>>();
+        let array_value = self.get_llvm_type().const_array(&llvm_values);
+        LEArrayValue { ty: self.get_array_type(values.len() as u32), llvm_value: array_value }
+    }
+}
+
+impl<'ctx> LEType<'ctx> for LEFunctionPointerType<'ctx> {
+    type LLVM_Type = PointerType<'ctx>;
+
+    fn get_llvm_type(&self) -> Self::LLVM_Type {
+        self.inner.llvm_type
+    }
+
+    fn name(&self) -> &'static str {
+        "FunctionPointer"
+    }
+}
+
+impl<'ctx> LEBasicType<'ctx> for LEFunctionPointerType<'ctx> {
+    fn to_le_type_enum(&self) -> LEBasicTypeEnum<'ctx> {
+        LEBasicTypeEnum::FunctionPointer(self.clone())
+    }
+
+    fn get_array_type(&self, len: u32) -> LEArrayType<'ctx> {
+        let llvm_type = self.get_llvm_type();
+        let array_type = llvm_type.array_type(len);
+        LEArrayType {
+            inner: Rc::new(LEArrayTypeInner {
+                element_type: self.clone().to_le_type_enum(),
+                llvm_type: array_type,
+            })
+        }
+    }
+
+    fn get_pointer_type(&self) -> LEPointerType<'ctx> {
+        let llvm_type = self.get_llvm_type();
+        let pointer_type = llvm_type.ptr_type(0.into());
+        LEPointerType {
+            inner: Rc::new(LEPointerTypeInner {
+                point_type: self.to_le_type_enum(),
+                llvm_type: pointer_type,
+            })
+        }
+    }
+}
+
+impl<'ctx> Display for LEFunctionPointerType<'ctx> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let params = self.inner.function_type.param_types().iter().map(|ty| ty.to_string()).collect::<Vec<_>>().join(", ");
+        match self.inner.function_type.return_type() {
+            Some(return_type) => { write!(f, "le({})->{}", params, return_type) }
+            None => { write!(f, "le({})", params) }
+        }
     }
 }
 
@@ -590,6 +798,8 @@ pub enum LEBasicTypeEnum<'ctx> {
     Struct(LEStructType<'ctx>),
     /// A contiguous homogeneous "SIMD" container type.
     Vector(LEVectorType<'ctx>),
+    /// A pointer to function type.
+    FunctionPointer(LEFunctionPointerType<'ctx>),
 }
 
 // impl<'ctx> LEBasicType<'ctx> for LEBasicTypeEnum<'ctx> {
@@ -634,6 +844,7 @@ impl<'ctx> LEType<'ctx> for LEBasicTypeEnum<'ctx> {
             LEBasicTypeEnum::Array(t) => { t.get_llvm_basic_type() }
             LEBasicTypeEnum::Struct(t) => { t.get_llvm_basic_type() }
             LEBasicTypeEnum::Vector(t) => { t.get_llvm_basic_type() }
+            LEBasicTypeEnum::FunctionPointer(t) => { t.get_llvm_basic_type() }
         }
     }
 
@@ -646,6 +857,7 @@ impl<'ctx> LEType<'ctx> for LEBasicTypeEnum<'ctx> {
             LEBasicTypeEnum::Array(t) => { t.name() }
             LEBasicTypeEnum::Struct(t) => { t.name() }
             LEBasicTypeEnum::Vector(t) => { t.name() }
+            LEBasicTypeEnum::FunctionPointer(t) => { t.name() }
         }
     }
 
@@ -698,6 +910,14 @@ impl<'ctx> LEBasicTypeEnum<'ctx> {
         }
     }
 
+    pub fn into_function_pointer_type(self) -> Option<LEFunctionPointerType<'ctx>> {
+        if let LEBasicTypeEnum::FunctionPointer(i) = self {
+            Some(i)
+        } else {
+            None
+        }
+    }
+
     pub fn into_bool_type(self) -> Option<LEBoolType<'ctx>> {
         if let LEBasicTypeEnum::Bool(i) = self {
             Some(i)
@@ -727,6 +947,9 @@ impl<'ctx> LEBasicTypeEnum<'ctx> {
     pub fn is_vector_type(&self) -> bool {
         matches!(self,LEBasicTypeEnum::Vector(_))
     }
+    pub fn is_function_pointer_type(&self) -> bool {
+        matches!(self,LEBasicTypeEnum::FunctionPointer(_))
+    }
 }
 
 
@@ -740,6 +963,7 @@ impl<'ctx> Display for LEBasicTypeEnum<'ctx> {
             LEBasicTypeEnum::Pointer(t) => { t.fmt(f) }
             LEBasicTypeEnum::Struct(t) => { t.fmt(f) }
             LEBasicTypeEnum::Vector(t) => { t.fmt(f) }
+            LEBasicTypeEnum::FunctionPointer(t) => { t.fmt(f) }
         }
     }
 }
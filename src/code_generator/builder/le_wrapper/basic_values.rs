@@ -4,7 +4,7 @@ use enum_dispatch::enum_dispatch;
 use inkwell::types::{ArrayType, BasicTypeEnum, FloatType, FunctionType, IntType, PointerType};
 use inkwell::values::{AnyValueEnum, ArrayValue, BasicValueEnum, FloatValue, FunctionValue, IntValue, PointerValue, StructValue, VectorValue};
 
-use crate::code_generator::builder::{LEArrayType, LEBoolType, LEStructType, LEValue, LEVectorType};
+use crate::code_generator::builder::{LEArrayType, LEBoolType, LEFunctionPointerType, LEStructType, LEValue, LEVectorType};
 use crate::code_generator::builder::le_wrapper::{LEBasicType, LEBasicTypeEnum, LEBasicValue, LEFloatType, LEIntegerType, LEPointerType};
 use crate::code_generator::Result;
 use crate::error::CompileError;
@@ -301,6 +301,48 @@ impl<'ctx> Display for LEVectorValue<'ctx> {
 }
 
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LEFunctionPointerValue<'ctx> {
+    pub ty: LEFunctionPointerType<'ctx>,
+    pub llvm_value: PointerValue<'ctx>,
+}
+
+
+impl<'ctx> LEValue<'ctx> for LEFunctionPointerValue<'ctx> {
+    type LLVM_Value_Type = PointerValue<'ctx>;
+    type LEType = LEFunctionPointerType<'ctx>;
+
+    fn get_llvm_value(&self) -> Self::LLVM_Value_Type {
+        self.llvm_value
+    }
+
+    fn from_type_and_llvm_value(ty: LEBasicTypeEnum<'ctx>, value: BasicValueEnum<'ctx>) -> Result<Self> {
+        if let BasicValueEnum::PointerValue(i) = value {
+            if let LEBasicTypeEnum::FunctionPointer(t) = ty {
+                return Ok(LEFunctionPointerValue { ty: t, llvm_value: i });
+            }
+        }
+        Err(CompileError::TypeMismatched { expect: "LEFunctionPointerType".into(), found: ty.to_string() })
+    }
+}
+
+impl<'ctx> LEBasicValue<'ctx> for LEFunctionPointerValue<'ctx> {
+    fn to_le_value_enum(&self) -> LEBasicValueEnum<'ctx> {
+        LEBasicValueEnum::FunctionPointer(self.clone())
+    }
+
+    fn get_le_type(&self) -> LEBasicTypeEnum<'ctx> {
+        self.ty.clone().to_le_type_enum()
+    }
+}
+
+impl<'ctx> Display for LEFunctionPointerValue<'ctx> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[enum_dispatch]
 pub enum LEBasicValueEnum<'ctx> {
@@ -311,6 +353,7 @@ pub enum LEBasicValueEnum<'ctx> {
     Array(LEArrayValue<'ctx>),
     Struct(LEStructValue<'ctx>),
     Vector(LEVectorValue<'ctx>),
+    FunctionPointer(LEFunctionPointerValue<'ctx>),
 }
 
 
@@ -327,6 +370,7 @@ impl<'ctx> LEValue<'ctx> for LEBasicValueEnum<'ctx> {
             LEBasicValueEnum::Array(i) => { i.get_llvm_basic_value() }
             LEBasicValueEnum::Struct(i) => { i.get_llvm_basic_value() }
             LEBasicValueEnum::Vector(i) => { i.get_llvm_basic_value() }
+            LEBasicValueEnum::FunctionPointer(i) => { i.get_llvm_basic_value() }
         }
     }
 
@@ -339,6 +383,7 @@ impl<'ctx> LEValue<'ctx> for LEBasicValueEnum<'ctx> {
             (BasicValueEnum::StructValue(v), LEBasicTypeEnum::Struct(t)) => { Ok(LEBasicValueEnum::Struct(LEStructValue { ty: t, llvm_value: v })) }
             (BasicValueEnum::VectorValue(v), LEBasicTypeEnum::Vector(t)) => { Ok(LEBasicValueEnum::Vector(LEVectorValue { ty: t, llvm_value: v })) }
             (BasicValueEnum::PointerValue(v), LEBasicTypeEnum::Pointer(t)) => { Ok(LEBasicValueEnum::Pointer(LEPointerValue { ty: t, llvm_value: v })) }
+            (BasicValueEnum::PointerValue(v), LEBasicTypeEnum::FunctionPointer(t)) => { Ok(LEBasicValueEnum::FunctionPointer(LEFunctionPointerValue { ty: t, llvm_value: v })) }
             _ => { unreachable!() }
         }
     }
@@ -426,6 +471,7 @@ impl<'ctx> LEBasicValueEnum<'ctx> {
             LEBasicValueEnum::Array(i) => { BasicValueEnum::ArrayValue(i.llvm_value) }
             LEBasicValueEnum::Struct(i) => { BasicValueEnum::StructValue(i.llvm_value) }
             LEBasicValueEnum::Vector(i) => { BasicValueEnum::VectorValue(i.llvm_value) }
+            LEBasicValueEnum::FunctionPointer(i) => { BasicValueEnum::PointerValue(i.llvm_value) }
         }
     }
 }
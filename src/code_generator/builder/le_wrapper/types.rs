@@ -9,6 +9,8 @@ struct LEFunctionTypeInner<'ctx> {
     pub llvm_type: FunctionType<'ctx>,
     pub return_type: Option<LEBasicTypeEnum<'ctx>>,
     pub param_types: Vec<LEBasicTypeEnum<'ctx>>,
+    /// 返回值是否通过第一个隐藏的sret指针参数传出
+    pub struct_return: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -17,18 +19,26 @@ pub struct LEFunctionType<'ctx> {
 }
 
 impl<'ctx> LEFunctionType<'ctx> {
+    pub fn get_llvm_type(&self) -> FunctionType<'ctx> {
+        self.inner.llvm_type
+    }
     pub fn return_type(&self) -> Option<LEBasicTypeEnum<'ctx>> {
         self.inner.return_type.clone()
     }
     pub fn param_types(&self) -> &[LEBasicTypeEnum<'ctx>] {
         &self.inner.param_types
     }
-    pub fn new(llvm_type: FunctionType<'ctx>, return_type: Option<LEBasicTypeEnum<'ctx>>, param_types: Vec<LEBasicTypeEnum<'ctx>>) -> Self {
+    /// 返回值是否通过sret参数传出，此时LLVM函数的返回类型为void，第一个参数为返回值的指针
+    pub fn struct_return(&self) -> bool {
+        self.inner.struct_return
+    }
+    pub fn new(llvm_type: FunctionType<'ctx>, return_type: Option<LEBasicTypeEnum<'ctx>>, param_types: Vec<LEBasicTypeEnum<'ctx>>, struct_return: bool) -> Self {
         Self {
             inner: Rc::new(LEFunctionTypeInner {
                 llvm_type,
                 return_type,
                 param_types,
+                struct_return,
             })
         }
     }
@@ -1,10 +1,12 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
 use inkwell::basic_block::BasicBlock;
 use inkwell::context::Context;
 use inkwell::values::FunctionValue;
 
 use crate::ast::nodes::TypeDeclarator;
 use crate::code_generator::builder;
-use crate::code_generator::builder::{LEBasicTypeEnum, LEFunctionValue, LEPointerValue};
+use crate::code_generator::builder::{LEBasicTypeEnum, LEBasicValueEnum, LEFunctionValue, LEPointerValue, LEStructType};
 use crate::code_generator::context::symbol_table::{Symbol, SymbolTable};
 use crate::lexer::Position;
 
@@ -16,6 +18,33 @@ pub struct CompilerContext<'ctx> {
     pub current_function: Option<FunctionValue<'ctx>>,
     pub return_variable: Option<LEPointerValue<'ctx>>,
     pub return_block: Option<BasicBlock<'ctx>>,
+    /// 已生成的全局常量字符串，相同内容的字符串共享同一个全局变量
+    pub string_table: HashMap<String, LEPointerValue<'ctx>>,
+    /// 已生成的全局常量，以类型和初始值为键，相同初始值的常量共享同一个全局变量
+    pub constant_table: HashMap<String, LEPointerValue<'ctx>>,
+    /// 当前所在的所有循环的跳转目标，最内层的循环在最后
+    pub loop_blocks: Vec<LoopBlocks<'ctx>>,
+    /// 标记为`@noreturn`的函数，调用之后的代码不可达
+    pub noreturn_functions: HashSet<FunctionValue<'ctx>>,
+    /// 内置函数用到的C运行时函数，如`strlen`、`strcmp`
+    pub runtime_functions: BTreeMap<&'static str, LEFunctionValue<'ctx>>,
+    /// 内置函数`layout`返回的结构体类型，第一次使用时创建
+    pub layout_type: Option<LEStructType<'ctx>>,
+    /// 整数运算内置函数用到的LLVM intrinsic，如`llvm.sadd.with.overflow.i32`
+    pub intrinsic_functions: BTreeMap<String, FunctionValue<'ctx>>,
+    /// 内置函数`checked_add`返回的结构体类型，以整数类型名为键，第一次使用时创建
+    pub checked_types: HashMap<String, LEStructType<'ctx>>,
+}
+
+/// 循环中break和continue的跳转目标
+#[derive(Debug, Clone)]
+pub struct LoopBlocks<'ctx> {
+    /// break跳转到循环之后的块
+    pub break_block: BasicBlock<'ctx>,
+    /// continue在for循环中跳转到步进块，在while循环中跳转到条件块
+    pub continue_block: BasicBlock<'ctx>,
+    /// 循环表达式中每个`break value;`的值和所在的块，用于在循环之后的块生成phi，循环语句为None
+    pub break_values: Option<Vec<(LEBasicValueEnum<'ctx>, BasicBlock<'ctx>)>>,
 }
 
 
@@ -26,6 +55,14 @@ impl<'ctx> CompilerContext<'ctx> {
             current_function: None,
             return_variable: None,
             return_block: None,
+            string_table: HashMap::default(),
+            constant_table: HashMap::default(),
+            loop_blocks: vec![],
+            noreturn_functions: HashSet::default(),
+            runtime_functions: BTreeMap::default(),
+            layout_type: None,
+            intrinsic_functions: BTreeMap::default(),
+            checked_types: HashMap::default(),
         }
     }
 
@@ -43,6 +80,11 @@ impl<'ctx> CompilerContext<'ctx> {
         self.return_block = Some(return_block);
     }
 
+    /// 获取当前正在生成的函数的返回值类型，无返回值的函数返回None
+    pub fn current_return_type(&self) -> Option<LEBasicTypeEnum<'ctx>> {
+        self.return_variable.as_ref().map(|variable| variable.ty.get_point_type())
+    }
+
     pub fn insert_global_variable(&mut self, name: String, variable: LEPointerValue<'ctx>, defined_position: Position) -> Result<LEPointerValue<'ctx>> {
         self.symbols.insert_global_variable(name.into(), variable.clone(), defined_position)?;
         Ok(variable)
@@ -72,6 +114,33 @@ impl<'ctx> CompilerContext<'ctx> {
         Ok(function)
     }
 
+    pub fn remove_global_function(&mut self, name: &str) -> Option<Symbol<'ctx>> {
+        self.symbols.remove_global_symbol(name)
+    }
+
+    /// 重新放回之前删除的全局符号，用于替换函数失败时恢复原函数
+    pub fn restore_global_symbol(&mut self, name: String, symbol: Symbol<'ctx>) {
+        self.symbols.remove_global_symbol(&name);
+        self.symbols.insert_global_symbol(name, symbol).unwrap();
+    }
+
+    /// 当前块级符号表的层数，生成函数失败时用于丢弃函数内未弹出的符号表
+    pub fn block_depth(&self) -> usize {
+        self.symbols.block_depth()
+    }
+
+    pub fn truncate_block_tables(&mut self, depth: usize) {
+        self.symbols.truncate_block_tables(depth);
+        self.loop_blocks.clear();
+    }
+
+    pub fn mark_noreturn(&mut self, function: FunctionValue<'ctx>) {
+        self.noreturn_functions.insert(function);
+    }
+
+    pub fn is_noreturn(&self, function: FunctionValue<'ctx>) -> bool {
+        self.noreturn_functions.contains(&function)
+    }
 
     pub fn get_variable(&self, identifier: &str) -> Result<LEPointerValue<'ctx>> {
         self.symbols.get_variable(identifier)
@@ -88,4 +157,57 @@ impl<'ctx> CompilerContext<'ctx> {
     pub fn get_symbol(&self, identifier: &str) -> Option<Symbol<'ctx>> {
         self.symbols.get_symbol(identifier)
     }
+
+    pub fn get_similar_function(&self, name: &str) -> Option<String> {
+        self.symbols.get_similar_function(name)
+    }
+
+    /// 当前作用域中可见的变量及其类型，内层的变量在前
+    pub fn variables_in_scope(&self) -> Vec<(String, LEBasicTypeEnum<'ctx>)> {
+        self.symbols.variables_in_scope()
+    }
+
+    /// 遍历所有全局函数的名字和对应的LLVM函数，用于自定义的变换
+    pub fn functions(&self) -> impl Iterator<Item=(&str, FunctionValue<'ctx>)> + '_ {
+        self.symbols.functions()
+    }
+
+    pub fn exported_symbols(&self) -> Vec<String> {
+        self.symbols.exported_symbols()
+    }
+
+    pub fn undefined_symbols(&self) -> Vec<String> {
+        self.symbols.undefined_symbols()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use inkwell::context::Context;
+
+    use crate::code_generator::builder::{LEBasicType, LEFloatType, LEIntegerType, LEPointerValue};
+    use crate::code_generator::context::compile_context::CompilerContext;
+    use crate::lexer::Position;
+
+    #[test]
+    fn test_variables_in_scope() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let i32_type = LEIntegerType::from_llvm_type(context.i32_type(), true);
+        let f64_type = LEFloatType::from_llvm_type(context.f64_type(), true);
+        let outer = LEPointerValue { ty: i32_type.get_pointer_type(), llvm_value: module.add_global(context.i32_type(), None, "outer").as_pointer_value() };
+        let inner = LEPointerValue { ty: f64_type.get_pointer_type(), llvm_value: module.add_global(context.f64_type(), None, "inner").as_pointer_value() };
+        let mut compiler_context = CompilerContext::new(&context);
+        compiler_context.push_block_table();
+        compiler_context.insert_local_variable("outer".into(), outer, Position { range: 0..0 }).unwrap();
+        compiler_context.push_block_table();
+        compiler_context.insert_local_variable("inner".into(), inner, Position { range: 0..0 }).unwrap();
+        let variables = compiler_context.variables_in_scope();
+        assert_eq!(variables, vec![
+            ("inner".to_string(), f64_type.to_le_type_enum()),
+            ("outer".to_string(), i32_type.to_le_type_enum()),
+        ]);
+        compiler_context.pop_block_table();
+        assert_eq!(compiler_context.variables_in_scope().len(), 1);
+    }
 }
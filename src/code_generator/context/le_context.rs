@@ -1,4 +1,6 @@
+use inkwell::attributes::Attribute;
 use inkwell::context::Context;
+use inkwell::types::AnyType;
 
 use crate::ast::nodes::{Identifier, TypeDeclarator};
 use crate::code_generator::builder::{LEBasicTypeEnum, LEBoolType, LEFloatType, LEFunctionValue, LEIntegerType, LEPointerValue};
@@ -18,6 +20,12 @@ impl<'ctx> LEContext<'ctx> {
             compiler_context: CompilerContext::new(llvm_context),
         }
     }
+    /// 创建标记sret参数的属性，属性中需要带上返回值的类型
+    pub fn struct_return_attribute(&self, return_type: LEBasicTypeEnum<'ctx>) -> Attribute {
+        let kind_id = Attribute::get_named_enum_kind_id("sret");
+        self.llvm_context.create_type_attribute(kind_id, return_type.get_llvm_basic_type().as_any_type_enum())
+    }
+
     pub fn bool_type(&self) -> LEBoolType<'ctx> {
         self.compiler_context.symbols.bool_type()
     }
@@ -1,3 +1,4 @@
+pub use compile_context::LoopBlocks;
 pub use le_context::LEContext;
 
 mod le_context;
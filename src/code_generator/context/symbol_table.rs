@@ -1,9 +1,10 @@
 use std::collections::HashMap;
 
 use inkwell::context::Context;
+use inkwell::values::FunctionValue;
 
 use crate::ast::nodes::TypeDeclarator;
-use crate::code_generator::builder::{LEBasicType, LEBasicTypeEnum, LEBoolType, LEBoolValue, LEFloatType, LEFunctionValue, LEIntegerType, LEPointerValue};
+use crate::code_generator::builder::{LEBasicType, LEBasicTypeEnum, LEBoolType, LEBoolValue, LEFloatType, LEFunctionValue, LEIntegerType, LEPointerValue, LEVectorType};
 use crate::code_generator::Result;
 use crate::error::CompileError;
 use crate::lexer::Position;
@@ -49,6 +50,30 @@ impl<'ctx> Symbol<'ctx> {
     }
 }
 
+fn is_type_function_collision(defined: &Symbol, new: &Symbol) -> bool {
+    matches!((defined, new), (Symbol::Type(_), Symbol::Function(_)) | (Symbol::Function(_), Symbol::Type(_)))
+}
+
+/// 给出名字建议时允许的最大编辑距离
+const MAX_SUGGESTION_DISTANCE: usize = 3;
+
+/// 计算两个字符串的编辑距离
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitute_cost = if a_char == *b_char { 0 } else { 1 };
+            current[j + 1] = (previous[j] + substitute_cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
 #[derive(Clone, Debug)]
 struct BuiltinTypes<'ctx> {
     bool_type: LEBoolType<'ctx>,
@@ -206,6 +231,15 @@ impl<'ctx> SymbolTable<'ctx> {
                 let pointer_type = LEBasicType::get_pointer_type(&point_type);
                 Ok(pointer_type.to_le_type_enum())
             }
+            TypeDeclarator::Vector(vector) => {
+                let element_type = self.get_type(&vector.element_type)?;
+                //长度为0或超出u32范围的向量在LLVM中无法表示
+                let len = u32::try_from(vector.len).ok().filter(|len| *len > 0)
+                    .ok_or(CompileError::InvalidVectorLength { len: vector.len })?;
+                let vector_type = LEVectorType::new(element_type.clone(), len)
+                    .ok_or_else(|| CompileError::TypeMismatched { expect: "integer or float".into(), found: element_type.to_string() })?;
+                Ok(vector_type.to_le_type_enum())
+            }
         }
     }
 
@@ -227,6 +261,15 @@ impl<'ctx> SymbolTable<'ctx> {
         }
     }
 
+    /// 获取符号定义的位置，内置符号的位置为空
+    pub fn get_defined_position(&self, identifier: &str) -> Option<Position> {
+        match self.get_symbol(identifier)? {
+            Symbol::Type(t) => { Some(t.meta.defined_pos) }
+            Symbol::Variable(v) => { Some(v.meta.defined_pos) }
+            Symbol::Function(f) => { Some(f.meta.defined_pos) }
+        }
+    }
+
     pub fn get_symbol(&self, identifier: &str) -> Option<Symbol<'ctx>> {
         for block_symbols in self.table.iter().rev() {
             if let Some(symbol) = block_symbols.get(identifier) {
@@ -238,9 +281,13 @@ impl<'ctx> SymbolTable<'ctx> {
 
     pub fn insert_global_symbol(&mut self, name: String, symbol: Symbol<'ctx>) -> Result<()> {
         let global_table = self.table.first_mut().unwrap();
-        if let Some(symbol) = global_table.get(&name) {
-            return if !symbol.is_builtin() {
-                let defined_position = match symbol {
+        if let Some(defined_symbol) = global_table.get(&name) {
+            //函数与类型同名时无法确定名字指代的是哪一个
+            if !defined_symbol.is_builtin() && is_type_function_collision(defined_symbol, &symbol) {
+                return Err(CompileError::NameCollision { name });
+            }
+            return if !defined_symbol.is_builtin() {
+                let defined_position = match defined_symbol {
                     Symbol::Type(t) => { t.meta.defined_pos.clone() }
                     Symbol::Variable(v) => { v.meta.defined_pos.clone() }
                     Symbol::Function(f) => { f.meta.defined_pos.clone() }
@@ -255,6 +302,11 @@ impl<'ctx> SymbolTable<'ctx> {
         Ok(())
     }
 
+    /// 从全局符号表中删除符号，用于替换已经定义的函数
+    pub fn remove_global_symbol(&mut self, name: &str) -> Option<Symbol<'ctx>> {
+        self.table.first_mut().unwrap().remove(name)
+    }
+
     pub fn insert_local_symbol(&mut self, name: String, symbol: Symbol<'ctx>) -> Result<()> {
         if let Some(symbol) = self.get_symbol(&name) {
             return if !symbol.is_builtin() {
@@ -297,12 +349,90 @@ impl<'ctx> SymbolTable<'ctx> {
         self.insert_local_symbol(name, Symbol::Variable(Variable { pointer: value, meta: MetaData { defined_pos: position, is_built_in: false } }))
     }
 
+    /// 在所有可见的函数中查找与给定名字编辑距离最近的函数名，用于错误提示
+    pub fn get_similar_function(&self, name: &str) -> Option<String> {
+        self.table.iter()
+            .flat_map(|block_symbols| block_symbols.iter())
+            .filter(|(_, symbol)| matches!(symbol, Symbol::Function(_)))
+            .map(|(function_name, _)| (edit_distance(name, function_name), function_name))
+            .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+            .min_by(|x, y| x.0.cmp(&y.0).then_with(|| x.1.cmp(y.1)))
+            .map(|(_, function_name)| function_name.clone())
+    }
+
+    /// 从内层到外层列出当前可见的变量及其类型，被内层同名变量遮蔽的外层变量不会列出
+    pub fn variables_in_scope(&self) -> Vec<(String, LEBasicTypeEnum<'ctx>)> {
+        let mut variables: Vec<(String, LEBasicTypeEnum<'ctx>)> = vec![];
+        for block_symbols in self.table.iter().rev() {
+            let mut block_variables = block_symbols.iter()
+                .filter_map(|(name, symbol)| match symbol {
+                    Symbol::Variable(v) if !variables.iter().any(|(visible, _)| visible == name) => {
+                        Some((name.clone(), v.pointer.ty.get_point_type()))
+                    }
+                    _ => { None }
+                })
+                .collect::<Vec<_>>();
+            block_variables.sort_by(|x, y| x.0.cmp(&y.0));
+            variables.extend(block_variables);
+        }
+        variables
+    }
+
+    /// 遍历所有全局函数，包括只有声明的函数
+    pub fn functions(&self) -> impl Iterator<Item=(&str, FunctionValue<'ctx>)> + '_ {
+        let mut functions = self.table.first().unwrap()
+            .iter()
+            .filter_map(|(name, symbol)| match symbol {
+                Symbol::Function(f) if !f.meta.is_built_in => { Some((name.as_str(), f.inner.llvm_value)) }
+                _ => { None }
+            })
+            .collect::<Vec<_>>();
+        //按名字排序，保证每次编译的遍历顺序一致
+        functions.sort_by(|x, y| x.0.cmp(y.0));
+        functions.into_iter()
+    }
+
+    /// 本模块中定义的全局符号，包括有函数体的函数和全局变量
+    pub fn exported_symbols(&self) -> Vec<String> {
+        let mut symbols = self.table.first().unwrap()
+            .iter()
+            .filter(|(_, symbol)| match symbol {
+                Symbol::Function(f) => { !f.meta.is_built_in && f.inner.llvm_value.count_basic_blocks() > 0 }
+                Symbol::Variable(v) => { !v.meta.is_built_in }
+                Symbol::Type(_) => { false }
+            })
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        symbols.sort();
+        symbols
+    }
+
+    /// 本模块中只有声明而没有定义的全局符号，需要在链接时由其他模块提供
+    pub fn undefined_symbols(&self) -> Vec<String> {
+        let mut symbols = self.table.first().unwrap()
+            .iter()
+            .filter(|(_, symbol)| match symbol {
+                Symbol::Function(f) => { !f.meta.is_built_in && f.inner.llvm_value.count_basic_blocks() == 0 }
+                _ => { false }
+            })
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        symbols.sort();
+        symbols
+    }
+
     pub fn push_block_table(&mut self) {
         self.table.push(HashMap::default());
     }
     pub fn pop_block_table(&mut self) {
         self.table.pop();
     }
+    pub fn block_depth(&self) -> usize {
+        self.table.len()
+    }
+    pub fn truncate_block_tables(&mut self, depth: usize) {
+        self.table.truncate(depth);
+    }
 
     pub fn bool_type(&self) -> LEBoolType<'ctx> {
         self.builtin_types.bool_type.clone()
@@ -1,18 +1,29 @@
-use inkwell::AddressSpace;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::ptr::NonNull;
+
+use inkwell::{AddressSpace, IntPredicate};
+use inkwell::attributes::{Attribute, AttributeLoc};
 use inkwell::basic_block::BasicBlock;
+use inkwell::comdat::ComdatSelectionKind;
 use inkwell::context::Context;
 use inkwell::module::{Linkage, Module};
-use inkwell::types::BasicMetadataTypeEnum;
-use inkwell::values::BasicValueEnum;
+use inkwell::OptimizationLevel;
+use inkwell::targets::{CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetData, TargetTriple};
+use inkwell::types::{BasicMetadataTypeEnum, BasicType, BasicTypeEnum, VectorType};
+use inkwell::values::{AnyValue, BasicMetadataValueEnum, BasicValue, BasicValueEnum, FunctionValue, InstructionOpcode, InstructionValue};
 
 use crate::ast::nodes::*;
+use crate::ast::parse_program_at;
 use crate::code_generator;
 use crate::code_generator::builder::*;
 use crate::code_generator::builder::binary_operator_builder::{CompareBinaryOperator, LogicBinaryOperator};
 use crate::code_generator::builder::expression::Expression;
-use crate::code_generator::context::LEContext;
-use crate::error::{CompileError, LEError, Result};
+use crate::code_generator::context::{LEContext, LoopBlocks};
+use crate::code_generator::mangle::{demangle_signature, mangle};
+use crate::error::{CompileError, LEError, Result, Warning};
 use crate::lexer::{Number, Operator, Position};
+use crate::optimizer::Optimizer;
 
 macro_rules! le_error {
     ($expr:expr,$pos:expr) => {
@@ -21,13 +32,324 @@ macro_rules! le_error {
 }
 
 
+/// 不是由指令计算出的值即为常量，可以直接用于构造常量结构体和数组
+fn is_constant_value(value: &BasicValueEnum) -> bool {
+    value.as_instruction_value().is_none()
+}
+
+/// 给定类型的undef值，用作不可达代码中表达式的值
+fn undef_value(ty: BasicTypeEnum) -> BasicValueEnum {
+    match ty {
+        BasicTypeEnum::ArrayType(t) => { t.get_undef().into() }
+        BasicTypeEnum::FloatType(t) => { t.get_undef().into() }
+        BasicTypeEnum::IntType(t) => { t.get_undef().into() }
+        BasicTypeEnum::PointerType(t) => { t.get_undef().into() }
+        BasicTypeEnum::StructType(t) => { t.get_undef().into() }
+        BasicTypeEnum::VectorType(t) => { t.get_undef().into() }
+    }
+}
+
+/// 描述不能作为成员名的表达式，用于错误信息
+fn describe_expression(expr: &Expr) -> String {
+    match expr {
+        Expr::NumberLiteral(n) => { format!("number literal `{}`", n.number) }
+        Expr::StringLiteral(s) => { format!("string literal `\"{}\"`", s.content) }
+        Expr::CallExpression(call) => { format!("call to `{}`", call.function_name.name) }
+        Expr::Identifier(identifier) => { format!("identifier `{}`", identifier.name) }
+        _ => { "an expression".into() }
+    }
+}
+
+/// 两个表达式是否表示同一个左值，只识别变量、成员访问和以常量或变量为下标的访问这样求值没有副作用的形式
+fn is_same_place(left: &Expr, right: &Expr) -> bool {
+    match (left, right) {
+        (Expr::Identifier(l), Expr::Identifier(r)) => { l.name == r.name }
+        (Expr::BinaryOperator(l), Expr::BinaryOperator(r)) if matches!((&l.op, &r.op), (Operator::Dot, Operator::Dot)) => {
+            let same_member = match (l.right.as_ref(), r.right.as_ref()) {
+                (Expr::Identifier(l), Expr::Identifier(r)) => { l.name == r.name }
+                (Expr::NumberLiteral(l), Expr::NumberLiteral(r)) => { l.number == r.number }
+                _ => { false }
+            };
+            same_member && is_same_place(&l.left, &r.left)
+        }
+        (Expr::Index(l), Expr::Index(r)) => {
+            let same_index = match (l.index.as_ref(), r.index.as_ref()) {
+                (Expr::NumberLiteral(l), Expr::NumberLiteral(r)) => { l.number == r.number }
+                (l, r) => { is_same_place(l, r) }
+            };
+            same_index && is_same_place(&l.target, &r.target)
+        }
+        _ => { false }
+    }
+}
+
+/// 表达式求值是否没有副作用且不会出错，这样的表达式可以无条件求值
+/// 除法、取模、解引用、成员访问和下标可能因为除零或空指针出错，不视为无副作用
+fn is_side_effect_free(expr: &Expr) -> bool {
+    match expr {
+        Expr::NumberLiteral(_) | Expr::StringLiteral(_) | Expr::Identifier(_) => { true }
+        Expr::UnaryOperator(unary) if !matches!(unary.op, Operator::Mul) => { is_side_effect_free(&unary.expr) }
+        Expr::BinaryOperator(binary) => {
+            matches!(binary.op, Operator::Plus | Operator::Sub | Operator::Mul | Operator::Equal | Operator::NotEqual
+                | Operator::GreaterThan | Operator::LessThan | Operator::GreaterOrEqualThan | Operator::LessOrEqualThan
+                | Operator::And | Operator::Or | Operator::Xor | Operator::Cast | Operator::BitAnd | Operator::BitOr
+                | Operator::ShiftLeft | Operator::ShiftRight)
+                && is_side_effect_free(&binary.left) && is_side_effect_free(&binary.right)
+        }
+        Expr::Ternary(ternary) => {
+            is_side_effect_free(&ternary.cond) && is_side_effect_free(&ternary.then_value) && is_side_effect_free(&ternary.else_value)
+        }
+        _ => { false }
+    }
+}
+
+/// 检查`@const`函数体中只有编译期可以求值的语句：变量定义、对局部变量赋值、if语句和返回
+fn check_const_statement(statement: &Statement, const_functions: &HashMap<String, FunctionDefinition>) -> Result<()> {
+    match statement {
+        Statement::Expressions(expr) => {
+            match expr.as_ref() {
+                Expr::BinaryOperator(BinaryOpExpression { op: Operator::Assign, left, right, .. }) if matches!(left.as_ref(), Expr::Identifier(_)) => {
+                    check_const_expression(right, const_functions)
+                }
+                Expr::CompoundAssign(assign) if matches!(assign.target.as_ref(), Expr::Identifier(_)) => {
+                    check_const_expression(&assign.value, const_functions)
+                }
+                expr => { check_const_expression(expr, const_functions) }
+            }
+        }
+        Statement::VariableDefinition(variable) => { check_const_expression(&variable.value, const_functions) }
+        Statement::Return(Some(expr), _) => { check_const_expression(expr, const_functions) }
+        Statement::If(if_statement) => {
+            check_const_expression(&if_statement.cond, const_functions)?;
+            for statement in if_statement.then_block.statements.iter().chain(if_statement.else_block.iter().flat_map(|block| block.statements.iter())) {
+                check_const_statement(statement, const_functions)?;
+            }
+            Ok(())
+        }
+        Statement::Void(_) => { Ok(()) }
+        Statement::Return(None, pos) | Statement::Break(_, pos) | Statement::Continue(pos) | Statement::Unreachable(pos) => { Err(CompileError::ExpressionIsNotConstant.to_leerror(pos.clone())) }
+        Statement::ForLoop(for_loop) => { Err(CompileError::ExpressionIsNotConstant.to_leerror(for_loop.pos.clone())) }
+        Statement::WhileLoop(while_loop) => { Err(CompileError::ExpressionIsNotConstant.to_leerror(while_loop.pos.clone())) }
+    }
+}
+
+/// 检查`@const`函数中的表达式只包含字面量、局部变量、算术和比较运算、类型转换、三元表达式和对其他`@const`函数的调用
+fn check_const_expression(expr: &Expr, const_functions: &HashMap<String, FunctionDefinition>) -> Result<()> {
+    match expr {
+        Expr::NumberLiteral(_) | Expr::Identifier(_) => { Ok(()) }
+        Expr::UnaryOperator(unary) if matches!(unary.op, Operator::Plus | Operator::Sub | Operator::Not) => {
+            check_const_expression(&unary.expr, const_functions)
+        }
+        Expr::BinaryOperator(binary) if matches!(binary.op, Operator::Cast) => { check_const_expression(&binary.left, const_functions) }
+        Expr::BinaryOperator(binary) if !matches!(binary.op, Operator::Assign | Operator::Dot | Operator::Concat) => {
+            check_const_expression(&binary.left, const_functions)?;
+            check_const_expression(&binary.right, const_functions)
+        }
+        Expr::Ternary(ternary) => {
+            check_const_expression(&ternary.cond, const_functions)?;
+            check_const_expression(&ternary.then_value, const_functions)?;
+            check_const_expression(&ternary.else_value, const_functions)
+        }
+        Expr::CallExpression(call) if const_functions.contains_key(&call.function_name.name) => {
+            call.params.iter().try_for_each(|param| check_const_expression(param, const_functions))
+        }
+        _ => { Err(CompileError::ExpressionIsNotConstant.to_leerror(expr.pos())) }
+    }
+}
+
+/// 函数对内存的访问程度，按None、Read、Write依次递增
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum MemoryAccess {
+    None,
+    Read,
+    Write,
+}
+
+/// 指针是否指向函数栈上的变量，即由alloca得到或在其基础上计算的地址
+fn is_stack_address(pointer: BasicValueEnum) -> bool {
+    let mut value = pointer;
+    while let Some(instruction) = value.as_instruction_value() {
+        match instruction.get_opcode() {
+            InstructionOpcode::Alloca => { return true; }
+            InstructionOpcode::GetElementPtr | InstructionOpcode::BitCast => {
+                match instruction.get_operand(0).and_then(|operand| operand.left()) {
+                    Some(base) => { value = base; }
+                    None => { return false; }
+                }
+            }
+            _ => { return false; }
+        }
+    }
+    false
+}
+
+/// call指令调用的模块中的函数，内联汇编和函数指针不是模块中的函数，返回None
+fn called_function<'ctx>(instruction: InstructionValue<'ctx>, module: &Module<'ctx>) -> Option<FunctionValue<'ctx>> {
+    //被调用的函数是最后一个操作数
+    let callee = instruction.get_operand(instruction.get_num_operands() - 1)?.left()?;
+    if callee.as_instruction_value().is_some() {
+        return None;
+    }
+    module.get_function(callee.into_pointer_value().get_name().to_str().ok()?)
+}
+
+/// 函数体中是否直接调用了callee
+fn calls_function<'ctx>(function: FunctionValue<'ctx>, module: &Module<'ctx>, callee: FunctionValue<'ctx>) -> bool {
+    function.get_basic_blocks().into_iter().any(|block| {
+        let mut next_instruction = block.get_first_instruction();
+        while let Some(instruction) = next_instruction {
+            next_instruction = instruction.get_next_instruction();
+            if instruction.get_opcode() == InstructionOpcode::Call && called_function(instruction, module) == Some(callee) {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// 分析函数体对栈以外内存的访问，调用没有标记`readnone`或`readonly`的函数视为写内存
+fn memory_access(function: FunctionValue, module: &Module) -> MemoryAccess {
+    let readnone = Attribute::get_named_enum_kind_id("readnone");
+    let readonly = Attribute::get_named_enum_kind_id("readonly");
+    let mut access = MemoryAccess::None;
+    for block in function.get_basic_blocks() {
+        let mut next_instruction = block.get_first_instruction();
+        while let Some(instruction) = next_instruction {
+            next_instruction = instruction.get_next_instruction();
+            let pointer_operand = |index| instruction.get_operand(index).and_then(|operand| operand.left());
+            let instruction_access = match instruction.get_opcode() {
+                _ if instruction.get_volatile().unwrap_or(false) => { MemoryAccess::Write }
+                InstructionOpcode::Load => {
+                    if pointer_operand(0).map_or(false, is_stack_address) { MemoryAccess::None } else { MemoryAccess::Read }
+                }
+                InstructionOpcode::Store => {
+                    if pointer_operand(1).map_or(false, is_stack_address) { MemoryAccess::None } else { MemoryAccess::Write }
+                }
+                InstructionOpcode::Call => {
+                    match called_function(instruction, module) {
+                        Some(callee) if callee == function => { MemoryAccess::None }
+                        Some(callee) if callee.get_enum_attribute(AttributeLoc::Function, readnone).is_some() => { MemoryAccess::None }
+                        Some(callee) if callee.get_enum_attribute(AttributeLoc::Function, readonly).is_some() => { MemoryAccess::Read }
+                        _ => { MemoryAccess::Write }
+                    }
+                }
+                InstructionOpcode::AtomicRMW | InstructionOpcode::AtomicCmpXchg | InstructionOpcode::Fence | InstructionOpcode::Invoke => { MemoryAccess::Write }
+                _ => { MemoryAccess::None }
+            };
+            access = access.max(instruction_access);
+            if access == MemoryAccess::Write {
+                return access;
+            }
+        }
+    }
+    access
+}
+
+/// 表达式默认的最大嵌套深度，超过时报错而不是耗尽栈空间
+const DEFAULT_MAX_EXPRESSION_DEPTH: usize = 256;
+
+/// 结构体默认的最大成员数和按值嵌套的最大层数，避免异常的输入在生成结构体类型时耗尽内存
+const DEFAULT_MAX_STRUCT_FIELDS: usize = 4096;
+const DEFAULT_MAX_STRUCT_NESTING: usize = 64;
+
+/// 类型按值嵌套的结构体层数，指针指向的结构体不计入
+fn struct_nesting_depth(ty: &LEBasicTypeEnum) -> usize {
+    match ty {
+        LEBasicTypeEnum::Struct(structure) => {
+            1 + structure.get_storage_types().iter().map(struct_nesting_depth).max().unwrap_or(0)
+        }
+        LEBasicTypeEnum::Array(array) => { struct_nesting_depth(&array.element_type()) }
+        _ => { 0 }
+    }
+}
+
+/// 成员数超过该值的结构体作为返回值时通过sret参数返回
+const STRUCT_RETURN_MEMBER_THRESHOLD: usize = 2;
+
+fn needs_struct_return(struct_type: &LEStructType) -> bool {
+    struct_type.get_llvm_type().count_fields() as usize > STRUCT_RETURN_MEMBER_THRESHOLD
+}
+
+/// 根据各源文件的起始偏移找到位置所在的源文件，并把位置改为在该源文件中的位置，返回源文件的下标
+fn locate_position(offsets: &[usize], position: &mut Position) -> usize {
+    let index = offsets.iter().rposition(|offset| *offset <= position.range.start).unwrap_or(0);
+    let offset = offsets.get(index).copied().unwrap_or(0);
+    position.range = position.range.start - offset..position.range.end.saturating_sub(offset);
+    index
+}
+
+fn locate_error(offsets: &[usize], mut error: LEError) -> (usize, LEError) {
+    let index = error.position_mut().map_or(0, |position| locate_position(offsets, position));
+    (index, error)
+}
+
+/// LLVM中调用约定的编号
+const CALL_CONV_C: u32 = 0;
+const CALL_CONV_FAST: u32 = 8;
+const CALL_CONV_COLD: u32 = 9;
+
+/// 构建配置，决定生成的函数上附加哪些安全和调试相关的属性
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildProfile {
+    Default,
+    /// 开启栈保护(`sspstrong`)并保留帧指针(`"frame-pointer"="all"`)
+    Hardened,
+    /// 保留帧指针并以`optnone`禁止优化，局部变量始终保留在栈上便于调试器查看
+    Debug,
+    /// 生成后对整个模块运行优化流程，局部变量提升为寄存器，`@inline`函数优先被内联
+    Release,
+}
+
 pub struct CodeGenerator<'ctx> {
     pub context: LEContext<'ctx>,
     pub builder: LEBuilder<'ctx>,
+    /// 是否允许非bool类型的值隐式转换为bool，如`if(p)`表示`p`不为空指针
+    pub implicit_bool: bool,
+    pub build_profile: BuildProfile,
+    /// 是否在解引用指针前检查空指针，指针为空时调用`llvm.trap`
+    pub safety_checks: bool,
+    /// 独立环境模式，不自动声明C运行时函数，依赖运行时的内置函数只能使用用户自己声明的函数
+    pub freestanding: bool,
+    /// 表达式的最大嵌套深度，生成表达式是递归的，过深的表达式会耗尽栈空间
+    pub max_expression_depth: usize,
+    /// 当前正在生成的表达式的嵌套深度
+    expression_depth: usize,
+    /// 结构体的最大成员数
+    pub max_struct_fields: usize,
+    /// 结构体按值嵌套的最大层数
+    pub max_struct_nesting: usize,
+    /// 计算类型大小和对齐时使用的数据布局，编译到具体目标时应设置为目标机器的布局
+    pub target_data: TargetData,
+    /// 启用的条件编译特性，带有`@cfg(feature)`且feature不在其中的顶层项不会被生成
+    pub features: HashSet<String>,
+    /// 带有`@const`属性的函数，以常量实参调用时在编译期求值
+    const_functions: HashMap<String, FunctionDefinition>,
+    /// 正在编译期求值的`@const`函数调用栈，每一层保存该次调用中局部变量的值
+    const_frames: Vec<HashMap<String, LEBasicValueEnum<'ctx>>>,
+    /// 是否在必须得到常量的位置生成表达式，如全局变量的初始值和`static_assert`的条件
+    const_context: bool,
+    /// 生成代码过程中产生的警告，编译成功后由`compile`返回
+    warnings: Vec<Warning>,
+    /// 最近一次调用不返回的函数之后新建的不可达块，调用之后的代码生成在其中
+    noreturn_block: Option<BasicBlock<'ctx>>,
+    /// 正在生成代码的模块，只在`compile`和`add_function`执行期间有值，用于在表达式中创建全局常量
+    module: Option<NonNull<Module<'ctx>>>,
+    /// 有多个定义的函数名，这些函数的每个定义都使用重整后的符号名
+    overloaded_functions: HashSet<String>,
 }
 
 impl<'ctx> CodeGenerator<'ctx> {
     fn build_expression(&mut self, value: &Expr) -> Result<Expression<'ctx>> {
+        if self.expression_depth >= self.max_expression_depth {
+            return Err(CompileError::ExpressionTooDeep { limit: self.max_expression_depth }.to_leerror(value.pos()));
+        }
+        self.expression_depth += 1;
+        let result = self.build_expression_unchecked(value);
+        self.expression_depth -= 1;
+        result
+    }
+
+    fn build_expression_unchecked(&mut self, value: &Expr) -> Result<Expression<'ctx>> {
         //针对不同类型的表达式调用不同的生成函数
         match value {
             Expr::UnaryOperator(n) => { self.build_unary_operator_expression(n) }
@@ -36,10 +358,105 @@ impl<'ctx> CodeGenerator<'ctx> {
             Expr::CallExpression(n) => { self.build_call_expression(n) }
             Expr::Identifier(n) => { self.build_identifier_expression(n) }
             Expr::ArrayInitializer(n) => { self.build_array_initializer(n) }
+            Expr::ArrayRepeat(n) => { self.build_array_repeat(n) }
             Expr::StructureInitializer(n) => { self.build_structure_initializer(n) }
-            _ => { unimplemented!() }
-            // Expr::StringLiteral(n) => { Ok(Some(self.build_string_literal(n)?)) }
+            Expr::InlineAsm(n) => { self.build_inline_asm(n) }
+            Expr::Volatile(n) => { self.build_volatile_expression(n) }
+            Expr::If(n) => { self.build_if_expression(n) }
+            Expr::Ternary(n) => { self.build_ternary_expression(n) }
+            Expr::Block(n) => { self.build_block_expression(n) }
+            Expr::StringLiteral(n) => { self.build_string_literal(n) }
+            Expr::Index(n) => { self.build_index_expression(n) }
+            Expr::IndirectCall(n) => { self.build_indirect_call_expression(n) }
+            Expr::Type(n) => { Err(CompileError::ExpressionIsNotRightValueExpression.to_leerror(n.pos())) }
+            Expr::While(n) => { self.build_while_expression(n) }
+            Expr::CompoundAssign(n) => { self.build_compound_assign_expression(n) }
+        }
+    }
+
+    /// 成员为不带后缀的整数字面量时，返回结构体中按声明顺序对应的成员名，下标越界时返回None
+    fn positional_member_name(&self, left: &Expression<'ctx>, member: &Expr) -> Option<String> {
+        let index = match member {
+            Expr::NumberLiteral(NumberLiteral { number: Number::Integer(index, None), .. }) => { *index as usize }
+            _ => { return None; }
+        };
+        //指向结构体的指针会在build_dot中自动解引用
+        let struct_type = match left.get_le_type()? {
+            LEBasicTypeEnum::Pointer(pointer_type) => { pointer_type.get_point_type() }
+            other => { other }
+        };
+        let struct_type = match struct_type {
+            LEBasicTypeEnum::Struct(struct_type) => { struct_type }
+            _ => { return None; }
+        };
+        struct_type.get_member_name(index).map(|name| name.to_string())
+    }
+
+    /// 生成下标访问，数组按`[0, index]`取元素地址，指针按`[index]`偏移
+    fn build_index_expression(&mut self, value: &IndexExpression) -> Result<Expression<'ctx>> {
+        let target = self.build_expression(value.target.as_ref())?;
+        let index_expr = self.build_expression(value.index.as_ref())?;
+        let index = match le_error!(self.builder.read_expression(&self.context, index_expr),value.index.pos())? {
+            LEBasicValueEnum::Integer(index) => { index }
+            other => {
+                return Err(CompileError::TypeMismatched { expect: "Integer".into(), found: other.get_le_type().to_string() }.to_leerror(value.index.pos()));
+            }
+        };
+        //左值数组直接在原内存上取元素地址
+        if let Expression::Left(pointer) = &target {
+            if let LEBasicTypeEnum::Array(array_type) = pointer.ty.get_point_type() {
+                //常量下标在编译期检查越界，负数下标会被GEP当作很大的偏移
+                let constant_index = if index.ty.signed() {
+                    index.llvm_value.get_sign_extended_constant()
+                } else {
+                    index.llvm_value.get_zero_extended_constant().map(|i| i as i64)
+                };
+                if let Some(constant_index) = constant_index {
+                    if constant_index < 0 || constant_index as u64 >= array_type.len() as u64 {
+                        return Err(CompileError::ArrayIndexOutOfBounds { index: constant_index, length: array_type.len() }.to_leerror(value.index.pos()));
+                    }
+                }
+                let zero = self.context.i32_type().get_llvm_type().const_zero();
+                let element_pointer = unsafe { self.builder.llvm_builder.build_in_bounds_gep(pointer.llvm_value, &[zero, index.llvm_value], "") };
+                return Ok(Expression::Left(LEPointerValue { ty: array_type.element_type().get_pointer_type(), llvm_value: element_pointer }));
+            }
         }
+        //指针按下标偏移，运行时的负数下标是允许的
+        let pointer = match le_error!(self.builder.read_expression(&self.context, target),value.target.pos())? {
+            LEBasicValueEnum::Pointer(pointer) => { pointer }
+            other => {
+                return Err(CompileError::TypeMismatched { expect: "Array".into(), found: other.get_le_type().to_string() }.to_leerror(value.target.pos()));
+            }
+        };
+        if self.safety_checks {
+            self.build_null_check(&pointer);
+        }
+        let element_pointer = unsafe { self.builder.llvm_builder.build_in_bounds_gep(pointer.llvm_value, &[index.llvm_value], "") };
+        Ok(Expression::Left(LEPointerValue { ty: pointer.ty.clone(), llvm_value: element_pointer }))
+    }
+
+    /// 字符串字面量是指向全局常量字符串首字符的常量指针，可以用在全局变量的初始值中，相同内容的字面量共享同一个全局变量
+    fn build_string_literal(&mut self, value: &StringLiteral) -> Result<Expression<'ctx>> {
+        let module = self.current_module();
+        let array_pointer = self.intern_global_string(module, &value.content);
+        let zero = self.context.i64_type().get_llvm_type().const_zero();
+        let llvm_value = unsafe { array_pointer.llvm_value.const_in_bounds_gep(&[zero, zero]) };
+        let pointer = LEPointerValue { ty: self.context.i8_type().get_pointer_type(), llvm_value };
+        Ok(Expression::Right(pointer.to_le_value_enum()))
+    }
+
+    /// 在生成module的代码期间执行f，表达式中可以通过`current_module`取得该模块
+    fn with_module<T>(&mut self, module: &Module<'ctx>, f: impl FnOnce(&mut Self) -> T) -> T {
+        let previous = self.module.replace(NonNull::from(module));
+        let result = f(self);
+        self.module = previous;
+        result
+    }
+
+    /// 正在生成代码的模块，只能在`with_module`期间调用
+    fn current_module<'m>(&self) -> &'m Module<'ctx> {
+        //模块的引用在`with_module`返回前一直有效
+        unsafe { self.module.expect("no module is being compiled").as_ref() }
     }
 
 
@@ -49,6 +466,9 @@ impl<'ctx> CodeGenerator<'ctx> {
             .map_err(|e| LEError::new_compile_error(e, expr.structure_name.pos.clone()))?;
         //检查类型是否一致
         if let LEBasicTypeEnum::Struct(struct_type) = struct_type {
+            if let Some(base) = &expr.base {
+                return self.build_structure_update(expr, struct_type, base.as_ref());
+            }
             let initializer_member_num = expr.member_initial_values.len();
             if struct_type.get_llvm_type().get_field_types().len() != initializer_member_num {
                 return Err(CompileError::TypeMismatched { expect: struct_type.to_string(), found: expr.structure_name.name.clone() }.to_leerror(expr.pos()));
@@ -56,21 +476,82 @@ impl<'ctx> CodeGenerator<'ctx> {
             let mut value_array = vec![];
             for (name, initial_value) in expr.member_initial_values.iter() {
                 let value = self.build_expression(initial_value.as_ref())?;
-                value_array.push((struct_type.get_member_offset(name).unwrap(), value));
-            }
-            value_array.sort_unstable_by(|x, y| x.0.cmp(&y.0));
-            let struct_llvm_value = &value_array
-                .into_iter()
-                .map(|x| self.builder.read_expression(&self.context, x.1).unwrap().to_llvm_basic_value_enum()).collect::<Vec<_>>();
-            //创建结构体value
-            let struct_value = struct_type.get_llvm_type().const_named_struct(struct_llvm_value);
-            Ok(Expression::Right(LEStructValue { ty: struct_type, llvm_value: struct_value }.to_le_value_enum()))
+                //位域成员暂不支持在初始化器中赋值
+                let (offset, member_type) = struct_type.get_member_offset_and_type(name)
+                    .ok_or_else(|| CompileError::NoSuchMember { member_name: name.clone() }.to_leerror(initial_value.pos()))?;
+                let value = le_error!(self.builder.build_cast(&self.context, value, member_type),initial_value.pos())?;
+                value_array.push((offset, value.to_llvm_basic_value_enum()));
+            }
+            //按成员偏移排序，偏移相同时保持声明顺序，保证生成的IR可复现
+            value_array.sort_by_key(|(offset, _)| *offset);
+            //成员都是常量时直接创建常量结构体，否则申请内存后逐个写入成员
+            if value_array.iter().all(|(_, value)| is_constant_value(value)) {
+                let struct_llvm_value = &value_array.into_iter().map(|x| x.1).collect::<Vec<_>>();
+                let struct_value = struct_type.get_llvm_type().const_named_struct(struct_llvm_value);
+                Ok(Expression::Right(LEStructValue { ty: struct_type, llvm_value: struct_value }.to_le_value_enum()))
+            } else {
+                let pointer = self.builder.build_entry_alloca(&self.context, struct_type.to_le_type_enum());
+                for (offset, value) in value_array.into_iter() {
+                    let member_pointer = self.builder.llvm_builder.build_struct_gep(pointer.llvm_value, offset, "").unwrap();
+                    self.builder.llvm_builder.build_store(member_pointer, value);
+                }
+                Ok(Expression::Left(pointer))
+            }
         } else {
-            Err(LEError::new_compile_error(CompileError::TypeMismatched { expect: "Struct".into(), found: struct_type.name().into() }, expr.pos.clone()))
+            Err(LEError::new_compile_error(CompileError::TypeMismatched { expect: "Struct".into(), found: struct_type.to_string() }, expr.pos.clone()))
+        }
+    }
+
+    /// 生成`Point{ ..base, x: 5 }`，先将base复制到新申请的内存中，再逐个写入显式初始化的成员
+    fn build_structure_update(&mut self, expr: &StructureInitializer, struct_type: LEStructType<'ctx>, base: &Expr) -> Result<Expression<'ctx>> {
+        let base_expr = self.build_expression(base)?;
+        let base_value = le_error!(self.builder.read_expression(&self.context, base_expr),base.pos())?;
+        //base必须是同一个结构体类型
+        if base_value.get_le_type() != struct_type.to_le_type_enum() {
+            return Err(CompileError::TypeMismatched {
+                expect: struct_type.to_string(),
+                found: base_value.get_le_type().to_string(),
+            }.to_leerror(base.pos()));
+        }
+        let pointer = self.builder.build_entry_alloca(&self.context, struct_type.to_le_type_enum());
+        self.builder.llvm_builder.build_store(pointer.llvm_value, base_value.to_llvm_basic_value_enum());
+        for (name, initial_value) in expr.member_initial_values.iter() {
+            let value = self.build_expression(initial_value.as_ref())?;
+            let (offset, member_type) = struct_type.get_member_offset_and_type(name)
+                .ok_or_else(|| CompileError::NoSuchMember { member_name: name.clone() }.to_leerror(initial_value.pos()))?;
+            let value = le_error!(self.builder.build_cast(&self.context, value, member_type),initial_value.pos())?;
+            let member_pointer = self.builder.llvm_builder.build_struct_gep(pointer.llvm_value, offset, "").unwrap();
+            self.builder.llvm_builder.build_store(member_pointer, value.to_llvm_basic_value_enum());
+        }
+        Ok(Expression::Left(pointer))
+    }
+
+    /// 左侧为常量bool时折叠逻辑运算，absorbing为能决定结果的值，`&&`为false，`||`为true
+    /// 左侧等于absorbing时结果就是左侧，不再生成右侧；否则结果就是右侧转换为bool的值
+    /// 左侧不是常量时返回None
+    fn build_constant_logic(&mut self, left: &LEBasicValueEnum<'ctx>, right: &Expr, absorbing: bool) -> Result<Option<Expression<'ctx>>> {
+        let constant = match left {
+            LEBasicValueEnum::Bool(bool_value) => { bool_value.llvm_value.get_zero_extended_constant() }
+            _ => { None }
+        };
+        match constant {
+            Some(constant) if (constant != 0) == absorbing => { Ok(Some(Expression::Right(left.clone()))) }
+            Some(_) => {
+                let right_value = self.build_expression(right)?;
+                let bool_type = self.context.bool_type().to_le_type_enum();
+                Ok(Some(Expression::Right(le_error!(self.builder.build_cast(&self.context, right_value, bool_type),right.pos())?)))
+            }
+            None => { Ok(None) }
         }
     }
 
     fn build_unary_operator_expression(&mut self, expr: &UnaryOpExpression) -> Result<Expression<'ctx>> {
+        //`!!x`等价于x转换为bool
+        if let (Operator::Not, Expr::UnaryOperator(UnaryOpExpression { op: Operator::Not, expr: inner, .. })) = (&expr.op, expr.expr.as_ref()) {
+            let value = self.build_expression(inner.as_ref())?;
+            let value = le_error!(self.builder.read_expression(&self.context, value),inner.pos())?;
+            return Ok(Expression::Right(self.build_bool_value(value, inner.pos())?.to_le_value_enum()));
+        }
         let value = self.build_expression(expr.expr.as_ref())?;
         match expr.op {
             Operator::Plus => {
@@ -79,37 +560,82 @@ impl<'ctx> CodeGenerator<'ctx> {
             Operator::Sub => {
                 Ok(Expression::Right(self.builder.build_neg(&self.context, value).map_err(|e| e.to_leerror(expr.pos.clone()))?))
             }
-            // Operator::Not => {
-            //
-            // }
-            // Operator::Rev => {}
-            _ => { unimplemented!() }
+            Operator::Not => {
+                let value = le_error!(self.builder.read_expression(&self.context, value),expr.pos.clone())?;
+                let bool_value = self.build_bool_value(value, expr.expr.pos())?;
+                let llvm_value = self.builder.llvm_builder.build_not(bool_value.llvm_value, "");
+                Ok(Expression::Right(LEBoolValue { ty: bool_value.ty, llvm_value }.to_le_value_enum()))
+            }
+            //`*p`的结果是指针指向的左值，指针是函数返回的临时值时同样可以赋值
+            Operator::Mul => {
+                let pointer = match le_error!(self.builder.read_expression(&self.context, value),expr.expr.pos())? {
+                    LEBasicValueEnum::Pointer(pointer) => { pointer }
+                    other => {
+                        return Err(CompileError::NoSuitableUnaryOperator { op: Operator::Mul, target_type: other.get_le_type().to_string() }.to_leerror(expr.pos.clone()));
+                    }
+                };
+                if self.safety_checks {
+                    self.build_null_check(&pointer);
+                }
+                Ok(Expression::Left(pointer))
+            }
+            //`&x`取左值的地址，全局变量的地址是常量，可以用于初始化其他全局变量
+            Operator::BitAnd => {
+                match value {
+                    Expression::Left(pointer) => { Ok(Expression::Right(pointer.to_le_value_enum())) }
+                    _ => { Err(CompileError::ExpressionIsNotLeftValueExpression.to_leerror(expr.expr.pos())) }
+                }
+            }
+            //其他运算符不能作为前缀运算符
+            ref op => {
+                let target_type = value.get_le_type().map_or("void".to_string(), |ty| ty.to_string());
+                Err(CompileError::NoSuitableUnaryOperator { op: op.clone(), target_type }.to_leerror(expr.expr.pos()))
+            }
         }
     }
 
 
     fn build_array_initializer(&mut self, value: &ArrayInitializer) -> Result<Expression<'ctx>> {
+        let mut array_values = vec![];
+        for v in value.elements.iter() {
+            array_values.push((self.build_array_element(v)?, v.pos()));
+        }
+        self.build_array_from_values(array_values, value.pos.clone())
+    }
+
+    fn build_array_element(&mut self, element: &Expr) -> Result<LEBasicValueEnum<'ctx>> {
+        let expr = self.build_expression(element)?;
+        self.builder.read_expression(&self.context, expr).map_err(|e| e.to_leerror(element.pos()))
+    }
+
+    /// 由已求值的元素生成数组，元素全是常量时生成常量数组，否则申请内存后逐个写入
+    fn build_array_from_values(&mut self, values: Vec<(LEBasicValueEnum<'ctx>, Position)>, pos: Position) -> Result<Expression<'ctx>> {
         //禁止0长度的数组
-        if value.elements.is_empty() {
-            Err(CompileError::NotAllowZeroLengthArray.to_leerror(value.pos.clone()))
+        if values.is_empty() {
+            Err(CompileError::NotAllowZeroLengthArray.to_leerror(pos))
         } else {
-            let mut array_values = vec![];
+            let element_type = values[0].0.get_le_type();
+            let array_type = LEBasicType::get_array_type(&element_type, values.len() as u32);
             //对每个数组元素初始化的表达式做类型检查
-            for v in value.elements.iter() {
-                let expr = self.build_expression(v)?;
-                array_values.push(self.builder.read_expression(&self.context, expr).map_err(|e| e.to_leerror(v.pos()))?);
-            }
-            let first_value = array_values.first().unwrap();
-            let element_type = LEBasicValue::get_le_type(first_value);
-            let array_type = LEBasicType::get_array_type(&element_type, value.elements.len() as u32);
-            //对每个数组元素初始化的表达式做求值
-            for (index, others) in array_values.iter().enumerate() {
+            for (others, position) in values.iter() {
                 if others.get_le_type() != element_type {
                     return Err(CompileError::TypeMismatched {
-                        expect: first_value.get_le_type().to_string(),
+                        expect: element_type.to_string(),
                         found: others.get_le_type().to_string(),
-                    }.to_leerror(value.elements[index].pos()));
+                    }.to_leerror(position.clone()));
+                }
+            }
+            let array_values = values.into_iter().map(|(value, _)| value).collect::<Vec<_>>();
+            //元素不全是常量时申请内存后逐个写入元素
+            if !array_values.iter().all(|v| is_constant_value(&v.to_llvm_basic_value_enum())) {
+                let pointer = self.builder.build_entry_alloca(&self.context, array_type.to_le_type_enum());
+                let i32_type = self.context.i32_type().get_llvm_type();
+                for (index, element) in array_values.into_iter().enumerate() {
+                    let indices = [i32_type.const_zero(), i32_type.const_int(index as u64, false)];
+                    let element_pointer = unsafe { self.builder.llvm_builder.build_in_bounds_gep(pointer.llvm_value, &indices, "") };
+                    self.builder.llvm_builder.build_store(element_pointer, element.to_llvm_basic_value_enum());
                 }
+                return Ok(Expression::Left(pointer));
             }
             //生成数组value
             match element_type {
@@ -141,37 +667,105 @@ impl<'ctx> CodeGenerator<'ctx> {
                     let array_initial_values = array_values.into_iter().map(|v| v.try_into().unwrap()).collect::<Vec<LEVectorValue>>();
                     Ok(Expression::Right(t.const_array(&array_initial_values).to_le_value_enum()))
                 }
+                LEBasicTypeEnum::FunctionPointer(t) => {
+                    let array_initial_values = array_values.into_iter().map(|v| v.try_into().unwrap()).collect::<Vec<LEFunctionPointerValue>>();
+                    Ok(Expression::Right(t.const_array(&array_initial_values).to_le_value_enum()))
+                }
             }
         }
     }
 
-    fn build_binary_operator_expression(&mut self, value: &BinaryOpExpression) -> Result<Expression<'ctx>> {
-        //针对不同运算符调用不同的生成函数
-        match value.op {
-            Operator::Plus => {
-                let left = self.build_expression(value.left.as_ref())?;
-                let right = self.build_expression(value.right.as_ref())?;
-                Ok(Expression::Right(le_error!(self.builder.build_add(&self.context,left, right),value.pos())?))
+    /// 生成`[value; N]`，value只求值一次，为常量时生成N个相同元素的常量数组，否则在循环中逐个写入
+    fn build_array_repeat(&mut self, value: &ArrayRepeat) -> Result<Expression<'ctx>> {
+        if value.count == 0 {
+            return Err(CompileError::NotAllowZeroLengthArray.to_leerror(value.pos.clone()));
+        }
+        let element = self.build_array_element(value.element.as_ref())?;
+        if is_constant_value(&element.to_llvm_basic_value_enum()) {
+            let values = vec![(element, value.element.pos()); value.count as usize];
+            return self.build_array_from_values(values, value.pos.clone());
+        }
+        let array_type = LEBasicType::get_array_type(&element.get_le_type(), value.count);
+        let pointer = self.builder.build_entry_alloca(&self.context, array_type.to_le_type_enum());
+        let i32_type = self.context.i32_type().get_llvm_type();
+        let entry_block = self.builder.llvm_builder.get_insert_block().unwrap();
+        let body_block = self.context.llvm_context.insert_basic_block_after(entry_block, "repeat.body");
+        let end_block = self.context.llvm_context.insert_basic_block_after(body_block, "repeat.end");
+        self.builder.llvm_builder.build_unconditional_branch(body_block);
+        self.builder.llvm_builder.position_at_end(body_block);
+        let index = self.builder.llvm_builder.build_phi(i32_type, "");
+        index.add_incoming(&[(&i32_type.const_zero(), entry_block)]);
+        let index_value = index.as_basic_value().into_int_value();
+        let indices = [i32_type.const_zero(), index_value];
+        let element_pointer = unsafe { self.builder.llvm_builder.build_in_bounds_gep(pointer.llvm_value, &indices, "") };
+        self.builder.llvm_builder.build_store(element_pointer, element.to_llvm_basic_value_enum());
+        let next_index = self.builder.llvm_builder.build_int_add(index_value, i32_type.const_int(1, false), "");
+        index.add_incoming(&[(&next_index, body_block)]);
+        let finished = self.builder.llvm_builder.build_int_compare(IntPredicate::EQ, next_index, i32_type.const_int(value.count as u64, false), "");
+        self.builder.llvm_builder.build_conditional_branch(finished, end_block, body_block);
+        self.builder.llvm_builder.position_at_end(end_block);
+        Ok(Expression::Left(pointer))
+    }
+
+    /// 编译期拼接常量数组，如`[1, 2] ++ [3, 4]`，两侧必须是数组字面量或其拼接
+    fn build_array_concat(&mut self, value: &BinaryOpExpression) -> Result<Expression<'ctx>> {
+        let mut values = vec![];
+        self.collect_concat_elements(value.left.as_ref(), &mut values)?;
+        self.collect_concat_elements(value.right.as_ref(), &mut values)?;
+        let result = self.build_array_from_values(values, value.pos())?;
+        //元素中有非常量时结果不是常量数组
+        if !result.is_right_value() {
+            return Err(CompileError::ExpressionIsNotConstant.to_leerror(value.pos()));
+        }
+        Ok(result)
+    }
+
+    fn collect_concat_elements(&mut self, expr: &Expr, values: &mut Vec<(LEBasicValueEnum<'ctx>, Position)>) -> Result<()> {
+        match expr {
+            Expr::ArrayInitializer(array) => {
+                for element in array.elements.iter() {
+                    values.push((self.build_array_element(element)?, element.pos()));
+                }
+                Ok(())
             }
-            Operator::Sub => {
-                let left = self.build_expression(value.left.as_ref())?;
-                let right = self.build_expression(value.right.as_ref())?;
-                Ok(Expression::Right(le_error!(self.builder.build_sub(&self.context,left, right),value.pos())?))
+            Expr::ArrayRepeat(repeat) => {
+                let element = self.build_array_element(repeat.element.as_ref())?;
+                values.extend(std::iter::repeat((element, repeat.element.pos())).take(repeat.count as usize));
+                Ok(())
             }
-            Operator::Mul => {
-                let left = self.build_expression(value.left.as_ref())?;
-                let right = self.build_expression(value.right.as_ref())?;
-                Ok(Expression::Right(le_error!(self.builder.build_mul(&self.context,left, right),value.pos())?))
+            Expr::BinaryOperator(BinaryOpExpression { op: Operator::Concat, left, right, .. }) => {
+                self.collect_concat_elements(left.as_ref(), values)?;
+                self.collect_concat_elements(right.as_ref(), values)
             }
-            Operator::Div => {
+            _ => { Err(CompileError::ExpressionIsNotConstant.to_leerror(expr.pos())) }
+        }
+    }
+
+    fn build_binary_operator_expression(&mut self, value: &BinaryOpExpression) -> Result<Expression<'ctx>> {
+        //针对不同运算符调用不同的生成函数
+        match value.op {
+            Operator::Plus | Operator::Sub | Operator::Mul | Operator::Div | Operator::Mod | Operator::Xor
+            | Operator::BitAnd | Operator::BitOr | Operator::ShiftLeft | Operator::ShiftRight => {
                 let left = self.build_expression(value.left.as_ref())?;
                 let right = self.build_expression(value.right.as_ref())?;
-                Ok(Expression::Right(le_error!(self.builder.build_div(&self.context,left, right),value.pos())?))
+                self.build_arithmetic_operation(&value.op, left, right, value.pos())
             }
             Operator::Assign => {
+                //对volatile左值的赋值生成volatile store
+                if let Expr::Volatile(volatile) = value.left.as_ref() {
+                    let left = self.build_expression(volatile.expr.as_ref())?;
+                    let right = self.build_expression(value.right.as_ref())?;
+                    return Ok(Expression::Right(le_error!(self.builder.build_volatile_assign(&self.context,left, right),value.pos())?));
+                }
+                //`x = x;`这样两侧是同一个左值的赋值不生成store，只求值一次左值作为赋值表达式的值
+                if is_same_place(value.left.as_ref(), value.right.as_ref()) {
+                    self.warnings.push(Warning::SelfAssignment { position: value.pos() });
+                    let left = self.build_expression(value.left.as_ref())?;
+                    return Ok(Expression::Right(le_error!(self.builder.read_expression(&self.context, left),value.pos())?));
+                }
                 let left = self.build_expression(value.left.as_ref())?;
                 let right = self.build_expression(value.right.as_ref())?;
-                Ok(Expression::Left(le_error!(self.builder.build_assign(&self.context,left, right),value.pos())?))
+                Ok(Expression::Right(le_error!(self.builder.build_assign(&self.context,left, right),value.pos())?))
             }
             Operator::Equal => {
                 let left = self.build_expression(value.left.as_ref())?;
@@ -204,115 +798,892 @@ impl<'ctx> CodeGenerator<'ctx> {
                 Ok(Expression::Right(le_error!(self.builder.build_compare(&self.context,left, right, CompareBinaryOperator::LessOrEqualThan),value.pos())?.to_le_value_enum()))
             }
             Operator::Dot => {
-                let left = self.build_expression(value.left.as_ref())?;
-                if let Expr::Identifier(identifier) = value.right.as_ref() {
-                    Ok(Expression::Left(le_error!(self.builder.build_dot(&self.context,left, &identifier.name),value.pos())?))
+                //右侧为函数调用时为方法调用
+                if let Expr::CallExpression(call) = value.right.as_ref() {
+                    return self.build_method_call(value.left.as_ref(), call);
+                }
+                let mut left = self.build_expression(value.left.as_ref())?;
+                if self.safety_checks {
+                    left = self.build_checked_struct_pointer(left);
+                }
+                let member_name = if let Expr::Identifier(identifier) = value.right.as_ref() {
+                    identifier.name.clone()
+                } else if let Some(member_name) = self.positional_member_name(&left, value.right.as_ref()) {
+                    //`a.0`按声明顺序访问成员，转换为对应名字的成员访问
+                    member_name
                 } else {
-                    Err(CompileError::NoSuitableBinaryOperator {
-                        op: Operator::Dot,
-                        left_type: "".to_string(),
-                        right_type: "".to_string(),
-                    }.to_leerror(value.pos.clone()))
+                    return Err(CompileError::InvalidMemberAccess {
+                        left_type: left.get_le_type().map_or("void".to_string(), |ty| ty.to_string()),
+                        member: describe_expression(value.right.as_ref()),
+                    }.to_leerror(value.right.pos()));
+                };
+                let member = le_error!(self.builder.build_dot(&self.context,left, &member_name),value.pos())?;
+                //函数返回的结构体是临时值，其成员只能读取，不能赋值或取地址
+                if let Expr::CallExpression(_) = value.left.as_ref() {
+                    return Ok(Expression::Right(le_error!(self.builder.read_expression(&self.context, member),value.pos())?));
                 }
+                Ok(member)
             }
             Operator::And => {
                 let left = self.build_expression(value.left.as_ref())?;
+                let left = le_error!(self.builder.read_expression(&self.context, left),value.left.pos())?;
+                if let Some(folded) = self.build_constant_logic(&left, value.right.as_ref(), false)? {
+                    return Ok(folded);
+                }
                 let right = self.build_expression(value.right.as_ref())?;
-                Ok(Expression::Right(le_error!(self.builder.build_binary_logic(&self.context,left, right, LogicBinaryOperator::And),value.pos())?.to_le_value_enum()))
+                Ok(Expression::Right(le_error!(self.builder.build_binary_logic(&self.context,Expression::Right(left), right, LogicBinaryOperator::And),value.pos())?.to_le_value_enum()))
             }
             Operator::Or => {
                 let left = self.build_expression(value.left.as_ref())?;
+                let left = le_error!(self.builder.read_expression(&self.context, left),value.left.pos())?;
+                if let Some(folded) = self.build_constant_logic(&left, value.right.as_ref(), true)? {
+                    return Ok(folded);
+                }
                 let right = self.build_expression(value.right.as_ref())?;
-                Ok(Expression::Right(le_error!(self.builder.build_binary_logic(&self.context,left, right, LogicBinaryOperator::Or),value.pos())?.to_le_value_enum()))
+                Ok(Expression::Right(le_error!(self.builder.build_binary_logic(&self.context,Expression::Right(left), right, LogicBinaryOperator::Or),value.pos())?.to_le_value_enum()))
             }
-            Operator::Xor => {
+            Operator::Cast => {
                 let left = self.build_expression(value.left.as_ref())?;
-                let right = self.build_expression(value.right.as_ref())?;
-                Ok(Expression::Right(le_error!(self.builder.build_binary_logic(&self.context,left, right, LogicBinaryOperator::Xor),value.pos())?.to_le_value_enum()))
+                let type_declarator = match value.right.as_ref() {
+                    Expr::Identifier(type_identifier) => { TypeDeclarator::TypeIdentifier(type_identifier.clone()) }
+                    Expr::Type(type_declarator) => { type_declarator.clone() }
+                    _ => { return Err(CompileError::ExpressionIsNotType { pos: value.right.pos() }.to_leerror(value.right.pos())); }
+                };
+                let ty = le_error!(self.context.get_generic_type(&type_declarator),type_declarator.pos())?;
+                Ok(Expression::Right(le_error!(self.builder.build_cast(&self.context,left,ty),value.right.pos())?))
             }
+            Operator::Concat => { self.build_array_concat(value) }
+            _ => { unimplemented!() }
+        }
+    }
 
-            Operator::Mod => {
-                let left = self.build_expression(value.left.as_ref())?;
-                let right = self.build_expression(value.right.as_ref())?;
-                Ok(Expression::Right(le_error!(self.builder.build_mod(&self.context,left, right),value.pos())?.to_le_value_enum()))
-            }
-            Operator::Cast => {
-                let left = self.build_expression(value.left.as_ref())?;
-                if let Expr::Identifier(type_identifier) = value.right.as_ref() {
-                    let ty = le_error!(self.context.get_generic_type(&TypeDeclarator::TypeIdentifier(type_identifier.clone())),type_identifier.pos())?;
-                    Ok(Expression::Right(le_error!(self.builder.build_cast(&self.context,left,ty),value.right.pos())?))
-                } else {
-                    Err(CompileError::ExpressionIsNotType { pos: value.right.pos() }.to_leerror(value.right.pos()))
+    /// 生成算术和按位运算，操作数已经求值，复合赋值也通过这个函数计算新值
+    fn build_arithmetic_operation(&mut self, op: &Operator, left: Expression<'ctx>, right: Expression<'ctx>, pos: Position) -> Result<Expression<'ctx>> {
+        match op {
+            Operator::Plus => { Ok(Expression::Right(le_error!(self.builder.build_add(&self.context,left, right),pos)?)) }
+            Operator::Sub => { Ok(Expression::Right(le_error!(self.builder.build_sub(&self.context,left, right),pos)?)) }
+            Operator::Mul => { Ok(Expression::Right(le_error!(self.builder.build_mul(&self.context,left, right),pos)?)) }
+            Operator::Div => { Ok(Expression::Right(le_error!(self.builder.build_div(&self.context,left, right),pos)?)) }
+            Operator::Mod => { Ok(Expression::Right(le_error!(self.builder.build_mod(&self.context,left, right),pos)?.to_le_value_enum())) }
+            Operator::Xor => {
+                let left = le_error!(self.builder.read_expression(&self.context, left),pos.clone())?;
+                let right = le_error!(self.builder.read_expression(&self.context, right),pos.clone())?;
+                //整数之间为按位异或，其余为逻辑异或
+                if let (LEBasicValueEnum::Integer(_), LEBasicValueEnum::Integer(_)) = (&left, &right) {
+                    return Ok(Expression::Right(le_error!(self.builder.build_bitwise(left, right, Operator::Xor),pos)?));
                 }
+                Ok(Expression::Right(le_error!(self.builder.build_binary_logic(&self.context,Expression::Right(left), Expression::Right(right), LogicBinaryOperator::Xor),pos)?.to_le_value_enum()))
             }
-            _ => { unimplemented!() }
+            _ => {
+                let left = le_error!(self.builder.read_expression(&self.context, left),pos.clone())?;
+                let right = le_error!(self.builder.read_expression(&self.context, right),pos.clone())?;
+                Ok(Expression::Right(le_error!(self.builder.build_bitwise(left, right, op.clone()),pos)?))
+            }
+        }
+    }
+
+    /// 生成复合赋值`a op= b`，左值只求值一次，`a[f()] += 1`中的f()只调用一次
+    fn build_compound_assign_expression(&mut self, expr: &CompoundAssignExpression) -> Result<Expression<'ctx>> {
+        //volatile左值的读写都是volatile的
+        if let Expr::Volatile(volatile) = expr.target.as_ref() {
+            let pointer = le_error!(self.build_expression(volatile.expr.as_ref())?.to_left_value(),volatile.pos())?;
+            let current = Expression::Right(self.builder.build_volatile_load(&self.context, pointer.clone()));
+            let value = self.build_expression(expr.value.as_ref())?;
+            let result = self.build_arithmetic_operation(&expr.op, current, value, expr.pos())?;
+            return Ok(Expression::Right(le_error!(self.builder.build_volatile_assign(&self.context,Expression::Left(pointer), result),expr.pos())?));
         }
+        let target = self.build_expression(expr.target.as_ref())?;
+        if !matches!(target, Expression::Left(_) | Expression::BitField(..)) {
+            return Err(CompileError::ExpressionIsNotLeftValueExpression.to_leerror(expr.target.pos()));
+        }
+        let value = self.build_expression(expr.value.as_ref())?;
+        let result = self.build_arithmetic_operation(&expr.op, target.clone(), value, expr.pos())?;
+        Ok(Expression::Right(le_error!(self.builder.build_assign(&self.context,target, result),expr.pos())?))
+    }
+
+    /// 成员访问的左侧为结构体指针时，先检查指针不为空再解引用
+    fn build_checked_struct_pointer(&mut self, expr: Expression<'ctx>) -> Expression<'ctx> {
+        let pointer = match expr {
+            Expression::Left(pointer) if matches!(pointer.ty.get_point_type(), LEBasicTypeEnum::Pointer(ref p) if matches!(p.get_point_type(), LEBasicTypeEnum::Struct(_))) => {
+                self.builder.build_load(&self.context, pointer).try_into().unwrap()
+            }
+            Expression::Right(LEBasicValueEnum::Pointer(pointer)) if matches!(pointer.ty.get_point_type(), LEBasicTypeEnum::Struct(_)) => { pointer }
+            other => { return other; }
+        };
+        self.build_null_check(&pointer);
+        Expression::Right(pointer.to_le_value_enum())
+    }
+
+    /// 指针为空时跳转到调用`llvm.trap`的块，否则继续执行
+    fn build_null_check(&mut self, pointer: &LEPointerValue<'ctx>) {
+        let current_block = self.builder.llvm_builder.get_insert_block().unwrap();
+        let trap_block = self.context.llvm_context.insert_basic_block_after(current_block, "null.trap");
+        let continue_block = self.context.llvm_context.insert_basic_block_after(trap_block, "null.ok");
+        let is_null = self.builder.llvm_builder.build_is_null(pointer.llvm_value, "");
+        self.builder.llvm_builder.build_conditional_branch(is_null, trap_block, continue_block);
+        self.builder.llvm_builder.position_at_end(trap_block);
+        let trap = self.context.compiler_context.runtime_functions["llvm.trap"].clone();
+        self.builder.llvm_builder.build_call(trap.llvm_value, &[], "");
+        self.builder.llvm_builder.build_unreachable();
+        self.builder.llvm_builder.position_at_end(continue_block);
     }
 
     fn build_identifier_expression(&mut self, value: &Identifier) -> Result<Expression<'ctx>> {
         match value.name.as_str() {
             "true" => { Ok(Expression::Right(self.context.bool_type().const_true_value().to_le_value_enum())) }
             "false" => { Ok(Expression::Right(self.context.bool_type().const_false_value().to_le_value_enum())) }
-            _ => { Ok(Expression::Left(self.context.get_variable(&value.name).map_err(|e| e.to_leerror(value.pos.clone()))?)) }
+            //编译期求值时标识符只能是当前调用中的局部变量
+            _ if !self.const_frames.is_empty() => {
+                match self.const_frames.last().unwrap().get(&value.name) {
+                    Some(constant) => { Ok(Expression::Right(constant.clone())) }
+                    None => { Err(CompileError::ExpressionIsNotConstant.to_leerror(value.pos.clone())) }
+                }
+            }
+            _ => {
+                let variable = self.context.get_variable(&value.name);
+                //不是变量的函数名作为指向该函数的指针
+                if variable.is_err() {
+                    if let Ok(function) = self.context.compiler_context.get_function(&value.name) {
+                        //函数指针总是以C调用约定调用，其他调用约定的函数不能取地址
+                        if function.llvm_value.get_call_conventions() != CALL_CONV_C {
+                            return Err(CompileError::FunctionPointerCallConvention { function: value.name.clone() }.to_leerror(value.pos.clone()));
+                        }
+                        let ty = LEFunctionPointerType::from_function_type(function.ty.clone());
+                        let llvm_value = function.llvm_value.as_global_value().as_pointer_value();
+                        return Ok(Expression::Right(LEFunctionPointerValue { ty, llvm_value }.to_le_value_enum()));
+                    }
+                }
+                Ok(Expression::Left(variable.map_err(|e| e.to_leerror(value.pos.clone()))?))
+            }
+        }
+    }
+
+    /// 通过函数指针调用函数，被调用的表达式必须是函数指针
+    fn build_indirect_call_expression(&mut self, value: &IndirectCall) -> Result<Expression<'ctx>> {
+        let callee = self.build_expression(value.callee.as_ref())?;
+        let callee = match le_error!(self.builder.read_expression(&self.context, callee),value.callee.pos())? {
+            LEBasicValueEnum::FunctionPointer(function) => { function }
+            other => {
+                return Err(CompileError::TypeMismatched { expect: "function pointer".into(), found: other.get_le_type().to_string() }.to_leerror(value.callee.pos()));
+            }
+        };
+        let param_count = callee.ty.function_type().param_types().len();
+        if param_count != value.params.len() {
+            return Err(CompileError::ArgumentCountMismatch { function: callee.ty.to_string(), expect: param_count, found: value.params.len() }.to_leerror(value.pos.clone()));
+        }
+        let mut params = vec![];
+        for param in value.params.iter() {
+            params.push(self.build_expression(param)?)
         }
+        le_error!(self.builder.build_indirect_call(&self.context, callee, &params),value.pos.clone())
     }
 
     fn build_number_literal_expression(&mut self, value: &NumberLiteral) -> Result<Expression<'ctx>> {
-        match value.number {
-            Number::Integer(i) => {
-                let ty = self.context.i32_type();
-                let value = ty.get_llvm_type().const_int(i, true);
-                Ok(Expression::Right(LEIntegerValue { ty, llvm_value: value }.to_le_value_enum()))
+        //有类型后缀时使用后缀指定的类型，否则整数默认为i32，浮点数默认为f64
+        let ty = match value.number.suffix() {
+            Some(suffix) => {
+                let declarator = TypeDeclarator::TypeIdentifier(Identifier { name: suffix.to_string(), pos: value.pos.clone() });
+                le_error!(self.context.get_generic_type(&declarator), value.pos.clone())?
+            }
+            None => {
+                match value.number {
+                    Number::Integer(..) => { self.context.i32_type().to_le_type_enum() }
+                    Number::Float(..) => { self.context.double_type().to_le_type_enum() }
+                }
+            }
+        };
+        match (&value.number, ty) {
+            (Number::Integer(i, _), LEBasicTypeEnum::Integer(ty)) => {
+                let bit_width = ty.get_llvm_type().get_bit_width();
+                let max_value = if ty.signed() { (1u128 << (bit_width - 1)) - 1 } else { (1u128 << bit_width) - 1 };
+                if *i as u128 > max_value {
+                    return Err(CompileError::IntegerLiteralOutOfRange { literal: value.number.to_string(), ty: ty.to_string() }.to_leerror(value.pos.clone()));
+                }
+                let llvm_value = ty.get_llvm_type().const_int(*i, ty.signed());
+                Ok(Expression::Right(LEIntegerValue { ty, llvm_value }.to_le_value_enum()))
             }
-            Number::Float(f) => {
-                let ty = self.context.double_type();
-                let value = ty.get_llvm_type().const_float(f);
-                Ok(Expression::Right(LEFloatValue { ty, llvm_value: value }.to_le_value_enum()))
+            (Number::Integer(i, _), LEBasicTypeEnum::Float(ty)) => {
+                let llvm_value = ty.get_llvm_type().const_float(*i as f64);
+                Ok(Expression::Right(LEFloatValue { ty, llvm_value }.to_le_value_enum()))
+            }
+            (Number::Float(f, _), LEBasicTypeEnum::Float(ty)) => {
+                let llvm_value = ty.get_llvm_type().const_float(*f);
+                Ok(Expression::Right(LEFloatValue { ty, llvm_value }.to_le_value_enum()))
+            }
+            (_, ty) => {
+                Err(CompileError::TypeMismatched { expect: ty.to_string(), found: value.number.to_string() }.to_leerror(value.pos.clone()))
             }
         }
     }
 
     fn build_call_expression(&mut self, value: &FunctionCall) -> Result<Expression<'ctx>> {
-        //从符号表查找函数
-        let function = le_error!(self.context.compiler_context.get_function(&value.function_name.name),value.function_name.pos())?;
+        self.build_call_expression_with_tail_hint(value, false)
+    }
+
+    /// 生成函数调用，in_tail_position表示调用结果直接作为返回值
+    fn build_call_expression_with_tail_hint(&mut self, value: &FunctionCall, in_tail_position: bool) -> Result<Expression<'ctx>> {
+        //内置的原子操作
+        if let "atomic_add" | "atomic_load" | "atomic_store" = value.function_name.name.as_str() {
+            return self.build_atomic_builtin(value);
+        }
+        //内置的字符串操作
+        if let "strlen" | "streq" = value.function_name.name.as_str() {
+            return self.build_string_builtin(value);
+        }
+        if value.function_name.name == "layout" {
+            return self.build_layout_builtin(value);
+        }
+        if value.function_name.name == "static_assert" {
+            return self.build_static_assert(value);
+        }
+        //内置的向量操作
+        if let "vec" | "vec_shuffle" = value.function_name.name.as_str() {
+            return self.build_vector_builtin(value);
+        }
+        if let "vec_extract" | "vec_insert" = value.function_name.name.as_str() {
+            return self.build_vector_element_builtin(value);
+        }
+        //内置的整数溢出处理运算
+        if let "wrapping_add" | "checked_add" | "saturating_add" = value.function_name.name.as_str() {
+            return self.build_overflow_builtin(value);
+        }
+        //没有用户定义的同名函数或变量时，`len`为取数组长度的内置函数
+        if value.function_name.name == "len" && self.context.compiler_context.symbols.get_symbol("len").is_none() {
+            return self.build_len_builtin(value);
+        }
+        //保存函数指针的变量通过指针调用
+        if matches!(self.context.get_variable(&value.function_name.name), Ok(pointer) if pointer.ty.get_point_type().is_function_pointer_type()) {
+            return self.build_indirect_call_expression(&IndirectCall {
+                callee: Box::new(Expr::Identifier(value.function_name.clone())),
+                params: value.params.clone(),
+                pos: value.pos.clone(),
+            });
+        }
         let mut params = vec![];
         //对所有实参求值
         for param in value.params.iter() {
             params.push(self.build_expression(param)?)
         }
-        //生成函数调用
-        self.builder.build_call(&self.context, function, &params).map_err(|e| e.to_leerror(value.pos.clone()))
-    }
-
-    fn build_local_variable_definition(&mut self, variable: &Variable) -> Result<Expression<'ctx>> {
-        //获取变量的类型，初始值
-        let initial_value_expr = self.build_expression(variable.value.as_ref())?;
-        let initial_value = le_error!(self.builder.read_expression(&self.context, initial_value_expr),variable.value.pos())?;
-        let initial_type = LEBasicValue::get_le_type(&initial_value);
-
-        //保存指令插入点为入口块开头
-        let current_insert_block = self.builder.llvm_builder.get_insert_block().unwrap();
-        let parent_function = self.context.compiler_context.current_function.unwrap();
-        let entry_block = parent_function.get_first_basic_block().unwrap();
-        if let Some(first_instruction) = entry_block.get_first_instruction() {
-            self.builder.llvm_builder.position_at(entry_block, &first_instruction);
-        } else {
-            self.builder.llvm_builder.position_at_end(entry_block);
+        //实参都是常量时，在编译期求值`@const`函数的调用
+        if let Some(function) = self.const_functions.get(&value.function_name.name).cloned() {
+            let arguments = params.iter().map(|param| match param {
+                Expression::Right(argument) if is_constant_value(&argument.to_llvm_basic_value_enum()) => { Some(argument.clone()) }
+                _ => { None }
+            }).collect::<Option<Vec<_>>>();
+            if let Some(arguments) = arguments {
+                match self.eval_const_call(&function, arguments, value.pos.clone()) {
+                    Ok(result) => { return Ok(Expression::Right(result)); }
+                    //常量上下文中必须在编译期求值，其他位置求值失败时生成普通的函数调用
+                    Err(e) if self.const_context => { return Err(e); }
+                    Err(_) => {}
+                }
+            }
         }
-        //生成内存申请的代码
-        let pointer = if let Some(variable_type) = &variable.prototype.type_declarator {
-            let target_type = le_error!(self.context.get_generic_type(variable_type),variable_type.pos())?;
-            if target_type == initial_type {
-                self.builder.build_alloca(&self.context, initial_type)
-            } else {
-                return Err(CompileError::TypeMismatched { expect: target_type.to_string(), found: initial_type.to_string() }.to_leerror(variable.pos()));
+        //从符号表查找函数，重载的函数只能按实参类型找到对应的定义
+        let param_types = params.iter().filter_map(|p| p.get_le_type()).collect::<Vec<_>>();
+        let name = &value.function_name.name;
+        let function = if self.overloaded_functions.contains(name) {
+            match self.context.compiler_context.get_function(&self.llvm_symbol_name(name, &param_types)) {
+                Ok(function) => { Ok(function) }
+                Err(_) => {
+                    let found = param_types.iter().map(|ty| ty.to_string()).collect::<Vec<_>>().join(", ");
+                    return Err(CompileError::NoMatchingOverload { name: name.clone(), found }.to_leerror(value.pos.clone()));
+                }
             }
         } else {
-            self.builder.build_alloca(&self.context, initial_type)
+            self.context.compiler_context.get_function(name)
         };
-        self.builder.llvm_builder.position_at_end(current_insert_block);
-        //初始化申请的内存
-        le_error!(self.builder.build_store(&self.context, pointer.clone(),initial_value),variable.pos())?;
+        let function = match function {
+            Ok(function) => { function }
+            //函数不存在时，给出名字最接近的函数作为建议
+            Err(CompileError::UnknownIdentifier { .. }) => {
+                return Err(CompileError::FunctionNotFound {
+                    name: value.function_name.name.clone(),
+                    suggestion: self.context.compiler_context.get_similar_function(&value.function_name.name),
+                }.to_leerror(value.function_name.pos()));
+            }
+            Err(e) => { return Err(e.to_leerror(value.function_name.pos())); }
+        };
+        //参数中有指针时可能指向调用者栈上的变量，不能标记为尾调用，数组参数可能退化为指针
+        let is_tail_call = in_tail_position && !function.ty.struct_return() && !param_types.iter().any(|ty| matches!(ty, LEBasicTypeEnum::Pointer(_) | LEBasicTypeEnum::Array(_)));
+        let is_noreturn = self.context.compiler_context.is_noreturn(function.llvm_value);
+        //生成函数调用
+        let result = self.builder.build_call_with_tail_hint(&self.context, function, &params, is_tail_call).map_err(|e| e.to_leerror(value.pos.clone()))?;
+        //不返回的函数调用之后直接结束当前块，调用所在的表达式的剩余部分生成在新的不可达块中，调用的值为undef
+        if is_noreturn {
+            self.builder.llvm_builder.build_unreachable();
+            let noreturn_block = self.context.llvm_context.insert_basic_block_after(self.builder.llvm_builder.get_insert_block().unwrap(), "noreturn.after");
+            self.builder.llvm_builder.position_at_end(noreturn_block);
+            self.noreturn_block = Some(noreturn_block);
+            if let Expression::Right(return_value) = &result {
+                let ty = return_value.get_le_type();
+                let undef = undef_value(ty.get_llvm_basic_type());
+                return Ok(Expression::Right(le_error!(LEBasicValueEnum::from_type_and_llvm_value(ty, undef),value.pos.clone())?));
+            }
+        }
+        Ok(result)
+    }
+
+    /// 在编译期求值`@const`函数的一次调用，实参的类型和个数必须与形参一致
+    fn eval_const_call(&mut self, function: &FunctionDefinition, arguments: Vec<LEBasicValueEnum<'ctx>>, position: Position) -> Result<LEBasicValueEnum<'ctx>> {
+        let prototype = &function.prototype;
+        if prototype.param_types.len() != arguments.len() {
+            return Err(CompileError::ArgumentCountMismatch { function: prototype.identifier.name.clone(), expect: prototype.param_types.len(), found: arguments.len() }.to_leerror(position));
+        }
+        if self.const_frames.len() >= self.max_expression_depth {
+            return Err(CompileError::ExpressionTooDeep { limit: self.max_expression_depth }.to_leerror(position));
+        }
+        let mut frame = HashMap::new();
+        for ((name, param_type), argument) in function.param_names.iter().zip(prototype.param_types.iter()).zip(arguments) {
+            let ty = le_error!(self.context.get_generic_type(param_type),param_type.pos())?;
+            if argument.get_le_type() != ty {
+                return Err(CompileError::TypeMismatched { expect: ty.to_string(), found: argument.get_le_type().to_string() }.to_leerror(position));
+            }
+            frame.insert(name.clone(), argument);
+        }
+        self.const_frames.push(frame);
+        let result = self.eval_const_statements(&function.code_block.statements);
+        self.const_frames.pop();
+        let value = result?.ok_or_else(|| CompileError::ExpressionIsNotConstant.to_leerror(function.code_block.pos.clone()))?;
+        let return_type = match &prototype.return_type {
+            Some(type_declarator) => { le_error!(self.context.get_generic_type(type_declarator),type_declarator.pos())? }
+            None => { return Err(CompileError::ExpressionIsNotConstant.to_leerror(prototype.pos())); }
+        };
+        if value.get_le_type() != return_type {
+            return Err(CompileError::ReturnTypeMismatch { expect: return_type.to_string(), found: value.get_le_type().to_string() }.to_leerror(position));
+        }
+        Ok(value)
+    }
+
+    /// 依次求值语句，遇到返回语句时返回其值
+    fn eval_const_statements(&mut self, statements: &[Statement]) -> Result<Option<LEBasicValueEnum<'ctx>>> {
+        for statement in statements.iter() {
+            match statement {
+                Statement::VariableDefinition(variable) => {
+                    let value = self.eval_const_expression(&variable.value)?;
+                    if let Some(type_declarator) = &variable.prototype.type_declarator {
+                        let ty = le_error!(self.context.get_generic_type(type_declarator),type_declarator.pos())?;
+                        if value.get_le_type() != ty {
+                            return Err(CompileError::TypeMismatched { expect: ty.to_string(), found: value.get_le_type().to_string() }.to_leerror(variable.pos.clone()));
+                        }
+                    }
+                    self.const_frames.last_mut().unwrap().insert(variable.prototype.identifier.name.clone(), value);
+                }
+                Statement::Expressions(expr) => {
+                    //对局部变量的复合赋值展开为普通赋值，变量只是读取常量帧，重复求值没有副作用
+                    let desugared;
+                    let expr = match expr.as_ref() {
+                        Expr::CompoundAssign(assign) => {
+                            desugared = Expr::BinaryOperator(BinaryOpExpression {
+                                op: Operator::Assign,
+                                left: assign.target.clone(),
+                                right: Box::new(Expr::BinaryOperator(BinaryOpExpression { op: assign.op.clone(), left: assign.target.clone(), right: assign.value.clone(), pos: assign.pos.clone() })),
+                                pos: assign.pos.clone(),
+                            });
+                            &desugared
+                        }
+                        expr => { expr }
+                    };
+                    if let Expr::BinaryOperator(BinaryOpExpression { op: Operator::Assign, left, right, pos }) = expr {
+                        if let Expr::Identifier(identifier) = left.as_ref() {
+                            let value = self.eval_const_expression(right)?;
+                            let ty = self.eval_const_expression(left)?.get_le_type();
+                            if value.get_le_type() != ty {
+                                return Err(CompileError::TypeMismatched { expect: ty.to_string(), found: value.get_le_type().to_string() }.to_leerror(pos.clone()));
+                            }
+                            self.const_frames.last_mut().unwrap().insert(identifier.name.clone(), value);
+                            continue;
+                        }
+                    }
+                    self.eval_const_expression(expr)?;
+                }
+                Statement::Return(Some(expr), _) => { return Ok(Some(self.eval_const_expression(expr)?)); }
+                Statement::If(if_statement) => {
+                    let result = if self.eval_const_condition(&if_statement.cond)? {
+                        self.eval_const_statements(&if_statement.then_block.statements)?
+                    } else if let Some(else_block) = &if_statement.else_block {
+                        self.eval_const_statements(&else_block.statements)?
+                    } else {
+                        None
+                    };
+                    if result.is_some() {
+                        return Ok(result);
+                    }
+                }
+                Statement::Void(_) => {}
+                _ => { return Err(CompileError::ExpressionIsNotConstant.to_leerror(statement.pos())); }
+            }
+        }
+        Ok(None)
+    }
+
+    fn eval_const_condition(&mut self, cond: &Expr) -> Result<bool> {
+        let value = self.eval_const_expression(cond)?;
+        let bool_value = self.build_bool_value(value, cond.pos())?;
+        Ok(bool_value.llvm_value.get_zero_extended_constant().ok_or_else(|| CompileError::ExpressionIsNotConstant.to_leerror(cond.pos()))? != 0)
+    }
+
+    /// 求值`@const`函数中的表达式，运算的操作数先求值为常量，再交给普通的代码生成，常量运算由LLVM直接折叠
+    fn eval_const_expression(&mut self, expr: &Expr) -> Result<LEBasicValueEnum<'ctx>> {
+        //不会与源码中的标识符冲突的临时名字，用于保存已经求值的操作数
+        let operand = |name: &str, pos: Position| Box::new(Expr::Identifier(Identifier { name: name.into(), pos }));
+        let value = match expr {
+            Expr::CallExpression(call) => {
+                let function = self.const_functions.get(&call.function_name.name).cloned()
+                    .ok_or_else(|| CompileError::ExpressionIsNotConstant.to_leerror(call.pos.clone()))?;
+                let mut arguments = vec![];
+                for param in call.params.iter() {
+                    arguments.push(self.eval_const_expression(param)?);
+                }
+                self.eval_const_call(&function, arguments, call.pos.clone())?
+            }
+            Expr::Ternary(ternary) => {
+                if self.eval_const_condition(&ternary.cond)? {
+                    self.eval_const_expression(&ternary.then_value)?
+                } else {
+                    self.eval_const_expression(&ternary.else_value)?
+                }
+            }
+            //逻辑与和逻辑或需要短路，右侧可能是终止递归的条件
+            Expr::BinaryOperator(binary) if matches!(binary.op, Operator::And | Operator::Or) => {
+                let left = self.eval_const_condition(&binary.left)?;
+                let absorbing = matches!(binary.op, Operator::Or);
+                let result = if left == absorbing { left } else { self.eval_const_condition(&binary.right)? };
+                let bool_value = if result { self.context.bool_type().const_true_value() } else { self.context.bool_type().const_false_value() };
+                bool_value.to_le_value_enum()
+            }
+            Expr::BinaryOperator(binary) => {
+                let left = self.eval_const_expression(&binary.left)?;
+                let right = if let Operator::Cast = binary.op { None } else { Some(self.eval_const_expression(&binary.right)?) };
+                let frame = self.const_frames.last_mut().unwrap();
+                frame.insert("0left".into(), left);
+                let right_operand = match right {
+                    Some(right) => {
+                        frame.insert("0right".into(), right);
+                        operand("0right", binary.right.pos())
+                    }
+                    None => { binary.right.clone() }
+                };
+                let expression = self.build_expression(&Expr::BinaryOperator(BinaryOpExpression {
+                    op: binary.op.clone(),
+                    left: operand("0left", binary.left.pos()),
+                    right: right_operand,
+                    pos: binary.pos.clone(),
+                }))?;
+                le_error!(self.builder.read_expression(&self.context, expression),binary.pos.clone())?
+            }
+            Expr::UnaryOperator(unary) => {
+                let value = self.eval_const_expression(&unary.expr)?;
+                self.const_frames.last_mut().unwrap().insert("0operand".into(), value);
+                let expression = self.build_expression(&Expr::UnaryOperator(UnaryOpExpression {
+                    op: unary.op.clone(),
+                    expr: operand("0operand", unary.expr.pos()),
+                    pos: unary.pos.clone(),
+                }))?;
+                le_error!(self.builder.read_expression(&self.context, expression),unary.pos.clone())?
+            }
+            Expr::NumberLiteral(_) | Expr::Identifier(_) => {
+                let expression = self.build_expression(expr)?;
+                le_error!(self.builder.read_expression(&self.context, expression),expr.pos())?
+            }
+            _ => { return Err(CompileError::ExpressionIsNotConstant.to_leerror(expr.pos())); }
+        };
+        if !is_constant_value(&value.to_llvm_basic_value_enum()) {
+            return Err(CompileError::ExpressionIsNotConstant.to_leerror(expr.pos()));
+        }
+        Ok(value)
+    }
+
+    /// 检查`static_assert(cond)`或`static_assert(cond, "message")`，条件必须能在编译期折叠为常量，不生成任何代码
+    fn build_static_assert(&mut self, value: &FunctionCall) -> Result<Expression<'ctx>> {
+        if !(1..=2).contains(&value.params.len()) {
+            return Err(CompileError::ArgumentCountMismatch { function: value.function_name.name.clone(), expect: 1, found: value.params.len() }.to_leerror(value.pos.clone()));
+        }
+        let cond = &value.params[0];
+        let expr = self.build_const_expression(cond)?;
+        let cond_value = match le_error!(self.builder.read_expression(&self.context, expr),cond.pos())? {
+            LEBasicValueEnum::Bool(bool_value) => { bool_value }
+            other => {
+                return Err(CompileError::TypeMismatched { expect: "bool".into(), found: other.get_le_type().to_string() }.to_leerror(cond.pos()));
+            }
+        };
+        let constant = cond_value.llvm_value.get_zero_extended_constant()
+            .ok_or_else(|| CompileError::ExpressionIsNotConstant.to_leerror(cond.pos()))?;
+        if constant == 0 {
+            let message = match value.params.get(1) {
+                Some(Expr::StringLiteral(message)) => { message.content.clone() }
+                Some(other) => {
+                    return Err(CompileError::TypeMismatched { expect: "string literal".into(), found: describe_expression(other) }.to_leerror(other.pos()));
+                }
+                None => { "condition is false".into() }
+            };
+            return Err(CompileError::StaticAssertionFailed { message }.to_leerror(value.pos.clone()));
+        }
+        Ok(Expression::Unit)
+    }
+
+    /// 生成`layout(T)`，返回编译期常量结构体`{ size: i64, align: i64 }`，由数据布局计算类型T的大小和对齐
+    fn build_layout_builtin(&mut self, value: &FunctionCall) -> Result<Expression<'ctx>> {
+        if value.params.len() != 1 {
+            return Err(CompileError::ArgumentCountMismatch { function: value.function_name.name.clone(), expect: 1, found: value.params.len() }.to_leerror(value.pos.clone()));
+        }
+        let ty = match &value.params[0] {
+            Expr::Identifier(identifier) => {
+                le_error!(self.context.get_generic_type(&TypeDeclarator::TypeIdentifier(identifier.clone())),identifier.pos())?
+            }
+            other => {
+                return Err(CompileError::TypeMismatched { expect: "type name".into(), found: describe_expression(other) }.to_leerror(other.pos()));
+            }
+        };
+        let llvm_type = ty.get_llvm_basic_type();
+        let size = self.target_data.get_abi_size(&llvm_type);
+        let align = self.target_data.get_abi_alignment(&llvm_type) as u64;
+        let i64_type = self.context.i64_type();
+        let layout_type = match &self.context.compiler_context.layout_type {
+            Some(layout_type) => { layout_type.clone() }
+            None => {
+                let layout_type = LEStructType::from_llvm_type(&self.context, "Layout", &["size", "align"], &[i64_type.to_le_type_enum(), i64_type.to_le_type_enum()]);
+                self.context.compiler_context.layout_type = Some(layout_type.clone());
+                layout_type
+            }
+        };
+        let llvm_value = layout_type.get_llvm_type().const_named_struct(&[
+            i64_type.get_llvm_type().const_int(size, false).into(),
+            i64_type.get_llvm_type().const_int(align, false).into(),
+        ]);
+        Ok(Expression::Right(LEStructValue { ty: layout_type, llvm_value }.to_le_value_enum()))
+    }
+
+    /// 生成整数的`wrapping_add`、`checked_add`和`saturating_add`，两个参数必须是相同的整数类型
+    /// `wrapping_add`溢出时回绕，`checked_add`返回`{ value, overflowed }`，`saturating_add`溢出时取类型的最大或最小值
+    fn build_overflow_builtin(&mut self, value: &FunctionCall) -> Result<Expression<'ctx>> {
+        let name = value.function_name.name.as_str();
+        if value.params.len() != 2 {
+            return Err(CompileError::ArgumentCountMismatch { function: name.into(), expect: 2, found: value.params.len() }.to_leerror(value.pos.clone()));
+        }
+        let mut operands = vec![];
+        for param in value.params.iter() {
+            let expr = self.build_expression(param)?;
+            operands.push(le_error!(self.builder.read_expression(&self.context, expr),param.pos())?);
+        }
+        let (lhs, rhs) = match (&operands[0], &operands[1]) {
+            (LEBasicValueEnum::Integer(lhs), LEBasicValueEnum::Integer(rhs)) if lhs.ty == rhs.ty => { (lhs.clone(), rhs.clone()) }
+            (lhs, rhs) => {
+                return Err(CompileError::NoSuitableBinaryOperator {
+                    op: Operator::Plus,
+                    left_type: lhs.get_le_type().to_string(),
+                    right_type: rhs.get_le_type().to_string(),
+                }.to_leerror(value.pos.clone()));
+            }
+        };
+        let ty = lhs.ty.clone();
+        if name == "wrapping_add" {
+            let llvm_value = self.builder.llvm_builder.build_int_add(lhs.llvm_value, rhs.llvm_value, "");
+            return Ok(Expression::Right(LEIntegerValue { ty, llvm_value }.to_le_value_enum()));
+        }
+        let intrinsic_name = format!("llvm.{}add.{}.i{}",
+                                     if ty.signed() { "s" } else { "u" },
+                                     if name == "checked_add" { "with.overflow" } else { "sat" },
+                                     ty.get_llvm_type().get_bit_width());
+        //全局变量的初始值在声明intrinsic之前生成，其中只能使用可以常量折叠的wrapping_add
+        let intrinsic = *self.context.compiler_context.intrinsic_functions.get(&intrinsic_name)
+            .ok_or_else(|| CompileError::ExpressionIsNotConstant.to_leerror(value.pos.clone()))?;
+        let result = self.builder.llvm_builder.build_call(intrinsic, &[lhs.llvm_value.into(), rhs.llvm_value.into()], "")
+            .try_as_basic_value().left().unwrap();
+        if name == "saturating_add" {
+            return Ok(Expression::Right(LEIntegerValue { ty, llvm_value: result.into_int_value() }.to_le_value_enum()));
+        }
+        let checked_type = match self.context.compiler_context.checked_types.get(&ty.to_string()) {
+            Some(checked_type) => { checked_type.clone() }
+            None => {
+                let checked_type = LEStructType::from_llvm_type(&self.context, &format!("Checked.{}", ty), &["value", "overflowed"], &[ty.to_le_type_enum(), self.context.bool_type().to_le_type_enum()]);
+                self.context.compiler_context.checked_types.insert(ty.to_string(), checked_type.clone());
+                checked_type
+            }
+        };
+        //intrinsic返回的是字面结构体，逐个成员复制到具名的结构体中
+        let mut llvm_value = checked_type.get_llvm_type().get_undef();
+        for index in 0..2 {
+            let member = self.builder.llvm_builder.build_extract_value(result.into_struct_value(), index, "").unwrap();
+            llvm_value = self.builder.llvm_builder.build_insert_value(llvm_value, member, index, "").unwrap().into_struct_value();
+        }
+        Ok(Expression::Right(LEStructValue { ty: checked_type, llvm_value }.to_le_value_enum()))
+    }
+
+    /// 生成向量操作的内置函数
+    /// `vec(a, b, ...)`由类型相同的整数或浮点数构造向量，`vec_shuffle(a, b, i...)`按常量下标从两个向量中选取元素组成新向量
+    fn build_vector_builtin(&mut self, value: &FunctionCall) -> Result<Expression<'ctx>> {
+        let name = value.function_name.name.as_str();
+        let expect_count = if name == "vec" { 1 } else { 3 };
+        if value.params.len() < expect_count {
+            return Err(CompileError::ArgumentCountMismatch { function: name.into(), expect: expect_count, found: value.params.len() }.to_leerror(value.pos.clone()));
+        }
+        let operand_count = if name == "vec" { value.params.len() } else { 2 };
+        let mut operands = vec![];
+        for param in value.params[..operand_count].iter() {
+            let expr = self.build_expression(param)?;
+            operands.push(le_error!(self.builder.read_expression(&self.context, expr),param.pos())?);
+        }
+        if name == "vec" {
+            let element_type = operands[0].get_le_type();
+            let vector_type = LEVectorType::new(element_type.clone(), operands.len() as u32).ok_or_else(|| CompileError::TypeMismatched {
+                expect: "integer or float".into(),
+                found: element_type.to_string(),
+            }.to_leerror(value.params[0].pos()))?;
+            let mut llvm_value = vector_type.get_llvm_type().get_undef();
+            for (index, (operand, param)) in operands.into_iter().zip(value.params.iter()).enumerate() {
+                if operand.get_le_type() != element_type {
+                    return Err(CompileError::TypeMismatched { expect: element_type.to_string(), found: operand.get_le_type().to_string() }.to_leerror(param.pos()));
+                }
+                let index = self.context.i32_type().get_llvm_type().const_int(index as u64, false);
+                llvm_value = self.builder.llvm_builder.build_insert_element(llvm_value, operand.to_llvm_basic_value_enum(), index, "");
+            }
+            return Ok(Expression::Right(LEVectorValue { ty: vector_type, llvm_value }.to_le_value_enum()));
+        }
+        let (left, right) = match (&operands[0], &operands[1]) {
+            (LEBasicValueEnum::Vector(left), LEBasicValueEnum::Vector(right)) if left.ty == right.ty => { (left.clone(), right.clone()) }
+            (LEBasicValueEnum::Vector(left), other) => {
+                return Err(CompileError::TypeMismatched { expect: left.ty.to_string(), found: other.get_le_type().to_string() }.to_leerror(value.params[1].pos()));
+            }
+            (other, _) => {
+                return Err(CompileError::TypeMismatched { expect: "vector".into(), found: other.get_le_type().to_string() }.to_leerror(value.params[0].pos()));
+            }
+        };
+        //下标必须是常量，0..N选取第一个向量的元素，N..2N选取第二个向量的元素
+        let mut mask = vec![];
+        for param in value.params[2..].iter() {
+            let index = match param {
+                Expr::NumberLiteral(NumberLiteral { number: Number::Integer(index, None), .. }) => { *index }
+                _ => { return Err(CompileError::ExpressionIsNotConstant.to_leerror(param.pos())); }
+            };
+            if index >= 2 * left.ty.len() as u64 {
+                return Err(CompileError::ArrayIndexOutOfBounds { index: index as i64, length: 2 * left.ty.len() }.to_leerror(param.pos()));
+            }
+            mask.push(self.context.i32_type().get_llvm_type().const_int(index, false));
+        }
+        let result_type = LEVectorType::new(left.ty.element_type(), mask.len() as u32).unwrap();
+        let mask = VectorType::const_vector(&mask);
+        let llvm_value = self.builder.llvm_builder.build_shuffle_vector(left.llvm_value, right.llvm_value, mask, "");
+        Ok(Expression::Right(LEVectorValue { ty: result_type, llvm_value }.to_le_value_enum()))
+    }
+
+    /// 生成`vec_extract(v, i)`和`vec_insert(v, i, x)`，读取向量的第i个元素或返回替换了第i个元素的新向量
+    /// 下标可以是运行时的整数，为常量时检查是否越界
+    fn build_vector_element_builtin(&mut self, value: &FunctionCall) -> Result<Expression<'ctx>> {
+        let name = value.function_name.name.as_str();
+        let expect_count = if name == "vec_extract" { 2 } else { 3 };
+        if value.params.len() != expect_count {
+            return Err(CompileError::ArgumentCountMismatch { function: name.into(), expect: expect_count, found: value.params.len() }.to_leerror(value.pos.clone()));
+        }
+        let mut operands = vec![];
+        for param in value.params.iter() {
+            let expr = self.build_expression(param)?;
+            operands.push(le_error!(self.builder.read_expression(&self.context, expr),param.pos())?);
+        }
+        let vector = match &operands[0] {
+            LEBasicValueEnum::Vector(vector) => { vector.clone() }
+            other => {
+                return Err(CompileError::TypeMismatched { expect: "vector".into(), found: other.get_le_type().to_string() }.to_leerror(value.params[0].pos()));
+            }
+        };
+        let index = match &operands[1] {
+            LEBasicValueEnum::Integer(index) => { index.clone() }
+            other => {
+                return Err(CompileError::TypeMismatched { expect: "integer".into(), found: other.get_le_type().to_string() }.to_leerror(value.params[1].pos()));
+            }
+        };
+        if let Some(constant) = index.llvm_value.get_zero_extended_constant() {
+            if constant >= vector.ty.len() as u64 {
+                return Err(CompileError::ArrayIndexOutOfBounds { index: constant as i64, length: vector.ty.len() }.to_leerror(value.params[1].pos()));
+            }
+        }
+        if name == "vec_extract" {
+            let element = self.builder.llvm_builder.build_extract_element(vector.llvm_value, index.llvm_value, "");
+            return Ok(Expression::Right(le_error!(LEBasicValueEnum::from_type_and_llvm_value(vector.ty.element_type(), element),value.pos.clone())?));
+        }
+        let element_type = vector.ty.element_type();
+        if operands[2].get_le_type() != element_type {
+            return Err(CompileError::TypeMismatched { expect: element_type.to_string(), found: operands[2].get_le_type().to_string() }.to_leerror(value.params[2].pos()));
+        }
+        let llvm_value = self.builder.llvm_builder.build_insert_element(vector.llvm_value, operands[2].to_llvm_basic_value_enum(), index.llvm_value, "");
+        Ok(Expression::Right(LEVectorValue { ty: vector.ty, llvm_value }.to_le_value_enum()))
+    }
+
+    fn build_inline_asm(&mut self, value: &InlineAsm) -> Result<Expression<'ctx>> {
+        //对所有参数求值，并根据参数类型和返回类型构造内联汇编的函数类型
+        let mut args = vec![];
+        let mut param_llvm_metadata_types = vec![];
+        for arg in value.args.iter() {
+            let expr = self.build_expression(arg)?;
+            let arg_value = le_error!(self.builder.read_expression(&self.context, expr),arg.pos())?;
+            param_llvm_metadata_types.push(BasicMetadataTypeEnum::from(arg_value.get_le_type().get_llvm_basic_type()));
+            args.push(arg_value);
+        }
+        let return_type = match &value.return_type {
+            None => { None }
+            Some(type_declarator) => { Some(le_error!(self.context.get_generic_type(type_declarator),type_declarator.pos())?) }
+        };
+        let asm_function_type = match &return_type {
+            None => { self.context.llvm_context.void_type().fn_type(&param_llvm_metadata_types, false) }
+            Some(ty) => { ty.get_llvm_basic_type().fn_type(&param_llvm_metadata_types, false) }
+        };
+        Ok(self.builder.build_inline_asm(
+            &self.context,
+            asm_function_type,
+            return_type,
+            value.assembly.clone(),
+            value.constraints.clone(),
+            &args,
+        ).map_err(|e| e.to_leerror(value.pos()))?)
+    }
+
+    fn build_volatile_expression(&mut self, value: &VolatileExpression) -> Result<Expression<'ctx>> {
+        //作为右值使用时生成volatile load
+        let expr = self.build_expression(value.expr.as_ref())?;
+        let pointer = le_error!(expr.to_left_value(),value.pos())?;
+        Ok(Expression::Right(self.builder.build_volatile_load(&self.context, pointer)))
+    }
+
+    /// 内置函数`len(arr)`，定长数组的长度在类型中，直接得到i64常量，运行时长度的数组需要与长度一起作为参数传递
+    fn build_len_builtin(&mut self, value: &FunctionCall) -> Result<Expression<'ctx>> {
+        if value.params.len() != 1 {
+            return Err(CompileError::ArgumentCountMismatch { function: value.function_name.name.clone(), expect: 1, found: value.params.len() }.to_leerror(value.pos.clone()));
+        }
+        let array = &value.params[0];
+        let expr = self.build_expression(array)?;
+        let ty = match expr {
+            Expression::Left(pointer) => { pointer.ty.get_point_type() }
+            other => { le_error!(self.builder.read_expression(&self.context, other),array.pos())?.get_le_type() }
+        };
+        match ty {
+            LEBasicTypeEnum::Array(array_type) => {
+                let i64_type = self.context.i64_type();
+                let llvm_value = i64_type.get_llvm_type().const_int(array_type.len() as u64, false);
+                Ok(Expression::Right(LEIntegerValue { ty: i64_type, llvm_value }.to_le_value_enum()))
+            }
+            other => {
+                Err(CompileError::TypeMismatched { expect: "Array".into(), found: other.to_string() }.to_leerror(array.pos()))
+            }
+        }
+    }
+
+    fn build_method_call(&mut self, receiver: &Expr, call: &FunctionCall) -> Result<Expression<'ctx>> {
+        //方法以`结构体名.方法名`注册在符号表中，接收者的地址作为第一个参数传入
+        let receiver_expr = self.build_expression(receiver)?;
+        let mut receiver_pointer = le_error!(receiver_expr.to_left_value(),receiver.pos())?;
+        //接收者本身是指向结构体的指针时，先解引用一次
+        if let LEBasicTypeEnum::Pointer(_) = receiver_pointer.ty.get_point_type() {
+            receiver_pointer = self.builder.build_load(&self.context, receiver_pointer).try_into().unwrap();
+        }
+        let struct_type = match receiver_pointer.ty.get_point_type() {
+            LEBasicTypeEnum::Struct(struct_type) => { struct_type }
+            other => {
+                return Err(CompileError::TypeMismatched { expect: "Struct".into(), found: other.to_string() }.to_leerror(receiver.pos()));
+            }
+        };
+        let method_name = format!("{}.{}", struct_type.get_name(), call.function_name.name);
+        let function = self.context.compiler_context.get_function(&method_name)
+            .map_err(|_| CompileError::NoSuchMember { member_name: call.function_name.name.clone() }.to_leerror(call.function_name.pos()))?;
+        let mut params = vec![Expression::Right(receiver_pointer.to_le_value_enum())];
+        for param in call.params.iter() {
+            params.push(self.build_expression(param)?)
+        }
+        self.builder.build_call(&self.context, function, &params).map_err(|e| e.to_leerror(call.pos.clone()))
+    }
+
+    fn build_atomic_builtin(&mut self, value: &FunctionCall) -> Result<Expression<'ctx>> {
+        let name = value.function_name.name.as_str();
+        let expect_count = if name == "atomic_load" { 1 } else { 2 };
+        if value.params.len() != expect_count {
+            return Err(CompileError::ArgumentCountMismatch {
+                function: name.into(),
+                expect: expect_count,
+                found: value.params.len(),
+            }.to_leerror(value.pos.clone()));
+        }
+        let mut params = vec![];
+        for param in value.params.iter() {
+            params.push(self.build_expression(param)?)
+        }
+        let ptr = params.remove(0);
+        match name {
+            "atomic_add" => {
+                Ok(Expression::Right(le_error!(self.builder.build_atomic_add(&self.context, ptr, params.remove(0)),value.pos.clone())?))
+            }
+            "atomic_load" => {
+                Ok(Expression::Right(le_error!(self.builder.build_atomic_load(&self.context, ptr),value.pos.clone())?))
+            }
+            _ => {
+                le_error!(self.builder.build_atomic_store(&self.context, ptr, params.remove(0)),value.pos.clone())?;
+                Ok(Expression::Unit)
+            }
+        }
+    }
+
+    /// `strlen(s)`调用C运行时的`strlen`，`streq(a, b)`调用`strcmp`并与0比较
+    fn build_string_builtin(&mut self, value: &FunctionCall) -> Result<Expression<'ctx>> {
+        let name = value.function_name.name.as_str();
+        let expect_count = if name == "strlen" { 1 } else { 2 };
+        if value.params.len() != expect_count {
+            return Err(CompileError::ArgumentCountMismatch {
+                function: name.into(),
+                expect: expect_count,
+                found: value.params.len(),
+            }.to_leerror(value.pos.clone()));
+        }
+        let mut params = vec![];
+        for param in value.params.iter() {
+            params.push(self.build_expression(param)?)
+        }
+        let runtime_function = if name == "strlen" { "strlen" } else { "strcmp" };
+        let function = match self.context.compiler_context.runtime_functions.get(runtime_function) {
+            Some(function) => { function.clone() }
+            //独立环境下只能使用用户声明的运行时函数
+            None => {
+                self.context.compiler_context.get_function(runtime_function)
+                    .map_err(|_| CompileError::FeatureUnavailableInFreestanding { feature: name.into() }.to_leerror(value.pos.clone()))?
+            }
+        };
+        //运行时函数的参数类型为`ref i8`，由build_call检查实参类型
+        let result = le_error!(self.builder.build_call(&self.context, function, &params),value.pos.clone())?;
+        if name == "strlen" {
+            return Ok(result);
+        }
+        let compare_result = le_error!(self.builder.read_expression(&self.context, result),value.pos.clone())?;
+        let zero = self.context.i32_type().get_llvm_type().const_zero();
+        let llvm_value = self.builder.llvm_builder.build_int_compare(IntPredicate::EQ, compare_result.to_llvm_basic_value_enum().into_int_value(), zero, "");
+        Ok(Expression::Right(LEBoolValue { ty: self.context.bool_type(), llvm_value }.to_le_value_enum()))
+    }
+
+    fn build_local_variable_definition(&mut self, variable: &Variable) -> Result<Expression<'ctx>> {
+        //获取变量的类型，初始值
+        let initial_value_expr = self.build_expression(variable.value.as_ref())?;
+        let initial_value = le_error!(self.builder.read_expression(&self.context, initial_value_expr),variable.value.pos())?;
+        let initial_type = LEBasicValue::get_le_type(&initial_value);
+
+        //保存指令插入点为入口块开头
+        let current_insert_block = self.builder.llvm_builder.get_insert_block().unwrap();
+        let parent_function = self.context.compiler_context.current_function.unwrap();
+        let entry_block = parent_function.get_first_basic_block().unwrap();
+        if let Some(first_instruction) = entry_block.get_first_instruction() {
+            self.builder.llvm_builder.position_at(entry_block, &first_instruction);
+        } else {
+            self.builder.llvm_builder.position_at_end(entry_block);
+        }
+        //生成内存申请的代码
+        let pointer = if let Some(variable_type) = &variable.prototype.type_declarator {
+            let target_type = le_error!(self.context.get_generic_type(variable_type),variable_type.pos())?;
+            if target_type == initial_type {
+                self.builder.build_alloca(&self.context, initial_type)
+            } else {
+                return Err(CompileError::TypeMismatched { expect: target_type.to_string(), found: initial_type.to_string() }.to_leerror(variable.pos()));
+            }
+        } else {
+            self.builder.build_alloca(&self.context, initial_type)
+        };
+        self.builder.llvm_builder.position_at_end(current_insert_block);
+        //初始化申请的内存
+        le_error!(self.builder.build_store(&self.context, pointer.clone(),initial_value),variable.pos())?;
         le_error!(self.context.insert_local_variable(
             variable.prototype.identifier.name.clone(),
             pointer,variable.prototype.identifier.pos()),
@@ -323,125 +1694,311 @@ impl<'ctx> CodeGenerator<'ctx> {
 
     fn build_code_block(&mut self, code_block: &CodeBlock) -> Result<bool> {
         //对每一条语句调用生成函数
-        for statement in code_block.statements.iter() {
-            match statement {
-                Statement::Expressions(expr) => {
-                    self.build_expression(expr)?;
+        for (index, statement) in code_block.statements.iter().enumerate() {
+            if self.build_statement(statement)? {
+                //块已经结束，之后的语句不再生成，对第一条不是空语句的语句给出警告
+                if let Some(unreachable) = code_block.statements[index + 1..].iter().find(|s| !matches!(s, Statement::Void(_))) {
+                    self.warnings.push(Warning::UnreachableStatement { position: unreachable.pos() });
                 }
-                Statement::Return(expr) => {
-                    let value = self.build_expression(expr)?;
-                    self.build_return(value, expr.pos())?;
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// 生成一条语句，返回当前块是否已经结束
+    fn build_statement(&mut self, statement: &Statement) -> Result<bool> {
+        match statement {
+            Statement::Expressions(expr) => {
+                self.build_expression(expr)?;
+                //调用了不返回的函数时当前位于不可达的块中，结束这个块，之后的语句不再生成
+                let current_block = self.builder.llvm_builder.get_insert_block().unwrap();
+                if self.noreturn_block == Some(current_block) {
+                    self.builder.llvm_builder.build_unreachable();
                     return Ok(true);
                 }
-                Statement::If(if_expr) => {
-                    self.build_if_statement(if_expr)?;
-                }
-                Statement::ForLoop(for_loop) => {
-                    self.build_for_loop(for_loop)?;
-                }
-                Statement::VariableDefinition(variable_definition) => {
-                    self.build_local_variable_definition(variable_definition)?;
-                }
-                Statement::Void(_) => {}
-                Statement::WhileLoop(while_loop) => {
-                    self.build_while_loop(while_loop)?;
+                if current_block.get_terminator().is_some() {
+                    return Ok(true);
                 }
             }
+            Statement::Return(expr, position) => {
+                //直接返回函数调用的结果时，该调用处于尾调用位置
+                let value = match expr.as_deref() {
+                    Some(Expr::CallExpression(call)) => { self.build_call_expression_with_tail_hint(call, true)? }
+                    Some(expr) => { self.build_expression(expr)? }
+                    None => { Expression::Unit }
+                };
+                self.build_return(value, expr.as_ref().map_or(position.clone(), |expr| expr.pos()))?;
+                return Ok(true);
+            }
+            Statement::If(if_expr) => {
+                self.build_if_statement(if_expr)?;
+            }
+            Statement::ForLoop(for_loop) => {
+                self.build_for_loop(for_loop)?;
+            }
+            Statement::VariableDefinition(variable_definition) => {
+                self.build_local_variable_definition(variable_definition)?;
+            }
+            Statement::Break(value, position) => {
+                self.build_break(value.as_deref(), position.clone())?;
+                return Ok(true);
+            }
+            Statement::Continue(position) => {
+                self.build_continue(position.clone())?;
+                return Ok(true);
+            }
+            Statement::Unreachable(_) => {
+                self.builder.llvm_builder.build_unreachable();
+                return Ok(true);
+            }
+            //单独的分号是空语句，不生成任何指令
+            Statement::Void(_) => {}
+            Statement::WhileLoop(while_loop) => {
+                self.build_while_loop(while_loop)?;
+            }
         }
         Ok(false)
     }
 
-    fn build_return(&mut self, expr: Expression, position: Position) -> Result<()> {
+    fn build_return(&mut self, expr: Expression<'ctx>, position: Position) -> Result<()> {
         //拿到返回的basic block，将返回值存入返回变量
-        let return_variable = &self.context.compiler_context.return_variable;
         let return_block = self.context.compiler_context.return_block.unwrap();
-        let return_value = le_error!(self.builder.read_expression(&self.context, expr),position.clone())?;
-        if let Some(return_variable) = return_variable {
-            le_error!(self.builder.build_store(&self.context, return_variable.clone(), return_value),position)?;
+        match (self.context.compiler_context.current_return_type(), expr) {
+            //无返回值的函数只允许返回空值
+            (None, Expression::Unit) => {}
+            (None, expr) => {
+                let return_value = le_error!(self.builder.read_expression(&self.context, expr),position.clone())?;
+                return Err(CompileError::ReturnTypeMismatch {
+                    expect: "void".into(),
+                    found: return_value.get_le_type().to_string(),
+                }.to_leerror(position));
+            }
+            (Some(return_type), Expression::Unit) => {
+                return Err(CompileError::ReturnTypeMismatch {
+                    expect: return_type.to_string(),
+                    found: "void".into(),
+                }.to_leerror(position));
+            }
+            //检查返回值类型与函数声明的返回类型是否一致
+            (Some(return_type), expr) => {
+                let return_value = le_error!(self.builder.read_expression(&self.context, expr),position.clone())?;
+                let value_type = return_value.get_le_type();
+                if return_type != value_type {
+                    return Err(CompileError::ReturnTypeMismatch {
+                        expect: return_type.to_string(),
+                        found: value_type.to_string(),
+                    }.to_leerror(position));
+                }
+                let return_variable = self.context.compiler_context.return_variable.clone().unwrap();
+                le_error!(self.builder.build_store(&self.context, return_variable, return_value),position)?;
+            }
         }
         self.builder.llvm_builder.build_unconditional_branch(return_block);
         Ok(())
     }
 
-    fn build_for_loop(&mut self, for_loop: &ForLoop) -> Result<()> {
-        let loop_variable = for_loop.init_statement.as_ref();
+    /// 生成循环和分支的条件表达式，条件必须为bool类型
+    fn build_condition_expression(&mut self, cond_expr: &Expr) -> Result<LEBoolValue<'ctx>> {
+        //禁止直接使用赋值表达式作为条件，通常是误将`==`写成了`=`
+        if let Expr::BinaryOperator(BinaryOpExpression { op: Operator::Assign, .. }) = cond_expr {
+            return Err(CompileError::AssignmentInCondition.to_leerror(cond_expr.pos()));
+        }
+        let cond = self.build_expression(cond_expr)?;
+        let cond_value = le_error!(self.builder.read_expression(&self.context, cond),cond_expr.pos())?;
+        self.build_bool_value(cond_value, cond_expr.pos())
+    }
 
-        if let Statement::Expressions(cond_expr) = for_loop.condition.as_ref() {
-            //创建cond块,body块和after三个basic block
-            let cond_block = self.context.llvm_context.insert_basic_block_after(self.builder.llvm_builder.get_insert_block().unwrap(), "");
-            let body_block = self.context.llvm_context.insert_basic_block_after(cond_block, "");
-            let after_block = self.context.llvm_context.insert_basic_block_after(body_block, "");
-            self.context.compiler_context.push_block_table();
-            //如果有循环变量，则创建循环变量
-            if let Statement::VariableDefinition(v) = loop_variable {
-                self.build_local_variable_definition(v)?;
+    /// 将值转换为bool，只有开启implicit_bool时才允许非bool类型的值，
+    /// 指针与空指针比较，数字与0比较
+    fn build_bool_value(&mut self, value: LEBasicValueEnum<'ctx>, position: Position) -> Result<LEBoolValue<'ctx>> {
+        match value {
+            LEBasicValueEnum::Bool(bool_value) => { Ok(bool_value) }
+            LEBasicValueEnum::Pointer(pointer) if self.implicit_bool => {
+                let llvm_value = self.builder.llvm_builder.build_is_not_null(pointer.llvm_value, "");
+                Ok(LEBoolValue { ty: self.context.bool_type(), llvm_value })
             }
-            //创建body块的跳转
-            self.builder.llvm_builder.build_unconditional_branch(cond_block);
-            self.builder.llvm_builder.position_at_end(cond_block);
-            let cond = self.build_expression(cond_expr.as_ref())?;
-            let cond_value = le_error!(self.builder.read_expression(&self.context, cond),cond_expr.pos())?;
-            if let LEBasicValueEnum::Bool(bool_cond) = cond_value {
-                self.builder.llvm_builder.build_conditional_branch(bool_cond.get_llvm_value(), body_block, after_block);
-            } else {
-                return Err(CompileError::TypeMismatched {
-                    expect: "bool".into(),
-                    found: LEBasicValue::get_le_type(&cond_value).to_string(),
-                }.to_leerror(cond_expr.pos()));
+            value @ (LEBasicValueEnum::Integer(_) | LEBasicValueEnum::Float(_)) if self.implicit_bool => {
+                let bool_value = le_error!(self.builder.build_cast(&self.context, Expression::Right(value), self.context.bool_type().to_le_type_enum()),position)?;
+                Ok(bool_value.try_into().unwrap())
             }
-            self.builder.llvm_builder.position_at_end(body_block);
-            //生成循环体的代码
-            self.build_code_block(&for_loop.code_block)?;
+            value => {
+                Err(CompileError::TypeMismatched {
+                    expect: "Bool".into(),
+                    found: LEBasicValue::get_le_type(&value).to_string(),
+                }.to_leerror(position))
+            }
+        }
+    }
 
-            if let Statement::Expressions(step_expr) = for_loop.iterate.as_ref() {
+    fn build_for_loop(&mut self, for_loop: &ForLoop) -> Result<()> {
+        //条件为空语句时(`for(;;)`)为无限循环，只能通过break或return退出
+        let cond_expr = match for_loop.condition.as_ref() {
+            Statement::Expressions(cond_expr) => { Some(cond_expr) }
+            _ => { None }
+        };
+        //创建cond块,body块,step块和after四个basic block，有el块时在step块和after块之间创建else块
+        let cond_block = self.context.llvm_context.insert_basic_block_after(self.builder.llvm_builder.get_insert_block().unwrap(), "for.cond");
+        let body_block = self.context.llvm_context.insert_basic_block_after(cond_block, "for.body");
+        let step_block = self.context.llvm_context.insert_basic_block_after(body_block, "for.step");
+        let else_block = for_loop.else_block.as_ref().map(|_| self.context.llvm_context.insert_basic_block_after(step_block, "for.else"));
+        let after_block = self.context.llvm_context.insert_basic_block_after(else_block.unwrap_or(step_block), "for.end");
+        self.context.compiler_context.push_block_table();
+        //依次生成所有初始化语句，如果有循环变量，则创建循环变量
+        for init_statement in for_loop.init_statements.iter() {
+            match init_statement {
+                Statement::VariableDefinition(v) => {
+                    self.build_local_variable_definition(v)?;
+                }
+                Statement::Expressions(init_expr) => {
+                    self.build_expression(init_expr.as_ref())?;
+                }
+                _ => {}
+            }
+        }
+        //创建body块的跳转
+        self.builder.llvm_builder.build_unconditional_branch(cond_block);
+        self.builder.llvm_builder.position_at_end(cond_block);
+        match cond_expr {
+            Some(cond_expr) => {
+                let bool_cond = self.build_condition_expression(cond_expr.as_ref())?;
+                self.builder.llvm_builder.build_conditional_branch(bool_cond.get_llvm_value(), body_block, else_block.unwrap_or(after_block));
+            }
+            None => {
+                self.builder.llvm_builder.build_unconditional_branch(body_block);
+            }
+        }
+        self.builder.llvm_builder.position_at_end(body_block);
+        //生成循环体的代码，循环体中的break直接跳转到after块，continue跳转到step块
+        self.context.compiler_context.loop_blocks.push(LoopBlocks { break_block: after_block, continue_block: step_block, break_values: None });
+        let is_terminated = self.build_code_block(&for_loop.code_block)?;
+        self.context.compiler_context.loop_blocks.pop();
+        if !is_terminated {
+            self.builder.llvm_builder.build_unconditional_branch(step_block);
+        }
+        //依次生成所有步进语句
+        self.builder.llvm_builder.position_at_end(step_block);
+        for step_statement in for_loop.iterate.iter() {
+            if let Statement::Expressions(step_expr) = step_statement {
                 self.build_expression(step_expr.as_ref())?;
             }
-            self.builder.llvm_builder.build_unconditional_branch(cond_block);
-            self.builder.llvm_builder.position_at_end(after_block);
-            self.context.compiler_context.pop_block_table();
         }
+        self.builder.llvm_builder.build_unconditional_branch(cond_block);
+        self.build_loop_else_block(else_block, for_loop.else_block.as_ref(), after_block)?;
+        self.builder.llvm_builder.position_at_end(after_block);
+        self.context.compiler_context.pop_block_table();
         Ok(())
     }
 
     fn build_while_loop(&mut self, while_loop: &WhileLoop) -> Result<()> {
-        let cond_block = self.context.llvm_context.insert_basic_block_after(self.builder.llvm_builder.get_insert_block().unwrap(), "");
-        let body_block = self.context.llvm_context.insert_basic_block_after(cond_block, "");
-        let after_block = self.context.llvm_context.insert_basic_block_after(body_block, "");
+        let cond_block = self.context.llvm_context.insert_basic_block_after(self.builder.llvm_builder.get_insert_block().unwrap(), "while.cond");
+        let body_block = self.context.llvm_context.insert_basic_block_after(cond_block, "while.body");
+        let else_block = while_loop.else_block.as_ref().map(|_| self.context.llvm_context.insert_basic_block_after(body_block, "while.else"));
+        let after_block = self.context.llvm_context.insert_basic_block_after(else_block.unwrap_or(body_block), "while.end");
         self.builder.llvm_builder.build_unconditional_branch(cond_block);
         self.builder.llvm_builder.position_at_end(cond_block);
         self.context.compiler_context.push_block_table();
-        let cond = self.build_expression(while_loop.condition.as_ref())?;
-        let cond_value = le_error!(self.builder.read_expression(&self.context, cond),while_loop.condition.pos())?;
-        if let LEBasicValueEnum::Bool(bool_cond) = cond_value {
-            self.builder.llvm_builder.build_conditional_branch(bool_cond.get_llvm_value(), body_block, after_block);
-        } else {
-            return Err(CompileError::TypeMismatched {
-                expect: "bool".into(),
-                found: LEBasicValue::get_le_type(&cond_value).to_string(),
-            }.to_leerror(while_loop.condition.pos()));
+        let bool_cond = self.build_condition_expression(while_loop.condition.as_ref())?;
+        self.builder.llvm_builder.build_conditional_branch(bool_cond.get_llvm_value(), body_block, else_block.unwrap_or(after_block));
+        self.builder.llvm_builder.position_at_end(body_block);
+        //while循环中continue直接跳转到cond块重新判断条件
+        self.context.compiler_context.loop_blocks.push(LoopBlocks { break_block: after_block, continue_block: cond_block, break_values: None });
+        let is_terminated = self.build_code_block(&while_loop.code_block)?;
+        self.context.compiler_context.loop_blocks.pop();
+        if !is_terminated {
+            self.builder.llvm_builder.build_unconditional_branch(cond_block);
+        }
+        self.build_loop_else_block(else_block, while_loop.else_block.as_ref(), after_block)?;
+        self.builder.llvm_builder.position_at_end(after_block);
+        self.context.compiler_context.pop_block_table();
+        Ok(())
+    }
+
+    /// 生成作为表达式的while循环，循环只能通过`break value;`结束，所有break的值在循环之后的块中由phi合并
+    fn build_while_expression(&mut self, while_loop: &WhileLoop) -> Result<Expression<'ctx>> {
+        if let Some(else_block) = &while_loop.else_block {
+            return Err(CompileError::LoopExpressionWithoutValue.to_leerror(else_block.pos.clone()));
+        }
+        let body_block = self.context.llvm_context.insert_basic_block_after(self.builder.llvm_builder.get_insert_block().unwrap(), "while.body");
+        let after_block = self.context.llvm_context.insert_basic_block_after(body_block, "while.end");
+        self.context.compiler_context.push_block_table();
+        //条件不是常量true时循环可能不经过break结束，此时没有值
+        let bool_cond = self.build_condition_expression(while_loop.condition.as_ref())?;
+        if bool_cond.get_llvm_value().get_zero_extended_constant() != Some(1) {
+            return Err(CompileError::LoopExpressionWithoutValue.to_leerror(while_loop.condition.pos()));
         }
+        self.builder.llvm_builder.build_unconditional_branch(body_block);
         self.builder.llvm_builder.position_at_end(body_block);
-        self.build_code_block(&while_loop.code_block)?;
-        self.builder.llvm_builder.build_unconditional_branch(cond_block);
+        self.context.compiler_context.loop_blocks.push(LoopBlocks { break_block: after_block, continue_block: body_block, break_values: Some(vec![]) });
+        let is_terminated = self.build_code_block(&while_loop.code_block)?;
+        let loop_blocks = self.context.compiler_context.loop_blocks.pop().unwrap();
+        if !is_terminated {
+            self.builder.llvm_builder.build_unconditional_branch(body_block);
+        }
         self.builder.llvm_builder.position_at_end(after_block);
         self.context.compiler_context.pop_block_table();
+        let break_values = loop_blocks.break_values.unwrap();
+        if break_values.is_empty() {
+            return Err(CompileError::LoopExpressionWithoutValue.to_leerror(while_loop.pos.clone()));
+        }
+        Ok(Expression::Right(le_error!(self.builder.build_phi(&break_values),while_loop.pos.clone())?))
+    }
+
+    /// 生成循环正常结束（条件不满足）时执行的el块
+    fn build_loop_else_block(&mut self, else_block: Option<BasicBlock<'ctx>>, else_code_block: Option<&CodeBlock>, after_block: BasicBlock<'ctx>) -> Result<()> {
+        if let (Some(else_block), Some(else_code_block)) = (else_block, else_code_block) {
+            self.builder.llvm_builder.position_at_end(else_block);
+            let is_terminated = self.build_code_block(else_code_block)?;
+            if !is_terminated {
+                self.builder.llvm_builder.build_unconditional_branch(after_block);
+            }
+        }
+        Ok(())
+    }
+
+    fn build_break(&mut self, value: Option<&Expr>, position: Position) -> Result<()> {
+        let (break_block, is_loop_expression) = self.context.compiler_context.loop_blocks.last()
+            .map(|loop_blocks| (loop_blocks.break_block, loop_blocks.break_values.is_some()))
+            .ok_or_else(|| CompileError::BreakOutsideLoop.to_leerror(position.clone()))?;
+        match (value, is_loop_expression) {
+            (Some(value), false) => { return Err(CompileError::BreakValueInLoopStatement.to_leerror(value.pos())); }
+            (None, true) => { return Err(CompileError::LoopExpressionWithoutValue.to_leerror(position)); }
+            (Some(value), true) => {
+                let expr = self.build_expression(value)?;
+                let break_value = le_error!(self.builder.read_expression(&self.context, expr),value.pos())?;
+                //求值之后所在的块才是跳转到循环之后的块的前驱
+                let current_block = self.builder.llvm_builder.get_insert_block().unwrap();
+                let break_values = self.context.compiler_context.loop_blocks.last_mut().unwrap().break_values.as_mut().unwrap();
+                if let Some((first, _)) = break_values.first() {
+                    if first.get_le_type() != break_value.get_le_type() {
+                        return Err(CompileError::TypeMismatched { expect: first.get_le_type().to_string(), found: break_value.get_le_type().to_string() }.to_leerror(value.pos()));
+                    }
+                }
+                break_values.push((break_value, current_block));
+            }
+            (None, false) => {}
+        }
+        self.builder.llvm_builder.build_unconditional_branch(break_block);
+        Ok(())
+    }
+
+    fn build_continue(&mut self, position: Position) -> Result<()> {
+        let continue_block = self.context.compiler_context.loop_blocks.last().map(|loop_blocks| loop_blocks.continue_block)
+            .ok_or_else(|| CompileError::ContinueOutsideLoop.to_leerror(position))?;
+        self.builder.llvm_builder.build_unconditional_branch(continue_block);
         Ok(())
     }
 
     fn build_if_statement(&mut self, statement: &IfStatement) -> Result<()> {
-        let then_block = self.context.llvm_context.insert_basic_block_after(self.builder.llvm_builder.get_insert_block().unwrap(), "");
-        let else_block = self.context.llvm_context.insert_basic_block_after(then_block, "");
-        let merge_block = self.context.llvm_context.insert_basic_block_after(else_block, "");
-        let cond = self.build_expression(statement.cond.as_ref())?;
-        let cond_value = le_error!(self.builder.read_expression(&self.context, cond),statement.cond.pos())?;
-        if let LEBasicValueEnum::Bool(bool_cond) = cond_value {
-            self.builder.llvm_builder.build_conditional_branch(bool_cond.get_llvm_value(), then_block, else_block);
-        } else {
-            return Err(CompileError::TypeMismatched {
-                expect: "bool".into(),
-                found: LEBasicValue::get_le_type(&cond_value).to_string(),
-            }.to_leerror(statement.cond.pos()));
+        let bool_cond = self.build_condition_expression(statement.cond.as_ref())?;
+        //条件在编译期已经折叠为常量时，只生成会执行的分支
+        if let Some(cond_value) = bool_cond.get_llvm_value().get_zero_extended_constant() {
+            return self.build_constant_if_statement(statement, cond_value != 0);
         }
+        let then_block = self.context.llvm_context.insert_basic_block_after(self.builder.llvm_builder.get_insert_block().unwrap(), "if.then");
+        let else_block = self.context.llvm_context.insert_basic_block_after(then_block, "if.else");
+        let merge_block = self.context.llvm_context.insert_basic_block_after(else_block, "if.merge");
+        self.builder.llvm_builder.build_conditional_branch(bool_cond.get_llvm_value(), then_block, else_block);
         self.builder.llvm_builder.position_at_end(then_block);
         self.context.compiler_context.push_block_table();
         let is_then_return_block = self.build_code_block(&statement.then_block)?;
@@ -462,9 +2019,86 @@ impl<'ctx> CodeGenerator<'ctx> {
         Ok(())
     }
 
-    fn build_function_prototype(&mut self, module: &Module<'ctx>, prototype: &FunctionPrototype) -> Result<LEFunctionValue<'ctx>> {
-        let mut param_llvm_metadata_types = vec![];
-        let mut param_types = vec![];
+    fn build_constant_if_statement(&mut self, statement: &IfStatement, cond_value: bool) -> Result<()> {
+        let taken_block = if cond_value { Some(&statement.then_block) } else { statement.else_block.as_ref() };
+        if let Some(taken_block) = taken_block {
+            let body_block = self.context.llvm_context.insert_basic_block_after(self.builder.llvm_builder.get_insert_block().unwrap(), "if.then");
+            let merge_block = self.context.llvm_context.insert_basic_block_after(body_block, "if.merge");
+            self.builder.llvm_builder.build_unconditional_branch(body_block);
+            self.builder.llvm_builder.position_at_end(body_block);
+            self.context.compiler_context.push_block_table();
+            if !self.build_code_block(taken_block)? {
+                self.builder.llvm_builder.build_unconditional_branch(merge_block);
+            }
+            self.context.compiler_context.pop_block_table();
+            self.builder.llvm_builder.position_at_end(merge_block);
+        }
+        Ok(())
+    }
+
+    /// 生成if表达式，两个分支的值在合并块中通过phi节点汇合
+    fn build_if_expression(&mut self, expression: &IfExpression) -> Result<Expression<'ctx>> {
+        let bool_cond = self.build_condition_expression(expression.cond.as_ref())?;
+        let then_block = self.context.llvm_context.insert_basic_block_after(self.builder.llvm_builder.get_insert_block().unwrap(), "if.then");
+        let else_block = self.context.llvm_context.insert_basic_block_after(then_block, "if.else");
+        let merge_block = self.context.llvm_context.insert_basic_block_after(else_block, "if.merge");
+        self.builder.llvm_builder.build_conditional_branch(bool_cond.get_llvm_value(), then_block, else_block);
+        self.builder.llvm_builder.position_at_end(then_block);
+        let then_value = self.build_value_block(&expression.then_block, &expression.then_value)?;
+        let then_end_block = self.builder.llvm_builder.get_insert_block().unwrap();
+        self.builder.llvm_builder.build_unconditional_branch(merge_block);
+        self.builder.llvm_builder.position_at_end(else_block);
+        let else_value = self.build_value_block(&expression.else_block, &expression.else_value)?;
+        let else_end_block = self.builder.llvm_builder.get_insert_block().unwrap();
+        self.builder.llvm_builder.build_unconditional_branch(merge_block);
+        //两个分支的值类型必须一致
+        self.builder.llvm_builder.position_at_end(merge_block);
+        let value = le_error!(self.builder.build_phi(&[(then_value, then_end_block), (else_value, else_end_block)]),expression.else_value.pos())?;
+        Ok(Expression::Right(value))
+    }
+
+    /// 生成三元表达式，两个分支都没有副作用时求值两边后用select选择，否则按if表达式生成分支和phi
+    fn build_ternary_expression(&mut self, expression: &TernaryExpression) -> Result<Expression<'ctx>> {
+        if is_side_effect_free(&expression.then_value) && is_side_effect_free(&expression.else_value) {
+            let bool_cond = self.build_condition_expression(expression.cond.as_ref())?;
+            let then_value = self.build_expression(expression.then_value.as_ref())?;
+            let else_value = self.build_expression(expression.else_value.as_ref())?;
+            let value = le_error!(self.builder.build_select(&self.context, bool_cond, then_value, else_value),expression.pos())?;
+            return Ok(Expression::Right(value));
+        }
+        self.build_if_expression(&IfExpression {
+            cond: expression.cond.clone(),
+            then_block: CodeBlock { statements: vec![], pos: expression.then_value.pos() },
+            then_value: expression.then_value.clone(),
+            else_block: CodeBlock { statements: vec![], pos: expression.else_value.pos() },
+            else_value: expression.else_value.clone(),
+            pos: expression.pos(),
+        })
+    }
+
+    /// 生成块表达式，块中的语句在新的作用域中生成，返回块的值
+    fn build_block_expression(&mut self, expression: &BlockExpression) -> Result<Expression<'ctx>> {
+        let value = self.build_value_block(&expression.code_block, &expression.value)?;
+        Ok(Expression::Right(value))
+    }
+
+    /// 生成if表达式的分支或块表达式，返回块的值
+    fn build_value_block(&mut self, code_block: &CodeBlock, value: &Expr) -> Result<LEBasicValueEnum<'ctx>> {
+        self.context.compiler_context.push_block_table();
+        if self.build_code_block(code_block)? {
+            //分支中已经返回或跳出循环，值所在的块不可达
+            let unreachable_block = self.context.llvm_context.insert_basic_block_after(self.builder.llvm_builder.get_insert_block().unwrap(), "unreachable");
+            self.builder.llvm_builder.position_at_end(unreachable_block);
+        }
+        let expression = self.build_expression(value)?;
+        let result = le_error!(self.builder.read_expression(&self.context, expression),value.pos());
+        self.context.compiler_context.pop_block_table();
+        result
+    }
+
+    fn build_function_prototype(&mut self, module: &Module<'ctx>, prototype: &FunctionPrototype) -> Result<LEFunctionValue<'ctx>> {
+        let mut param_llvm_metadata_types = vec![];
+        let mut param_types = vec![];
 
         for param_type in prototype.param_types.iter() {
             let ty = self.context.get_generic_type(param_type).map_err(|e| e.to_leerror(param_type.pos()))?;
@@ -482,6 +2116,11 @@ impl<'ctx> CodeGenerator<'ctx> {
                 let ty = self.context.get_generic_type(type_declarator).map_err(|e| e.to_leerror(type_declarator.pos()))?;
                 return_type = Some(ty.clone());
                 match ty {
+                    //较大的结构体通过隐藏的第一个指针参数返回
+                    LEBasicTypeEnum::Struct(ref i) if needs_struct_return(i) => {
+                        param_llvm_metadata_types.insert(0, BasicMetadataTypeEnum::from(i.get_pointer_type().get_llvm_type()));
+                        self.context.llvm_context.void_type().fn_type(&param_llvm_metadata_types, false)
+                    }
                     LEBasicTypeEnum::Integer(i) => { i.get_llvm_type().fn_type(&param_llvm_metadata_types, false) }
                     LEBasicTypeEnum::Bool(i) => { i.get_llvm_type().fn_type(&param_llvm_metadata_types, false) }
                     LEBasicTypeEnum::Float(i) => { i.get_llvm_type().fn_type(&param_llvm_metadata_types, false) }
@@ -489,15 +2128,50 @@ impl<'ctx> CodeGenerator<'ctx> {
                     LEBasicTypeEnum::Array(i) => { i.get_llvm_type().fn_type(&param_llvm_metadata_types, false) }
                     LEBasicTypeEnum::Struct(i) => { i.get_llvm_type().fn_type(&param_llvm_metadata_types, false) }
                     LEBasicTypeEnum::Vector(i) => { i.get_llvm_type().fn_type(&param_llvm_metadata_types, false) }
+                    LEBasicTypeEnum::FunctionPointer(i) => { i.get_llvm_type().fn_type(&param_llvm_metadata_types, false) }
                 }
             }
         };
+        let symbol_name = self.function_symbol_name(&prototype.identifier.name, &param_types);
+        //参数类型也相同的同名函数不是合法的重载
+        if let Ok(function) = self.context.compiler_context.get_function(&symbol_name) {
+            if function.ty.param_types() == param_types.as_slice() {
+                return Err(CompileError::DuplicateFunction { name: prototype.identifier.name.clone() }.to_leerror(prototype.identifier.pos()));
+            }
+        }
         //构造一个LLVM函数
-        let external_function_value = module.add_function(&prototype.identifier.name, external_function, Some(Linkage::External));
-        let function_type = LEFunctionType::new(external_function, return_type, param_types);
+        let external_function_value = module.add_function(&symbol_name, external_function, Some(Linkage::External));
+        //处理函数属性
+        for attribute in prototype.attributes.iter() {
+            match attribute.name.name.as_str() {
+                "ccc" => { external_function_value.set_call_conventions(CALL_CONV_C) }
+                "fastcc" => { external_function_value.set_call_conventions(CALL_CONV_FAST) }
+                "coldcc" => { external_function_value.set_call_conventions(CALL_CONV_COLD) }
+                "cfg" if attribute.params.len() == 1 => {}
+                "const" => {}
+                "inline" => {
+                    let inline_hint = self.context.llvm_context.create_enum_attribute(Attribute::get_named_enum_kind_id("inlinehint"), 0);
+                    external_function_value.add_attribute(AttributeLoc::Function, inline_hint);
+                }
+                "noreturn" => {
+                    let noreturn = self.context.llvm_context.create_enum_attribute(Attribute::get_named_enum_kind_id("noreturn"), 0);
+                    external_function_value.add_attribute(AttributeLoc::Function, noreturn);
+                    self.context.compiler_context.mark_noreturn(external_function_value);
+                }
+                _ => {
+                    return Err(CompileError::UnknownAttribute { attribute: attribute.name.name.clone() }.to_leerror(attribute.pos()));
+                }
+            }
+        }
+        let struct_return = matches!(&return_type, Some(LEBasicTypeEnum::Struct(s)) if needs_struct_return(s));
+        if struct_return {
+            let sret_attribute = self.context.struct_return_attribute(return_type.clone().unwrap());
+            external_function_value.add_attribute(AttributeLoc::Param(0), sret_attribute);
+        }
+        let function_type = LEFunctionType::new(external_function, return_type, param_types, struct_return);
         let le_function = LEFunctionValue { ty: function_type, llvm_value: external_function_value };
         le_error!(self.context.insert_global_function(
-            prototype.identifier.name.clone(),
+            symbol_name,
             le_function.clone(),
             prototype.identifier.pos()),prototype.identifier.pos())?;
         Ok(le_function)
@@ -524,13 +2198,58 @@ impl<'ctx> CodeGenerator<'ctx> {
         }
     }
 
-    fn build_function(&mut self, module: &Module<'ctx>, function_node: &FunctionDefinition) -> Result<LEFunctionValue<'ctx>> {
-        let function_value = self.build_function_prototype(module, &function_node.prototype)?;
-        let entry = self.context.llvm_context.append_basic_block(function_value.llvm_value, "");
-        let return_block = self.context.llvm_context.append_basic_block(function_value.llvm_value, "");
+    /// `@inline`函数可能在多个链接在一起的模块中都有定义，放入选择方式为any的同名comdat中，由链接器只保留一份
+    /// Mach-O不支持comdat，以Darwin为目标时只依靠linkonce_odr链接合并
+    fn place_inline_function_in_comdat(&self, module: &Module<'ctx>, prototype: &FunctionPrototype, function: FunctionValue<'ctx>) {
+        if !prototype.attributes.iter().any(|attribute| attribute.name.name == "inline") {
+            return;
+        }
+        function.set_linkage(Linkage::LinkOnceODR);
+        let triple = module.get_triple();
+        let triple = triple.as_str().to_string_lossy();
+        if ["apple", "darwin", "macos", "ios"].iter().any(|os| triple.contains(os)) {
+            return;
+        }
+        let name = function.get_name().to_string_lossy().to_string();
+        let comdat = module.get_or_insert_comdat(&name);
+        comdat.set_selection_kind(ComdatSelectionKind::Any);
+        function.as_global_value().set_comdat(comdat);
+    }
+
+    /// 根据构建配置为函数定义添加属性
+    fn apply_build_profile(&self, function: FunctionValue<'ctx>) {
+        let llvm_context = self.context.llvm_context;
+        match self.build_profile {
+            BuildProfile::Hardened => {
+                let stack_protector = llvm_context.create_enum_attribute(Attribute::get_named_enum_kind_id("sspstrong"), 0);
+                function.add_attribute(AttributeLoc::Function, stack_protector);
+                function.add_attribute(AttributeLoc::Function, llvm_context.create_string_attribute("frame-pointer", "all"));
+            }
+            BuildProfile::Debug => {
+                //optnone要求函数同时带有noinline
+                for name in ["optnone", "noinline"] {
+                    function.add_attribute(AttributeLoc::Function, llvm_context.create_enum_attribute(Attribute::get_named_enum_kind_id(name), 0));
+                }
+                function.add_attribute(AttributeLoc::Function, llvm_context.create_string_attribute("frame-pointer", "all"));
+            }
+            BuildProfile::Default | BuildProfile::Release => {}
+        }
+    }
+
+    /// 生成函数体，函数原型已在第一遍中生成
+    fn build_function(&mut self, function_node: &FunctionDefinition, function_value: LEFunctionValue<'ctx>) -> Result<LEFunctionValue<'ctx>> {
+        self.apply_build_profile(function_value.llvm_value);
+        let entry = self.context.llvm_context.append_basic_block(function_value.llvm_value, "entry");
+        let return_block = self.context.llvm_context.append_basic_block(function_value.llvm_value, "return");
         let return_type = function_value.ty.return_type();
         //对返回值为空类型或其他类型做特殊处理
-        if let Some(none_void_type) = return_type {
+        if function_value.ty.struct_return() {
+            //通过sret参数返回时，直接将返回值写入调用者提供的内存
+            let sret_pointer = function_value.llvm_value.get_first_param().unwrap().into_pointer_value();
+            let return_variable = LEPointerValue::from_type_and_llvm_value(return_type.unwrap(), BasicValueEnum::PointerValue(sret_pointer)).unwrap();
+            self.context.compiler_context.set_current_context(function_value.llvm_value, Some(return_variable), return_block);
+            self.build_return_block(return_block, None)?;
+        } else if let Some(none_void_type) = return_type {
             self.builder.llvm_builder.position_at_end(entry);
             let return_variable = self.builder.build_alloca(&self.context, none_void_type);
             self.context.compiler_context.set_current_context(function_value.llvm_value, Some(return_variable.clone()), return_block);
@@ -545,7 +2264,7 @@ impl<'ctx> CodeGenerator<'ctx> {
         self.context.compiler_context.push_block_table();
         let function = &function_value;
         let names = &function_node.param_names;
-        let param_value_iter = function.llvm_value.get_param_iter();
+        let param_value_iter = function.llvm_value.get_param_iter().skip(function.ty.struct_return() as usize);
         let param_type_iter = function.ty.param_types();
         //生成保存参数的变量
         for (index, ((param, name), param_type)) in param_value_iter.zip(names).zip(param_type_iter).enumerate() {
@@ -576,38 +2295,214 @@ impl<'ctx> CodeGenerator<'ctx> {
             //生成所有的函数原型
             self.build_function_prototype(module, function_prototype)?;
         }
+        //先生成所有函数定义的原型，函数体中可以调用定义在其后的函数
+        let mut function_values = Vec::with_capacity(ast.function_definitions.len());
         for function_node in ast.function_definitions.iter() {
-            let name = function_node.prototype.identifier.clone();
+            function_values.push(self.build_function_prototype(module, &function_node.prototype)?);
+        }
+        for function_prototype in ast.forward_declarations.iter() {
+            self.build_forward_declaration(module, function_prototype)?;
+        }
+        self.declare_runtime_functions(module)?;
+        let mut defined_functions = Vec::with_capacity(function_values.len());
+        for (function_node, function_value) in ast.function_definitions.iter().zip(function_values) {
             //生成所有的函数实现
-            self.build_function(module, function_node)?;
+            let function_value = self.build_function(function_node, function_value)?;
+            self.place_inline_function_in_comdat(module, &function_node.prototype, function_value.llvm_value);
+            defined_functions.push(function_value.llvm_value);
         }
+        self.infer_memory_attributes(module, &defined_functions);
+        self.remove_unused_runtime_functions();
         Ok(())
     }
 
+    /// 只访问自身栈上变量的函数标记为`readnone`，还读取其他内存但不写入的函数标记为`readonly`
+    fn infer_memory_attributes(&self, module: &Module<'ctx>, functions: &[FunctionValue<'ctx>]) {
+        let readnone = Attribute::get_named_enum_kind_id("readnone");
+        let readonly = Attribute::get_named_enum_kind_id("readonly");
+        //函数的属性依赖被调用函数的属性，重复推断直到不再变化
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for function in functions.iter() {
+                if function.get_enum_attribute(AttributeLoc::Function, readnone).is_some() {
+                    continue;
+                }
+                let has_readonly = function.get_enum_attribute(AttributeLoc::Function, readonly).is_some();
+                match memory_access(*function, module) {
+                    MemoryAccess::None => {
+                        if has_readonly {
+                            function.remove_enum_attribute(AttributeLoc::Function, readonly);
+                        }
+                        function.add_attribute(AttributeLoc::Function, self.context.llvm_context.create_enum_attribute(readnone, 0));
+                        changed = true;
+                    }
+                    MemoryAccess::Read if !has_readonly => {
+                        function.add_attribute(AttributeLoc::Function, self.context.llvm_context.create_enum_attribute(readonly, 0));
+                        changed = true;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// 声明内置函数和安全检查用到的运行时函数，模块中已有同名函数时直接使用
+    fn declare_runtime_functions(&mut self, module: &Module<'ctx>) -> Result<()> {
+        let char_pointer_type = self.context.i8_type().get_pointer_type().to_le_type_enum();
+        let runtime_functions = [
+            ("strlen", Some(self.context.u64_type().to_le_type_enum()), vec![char_pointer_type.clone()]),
+            ("strcmp", Some(self.context.i32_type().to_le_type_enum()), vec![char_pointer_type.clone(), char_pointer_type]),
+            ("llvm.trap", None, vec![]),
+        ];
+        for (name, return_type, param_types) in runtime_functions {
+            //独立环境没有C运行时，只保留不依赖运行时的LLVM内置函数
+            if self.freestanding && !name.starts_with("llvm.") {
+                continue;
+            }
+            let param_llvm_metadata_types = param_types.iter().map(|ty| BasicMetadataTypeEnum::from(ty.get_llvm_basic_type())).collect::<Vec<_>>();
+            let llvm_type = match &return_type {
+                Some(ty) => { ty.get_llvm_basic_type().fn_type(&param_llvm_metadata_types, false) }
+                None => { self.context.llvm_context.void_type().fn_type(&param_llvm_metadata_types, false) }
+            };
+            let llvm_value = match module.get_function(name) {
+                //用户声明的同名函数签名必须与运行时函数一致，否则调用时的值类型与实际不符
+                Some(existing) if existing.get_type() != llvm_type => {
+                    let position = self.context.compiler_context.symbols.get_defined_position(name).unwrap_or(Position { range: 0..0 });
+                    return Err(CompileError::TypeMismatched {
+                        expect: llvm_type.print_to_string().to_string(),
+                        found: existing.get_type().print_to_string().to_string(),
+                    }.to_leerror(position));
+                }
+                Some(existing) => { existing }
+                None => { module.add_function(name, llvm_type, Some(Linkage::External)) }
+            };
+            let function = LEFunctionValue { ty: LEFunctionType::new(llvm_type, return_type, param_types, false), llvm_value };
+            self.context.compiler_context.runtime_functions.insert(name, function);
+        }
+        //整数溢出处理内置函数用到的intrinsic，每种整数宽度各有一个重载
+        for bit_width in [8, 16, 32, 64] {
+            let int_type = self.context.llvm_context.custom_width_int_type(bit_width);
+            let overflow_result_type = self.context.llvm_context.struct_type(&[int_type.into(), self.context.llvm_context.bool_type().into()], false);
+            for sign in ["s", "u"] {
+                let intrinsics = [
+                    (format!("llvm.{}add.with.overflow.i{}", sign, bit_width), overflow_result_type.fn_type(&[int_type.into(), int_type.into()], false)),
+                    (format!("llvm.{}add.sat.i{}", sign, bit_width), int_type.fn_type(&[int_type.into(), int_type.into()], false)),
+                ];
+                for (name, llvm_type) in intrinsics {
+                    let llvm_value = module.get_function(&name)
+                        .unwrap_or_else(|| module.add_function(&name, llvm_type, Some(Linkage::External)));
+                    self.context.compiler_context.intrinsic_functions.insert(name, llvm_value);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 删除没有被调用且不是用户声明的运行时函数，避免在每个模块中都留下无用的声明
+    fn remove_unused_runtime_functions(&mut self) {
+        let runtime_functions = std::mem::take(&mut self.context.compiler_context.runtime_functions);
+        for (name, function) in runtime_functions {
+            let is_user_function = self.context.compiler_context.get_function(name).is_ok();
+            if !is_user_function && function.llvm_value.as_global_value().as_pointer_value().get_first_use().is_none() {
+                unsafe { function.llvm_value.delete(); }
+            }
+        }
+        for function in std::mem::take(&mut self.context.compiler_context.intrinsic_functions).into_values() {
+            if function.as_global_value().as_pointer_value().get_first_use().is_none() {
+                unsafe { function.delete(); }
+            }
+        }
+    }
+
+    /// 新生成的函数在符号表和LLVM模块中的名字，重载的函数都使用重整后的名字
+    /// 增量添加的函数与已生成的同名函数参数类型不同时也是重载，之前生成的函数保留原名
+    fn function_symbol_name(&mut self, name: &str, param_types: &[LEBasicTypeEnum<'ctx>]) -> String {
+        if let Ok(function) = self.context.compiler_context.get_function(name) {
+            if function.ty.param_types() != param_types {
+                self.overloaded_functions.insert(name.to_string());
+            }
+        }
+        if self.overloaded_functions.contains(name) {
+            mangle(name, param_types)
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// 同名的函数定义都是重载，与定义的顺序无关，全部使用重整后的符号名
+    fn collect_overloaded_functions(&mut self, ast: &Ast) {
+        let mut defined = HashSet::new();
+        for function in ast.function_definitions.iter() {
+            let name = &function.prototype.identifier.name;
+            if !defined.insert(name.as_str()) {
+                self.overloaded_functions.insert(name.clone());
+            }
+        }
+    }
+
+    /// 前置声明与已有的同名同参数函数定义一致时直接使用该定义，没有对应定义时作为外部函数声明
+    fn build_forward_declaration(&mut self, module: &Module<'ctx>, prototype: &FunctionPrototype) -> Result<()> {
+        let mut param_types = vec![];
+        for param_type in prototype.param_types.iter() {
+            param_types.push(le_error!(self.context.get_generic_type(param_type), param_type.pos())?);
+        }
+        let return_type = match &prototype.return_type {
+            Some(type_declarator) => { Some(le_error!(self.context.get_generic_type(type_declarator), type_declarator.pos())?) }
+            None => { None }
+        };
+        match self.context.compiler_context.get_function(&self.llvm_symbol_name(&prototype.identifier.name, &param_types)) {
+            Ok(function) if function.ty.param_types() == param_types.as_slice() => {
+                if function.ty.return_type() != return_type {
+                    let describe = |ty: Option<LEBasicTypeEnum>| ty.map_or("void".to_string(), |ty| ty.to_string());
+                    return Err(CompileError::ReturnTypeMismatch {
+                        expect: describe(function.ty.return_type()),
+                        found: describe(return_type),
+                    }.to_leerror(prototype.pos()));
+                }
+                Ok(())
+            }
+            _ => {
+                self.build_function_prototype(module, prototype)?;
+                Ok(())
+            }
+        }
+    }
+
+    /// 在常量上下文中生成表达式，其中的`@const`函数调用必须在编译期求值
+    fn build_const_expression(&mut self, value: &Expr) -> Result<Expression<'ctx>> {
+        let outer = std::mem::replace(&mut self.const_context, true);
+        let result = self.build_expression(value);
+        self.const_context = outer;
+        result
+    }
+
     fn generate_all_global_variables(&mut self, module: &Module<'ctx>, ast: &Ast) -> Result<()> {
         for variable in ast.globals_variables.iter() {
-            let expr_value = self.build_expression(variable.value.as_ref())?;
+            let expr_value = self.build_const_expression(variable.value.as_ref())?;
             let initial = le_error!(self.builder.read_expression(&self.context,expr_value),variable.value.pos())?;
             let initial_type = LEBasicValue::get_le_type(&initial);
             if let Some(exact_type) = &variable.prototype.type_declarator {
                 let target_type = le_error!(self.context.get_generic_type(exact_type),exact_type.pos())?;
-                if target_type == initial_type {
-                    self.create_global_variable(
-                        variable.prototype.identifier.name.clone(),
-                        initial,
-                        module,
-                        variable.prototype.identifier.pos(),
-                    )?;
-                } else {
+                if target_type != initial_type {
                     return Err(CompileError::TypeMismatched { expect: target_type.to_string(), found: initial_type.to_string() }.to_leerror(variable.pos()));
                 }
+            }
+            //`@const`的全局变量只读，相同初始值的常量共享同一个全局变量
+            let mut is_constant = false;
+            for attribute in variable.attributes.iter() {
+                match (attribute.name.name.as_str(), attribute.params.as_slice()) {
+                    ("const", []) => { is_constant = true; }
+                    _ => {
+                        return Err(CompileError::UnknownAttribute { attribute: attribute.name.name.clone() }.to_leerror(attribute.pos()));
+                    }
+                }
+            }
+            let name = variable.prototype.identifier.name.clone();
+            if is_constant {
+                self.create_constant_global(name, initial, module, variable.prototype.identifier.pos())?;
             } else {
-                self.create_global_variable(
-                    variable.prototype.identifier.name.clone(),
-                    initial,
-                    module,
-                    variable.prototype.identifier.pos(),
-                )?;
+                self.create_global_variable(name, initial, module, variable.prototype.identifier.pos())?;
             }
         }
         Ok(())
@@ -619,15 +2514,98 @@ impl<'ctx> CodeGenerator<'ctx> {
         Ok(pointer)
     }
 
+    /// 创建以常量初始化的只读全局变量，类型和初始值都相同的常量共享同一个`unnamed_addr`的全局变量
+    pub fn create_constant_global(&mut self, name: String, initial_value: LEBasicValueEnum<'ctx>, module: &Module<'ctx>, position: Position) -> Result<LEPointerValue<'ctx>> {
+        let value_type = LEBasicValue::get_le_type(&initial_value);
+        let llvm_value = initial_value.to_llvm_basic_value_enum();
+        let key = format!("{} {}", value_type, llvm_value.print_to_string().to_string());
+        let pointer = match self.context.compiler_context.constant_table.get(&key) {
+            Some(pointer) => { pointer.clone() }
+            None => {
+                let global = module.add_global(value_type.get_llvm_basic_type(), None, "");
+                global.set_initializer(&llvm_value);
+                global.set_constant(true);
+                global.set_unnamed_addr(true);
+                global.set_linkage(Linkage::Private);
+                let pointer = LEPointerValue { ty: value_type.get_pointer_type(), llvm_value: global.as_pointer_value() };
+                self.context.compiler_context.constant_table.insert(key, pointer.clone());
+                pointer
+            }
+        };
+        le_error!(self.context.insert_global_variable(name, pointer.clone(), position.clone()),position)?;
+        Ok(pointer)
+    }
+
+    /// 结构体成员的类型为`i3`、`u5`这样的非标准位宽整数时为位域，返回对应位宽的整数类型
+    fn bit_field_type(&self, ty: &TypeDeclarator) -> Option<LEIntegerType<'ctx>> {
+        if let TypeDeclarator::TypeIdentifier(identifier) = ty {
+            let signed = match identifier.name.chars().next()? {
+                'i' => { true }
+                'u' => { false }
+                _ => { return None; }
+            };
+            let bit_width = identifier.name[1..].parse::<u32>().ok()?;
+            if (1..64).contains(&bit_width) && !matches!(bit_width, 8 | 16 | 32) {
+                let llvm_type = self.context.llvm_context.custom_width_int_type(bit_width);
+                return Some(LEIntegerType::from_llvm_type(llvm_type, signed));
+            }
+        }
+        None
+    }
+
     fn generate_all_global_structures(&mut self, module: &Module, ast: &Ast) -> Result<()> {
         for structure in ast.globals_structures.iter() {
+            if structure.members.len() > self.max_struct_fields {
+                return Err(CompileError::StructTooComplex {
+                    name: structure.identifier.name.clone(),
+                    kind: "fields".into(),
+                    limit: self.max_struct_fields,
+                }.to_leerror(structure.identifier.pos()));
+            }
             let mut names = vec![];
             let mut types = vec![];
+            let mut has_bit_fields = false;
             for (name, ty) in structure.members.iter() {
+                if names.contains(&name.as_str()) {
+                    return Err(CompileError::DuplicateStructMember {
+                        struct_name: structure.identifier.name.clone(),
+                        member: name.clone(),
+                    }.to_leerror(ty.pos()));
+                }
                 names.push(name.as_str());
-                types.push(le_error!(self.context.get_generic_type(ty),ty.pos())?);
+                types.push(match self.bit_field_type(ty) {
+                    Some(bit_field_type) => {
+                        has_bit_fields = true;
+                        bit_field_type.to_le_type_enum()
+                    }
+                    None => { le_error!(self.context.get_generic_type(ty),ty.pos())? }
+                });
+            }
+            //`@repr(C)`的结构体按声明顺序和C的对齐规则排列成员
+            let mut repr_c = false;
+            for attribute in structure.attributes.iter() {
+                match (attribute.name.name.as_str(), attribute.params.as_slice()) {
+                    ("repr", [repr]) if repr.name == "C" => { repr_c = true; }
+                    ("cfg", [_]) => {}
+                    _ => {
+                        return Err(CompileError::UnknownAttribute { attribute: attribute.name.name.clone() }.to_leerror(attribute.pos()));
+                    }
+                }
+            }
+            //默认布局没有填充，对齐要求高的成员排在前面，使其尽量落在对齐的地址上
+            //位域需要与相邻的位域打包，含有位域的结构体保持声明顺序
+            let mut order = (0..names.len()).collect::<Vec<_>>();
+            if !repr_c && !has_bit_fields {
+                order.sort_by_key(|index| std::cmp::Reverse(self.target_data.get_abi_alignment(&types[*index].get_llvm_basic_type())));
+            }
+            let structure_type = LEStructType::from_llvm_type_with_layout(&self.context, &structure.identifier.name, &names, &types, &order, !repr_c);
+            if struct_nesting_depth(&structure_type.to_le_type_enum()) > self.max_struct_nesting {
+                return Err(CompileError::StructTooComplex {
+                    name: structure.identifier.name.clone(),
+                    kind: "levels of nesting".into(),
+                    limit: self.max_struct_nesting,
+                }.to_leerror(structure.identifier.pos()));
             }
-            let structure_type = LEStructType::from_llvm_type(&self.context, &names, &types);
             le_error!(self.context.insert_global_type(
                 structure.identifier.name.clone(),
                 structure_type.to_le_type_enum(),
@@ -638,14 +2616,322 @@ impl<'ctx> CodeGenerator<'ctx> {
     }
 
 
-    pub fn compile(&mut self, module: &Module<'ctx>, ast: &Ast) -> Result<()> {
+    /// 收集带有`@const`属性的函数，并检查其中只有编译期可以求值的操作
+    fn collect_const_functions(&mut self, ast: &Ast) -> Result<()> {
+        for function in ast.function_definitions.iter() {
+            if function.prototype.attributes.iter().any(|attribute| attribute.name.name == "const") {
+                self.const_functions.insert(function.prototype.identifier.name.clone(), function.clone());
+            }
+        }
+        for function in self.const_functions.values() {
+            for statement in function.code_block.statements.iter() {
+                check_const_statement(statement, &self.const_functions)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 顶层项的所有`@cfg(feature)`属性的feature都已启用时才生成该项
+    fn is_cfg_enabled(&self, attributes: &[crate::ast::nodes::Attribute]) -> bool {
+        attributes.iter().all(|attribute| match (attribute.name.name.as_str(), attribute.params.as_slice()) {
+            ("cfg", [feature]) => self.features.contains(&feature.name),
+            _ => true,
+        })
+    }
+
+    /// 去掉条件编译未启用的顶层项
+    fn filter_cfg_items(&self, ast: &Ast) -> Ast {
+        Ast {
+            globals_variables: ast.globals_variables.clone(),
+            globals_structures: ast.globals_structures.iter().filter(|s| self.is_cfg_enabled(&s.attributes)).cloned().collect(),
+            function_definitions: ast.function_definitions.iter().filter(|f| self.is_cfg_enabled(&f.prototype.attributes)).cloned().collect(),
+            extern_functions: ast.extern_functions.iter().filter(|f| self.is_cfg_enabled(&f.attributes)).cloned().collect(),
+            forward_declarations: ast.forward_declarations.iter().filter(|f| self.is_cfg_enabled(&f.attributes)).cloned().collect(),
+        }
+    }
+
+    /// 编译整个语法树到模块中，成功时返回编译过程中产生的警告
+    pub fn compile(&mut self, module: &Module<'ctx>, ast: &Ast) -> Result<Vec<Warning>> {
+        self.with_module(module, |generator| generator.compile_ast(module, ast))
+    }
+
+    fn compile_ast(&mut self, module: &Module<'ctx>, ast: &Ast) -> Result<Vec<Warning>> {
+        let ast = &self.filter_cfg_items(ast);
+        self.collect_overloaded_functions(ast);
+        //全局变量的初始值中可能调用`@const`函数，需要在生成全局变量前收集
+        self.collect_const_functions(ast)?;
         //生成所有全局变量相关的代码
         self.generate_all_global_variables(module, ast)?;
         //生成所有全局结构体类型的代码
         self.generate_all_global_structures(module, ast)?;
         //生成所有全局函数的代码
         self.generate_all_functions(module, ast)?;
-        Ok(())
+        if self.build_profile == BuildProfile::Release {
+            Optimizer::run_module_passes(module, OptimizationLevel::Default);
+        }
+        Ok(std::mem::take(&mut self.warnings))
+    }
+
+    /// 返回源码中名为source_name、参数类型为param_types的函数在LLVM模块中的符号名
+    /// 以原名生成的同名函数参数类型相同时使用原名，否则是重载，使用重整后的符号名
+    pub fn llvm_symbol_name(&self, source_name: &str, param_types: &[LEBasicTypeEnum<'ctx>]) -> String {
+        match self.context.compiler_context.get_function(source_name) {
+            Ok(function) if function.ty.param_types() == param_types => { source_name.to_string() }
+            _ => { mangle(source_name, param_types) }
+        }
+    }
+
+    /// 将LLVM模块中的符号名还原为源码中的函数名和参数类型名，是`llvm_symbol_name`的逆操作
+    /// 没有重整过的符号名从已生成的同名函数取得参数类型，找不到时返回None
+    pub fn source_symbol_name(&self, symbol_name: &str) -> Option<(String, Vec<String>)> {
+        if let Some(signature) = demangle_signature(symbol_name) {
+            return Some(signature);
+        }
+        let function = self.context.compiler_context.get_function(symbol_name).ok()?;
+        Some((symbol_name.to_string(), function.ty.param_types().iter().map(|ty| ty.to_string()).collect()))
+    }
+
+    /// 向已经编译过的模块中增量添加一个函数，函数体可以引用之前编译的全局变量、结构体和函数
+    pub fn add_function(&mut self, module: &Module<'ctx>, def: &FunctionDefinition) -> Result<LEFunctionValue<'ctx>> {
+        self.with_module(module, |generator| generator.add_function_to_module(module, def))
+    }
+
+    fn add_function_to_module(&mut self, module: &Module<'ctx>, def: &FunctionDefinition) -> Result<LEFunctionValue<'ctx>> {
+        let function_value = self.build_function_prototype(module, &def.prototype)?;
+        self.declare_runtime_functions(module)?;
+        let function_value = self.build_function(def, function_value)?;
+        self.place_inline_function_in_comdat(module, &def.prototype, function_value.llvm_value);
+        self.infer_memory_attributes(module, &[function_value.llvm_value]);
+        self.remove_unused_runtime_functions();
+        Ok(function_value)
+    }
+
+    /// 用新的定义替换已经生成的同名函数，原函数的所有调用都改为调用新函数
+    /// 原函数已被调用时新定义的参数类型、返回值类型和调用约定必须与原函数一致
+    pub fn replace_function(&mut self, module: &Module<'ctx>, def: &FunctionDefinition) -> Result<LEFunctionValue<'ctx>> {
+        let name = &def.prototype.identifier.name;
+        let old_function = self.context.compiler_context.get_function(name)
+            .map_err(|_| CompileError::FunctionNotFound { name: name.clone(), suggestion: None }.to_leerror(def.prototype.identifier.pos()))?;
+        let old_pointer = old_function.llvm_value.as_global_value().as_pointer_value();
+        let mut param_types = vec![];
+        for param_type in def.prototype.param_types.iter() {
+            param_types.push(le_error!(self.context.get_generic_type(param_type), param_type.pos())?);
+        }
+        let return_type = match &def.prototype.return_type {
+            Some(type_declarator) => { Some(le_error!(self.context.get_generic_type(type_declarator), type_declarator.pos())?) }
+            None => { None }
+        };
+        if old_pointer.get_first_use().is_some() && (old_function.ty.param_types() != param_types.as_slice() || old_function.ty.return_type() != return_type) {
+            let describe = |params: &[LEBasicTypeEnum], ty: Option<LEBasicTypeEnum>| {
+                format!("({})->{}", params.iter().map(|ty| ty.to_string()).collect::<Vec<_>>().join(", "), ty.map_or("void".to_string(), |ty| ty.to_string()))
+            };
+            return Err(CompileError::TypeMismatched {
+                expect: describe(old_function.ty.param_types(), old_function.ty.return_type()),
+                found: describe(&param_types, return_type),
+            }.to_leerror(def.prototype.pos()));
+        }
+        //先让出原函数的名字和符号，新函数才能使用相同的符号名
+        let old_symbol_name = old_function.llvm_value.get_name().to_str().unwrap().to_string();
+        let old_symbol = self.context.compiler_context.remove_global_function(name).unwrap();
+        old_pointer.set_name("");
+        let block_depth = self.context.compiler_context.block_depth();
+        let is_called = old_pointer.get_first_use().is_some();
+        //已有的调用点使用原函数的调用约定，不能在替换时改变
+        let result = self.add_function(module, def).and_then(|function_value| {
+            if is_called && function_value.llvm_value.get_call_conventions() != old_function.llvm_value.get_call_conventions() {
+                return Err(CompileError::CallConventionChanged { function: name.clone() }.to_leerror(def.prototype.pos()));
+            }
+            Ok(function_value)
+        });
+        match result {
+            Ok(function_value) => {
+                old_pointer.replace_all_uses_with(function_value.llvm_value.as_global_value().as_pointer_value());
+                unsafe { old_function.llvm_value.delete(); }
+                self.refresh_caller_memory_attributes(module, function_value.llvm_value);
+                Ok(function_value)
+            }
+            Err(err) => {
+                //新定义生成失败时删除生成了一半的新函数，恢复原函数的名字和符号
+                if let Ok(new_function) = self.context.compiler_context.get_function(name) {
+                    unsafe { new_function.llvm_value.delete(); }
+                }
+                self.context.compiler_context.truncate_block_tables(block_depth);
+                self.context.compiler_context.restore_global_symbol(name.clone(), old_symbol);
+                old_pointer.set_name(&old_symbol_name);
+                Err(err)
+            }
+        }
+    }
+
+    /// 函数被替换后，直接或间接调用它的函数上按原函数推断的内存属性不再成立，清除后重新推断
+    fn refresh_caller_memory_attributes(&self, module: &Module<'ctx>, function: FunctionValue<'ctx>) {
+        let readnone = Attribute::get_named_enum_kind_id("readnone");
+        let readonly = Attribute::get_named_enum_kind_id("readonly");
+        let mut callers = vec![function];
+        let mut index = 0;
+        while index < callers.len() {
+            let callee = callers[index];
+            for candidate in module.get_functions() {
+                if candidate.count_basic_blocks() > 0 && !callers.contains(&candidate) && calls_function(candidate, module, callee) {
+                    callers.push(candidate);
+                }
+            }
+            index += 1;
+        }
+        for caller in callers.iter() {
+            caller.remove_enum_attribute(AttributeLoc::Function, readnone);
+            caller.remove_enum_attribute(AttributeLoc::Function, readonly);
+        }
+        self.infer_memory_attributes(module, &callers);
+    }
+
+    /// 获取内容为s的全局常量字符串（以0结尾），相同内容的字符串只会生成一次
+    pub fn intern_global_string(&mut self, module: &Module<'ctx>, s: &str) -> LEPointerValue<'ctx> {
+        if let Some(pointer) = self.context.compiler_context.string_table.get(s) {
+            return pointer.clone();
+        }
+        let i8_type = self.context.i8_type();
+        let bytes = s.bytes()
+            .chain(std::iter::once(0))
+            .map(|byte| i8_type.get_llvm_type().const_int(byte as u64, false))
+            .collect::<Vec<_>>();
+        let array_type = i8_type.get_array_type(bytes.len() as u32);
+        let global = module.add_global(array_type.get_llvm_type(), None, ".str");
+        global.set_initializer(&i8_type.get_llvm_type().const_array(&bytes));
+        global.set_constant(true);
+        global.set_unnamed_addr(true);
+        global.set_linkage(Linkage::Private);
+        let pointer = LEPointerValue { ty: array_type.get_pointer_type(), llvm_value: global.as_pointer_value() };
+        self.context.compiler_context.string_table.insert(s.into(), pointer.clone());
+        pointer
+    }
+
+    /// 遍历所有全局函数的名字和对应的LLVM函数，用于在编译后运行自定义的变换
+    pub fn functions(&self) -> impl Iterator<Item=(&str, FunctionValue<'ctx>)> + '_ {
+        self.context.compiler_context.functions()
+    }
+
+    /// 按名字获取全局函数，重载的函数需要使用重整后的名字
+    pub fn get_function(&self, name: &str) -> Option<LEFunctionValue<'ctx>> {
+        self.context.compiler_context.get_function(name).ok()
+    }
+
+    /// 生成C运行时调用的`main(argc, argv)`函数，在其中调用用户的入口函数并以其返回值作为退出码，入口函数无返回值时返回0
+    /// 入口函数没有参数，或者以`(i32, ref ref i8)`接收argc和argv；入口函数本身名为`main`时改名并设为内部链接，让出符号给包装函数
+    pub fn emit_c_main_wrapper(&mut self, module: &Module<'ctx>, entry: &str) -> Result<FunctionValue<'ctx>> {
+        let position = self.context.compiler_context.symbols.get_defined_position(entry).unwrap_or(Position { range: 0..0 });
+        let entry_function = self.context.compiler_context.get_function(entry)
+            .map_err(|_| CompileError::FunctionNotFound { name: entry.into(), suggestion: None }.to_leerror(position.clone()))?;
+        let argc_type = self.context.i32_type().to_le_type_enum();
+        let argv_type = self.context.i8_type().get_pointer_type().get_pointer_type().to_le_type_enum();
+        let pass_arguments = match entry_function.ty.param_types() {
+            [] => { false }
+            [argc, argv] if argc == &argc_type && argv == &argv_type => { true }
+            [_, _] => {
+                return Err(CompileError::TypeMismatched { expect: format!("({}, {})", argc_type, argv_type), found: entry_function.ty.param_types().iter().map(|ty| ty.to_string()).collect::<Vec<_>>().join(", ") }.to_leerror(position));
+            }
+            params => {
+                return Err(CompileError::ArgumentCountMismatch { function: entry.into(), expect: 0, found: params.len() }.to_leerror(position));
+            }
+        };
+        let return_type = entry_function.ty.return_type();
+        if !matches!(return_type, None | Some(LEBasicTypeEnum::Integer(_)) | Some(LEBasicTypeEnum::Bool(_))) {
+            return Err(CompileError::ReturnTypeMismatch { expect: "i32".into(), found: return_type.unwrap().to_string() }.to_leerror(position));
+        }
+        if entry_function.llvm_value.get_name().to_bytes() == b"main" {
+            entry_function.llvm_value.as_global_value().set_name("main.entry");
+            entry_function.llvm_value.set_linkage(Linkage::Internal);
+        }
+        let i32_type = self.context.llvm_context.i32_type();
+        let argv_llvm_type = argv_type.get_llvm_basic_type();
+        let main_function = module.add_function("main", i32_type.fn_type(&[i32_type.into(), argv_llvm_type.into()], false), Some(Linkage::External));
+        let block = self.context.llvm_context.append_basic_block(main_function, "entry");
+        self.builder.llvm_builder.position_at_end(block);
+        let arguments = if pass_arguments {
+            main_function.get_param_iter().map(BasicMetadataValueEnum::from).collect::<Vec<_>>()
+        } else {
+            vec![]
+        };
+        let call_site = self.builder.llvm_builder.build_call(entry_function.llvm_value, &arguments, "");
+        call_site.set_tail_call(true);
+        let exit_code = match (return_type, call_site.try_as_basic_value().left()) {
+            (Some(LEBasicTypeEnum::Bool(_)), Some(value)) => {
+                self.builder.llvm_builder.build_int_z_extend(value.into_int_value(), i32_type, "")
+            }
+            (Some(_), Some(value)) => {
+                self.builder.llvm_builder.build_int_cast(value.into_int_value(), i32_type, "")
+            }
+            _ => i32_type.const_zero(),
+        };
+        self.builder.llvm_builder.build_return(Some(&exit_code));
+        Ok(main_function)
+    }
+
+    /// 获取本模块导出的符号，用于多模块链接
+    pub fn exported_symbols(&self) -> Vec<String> {
+        self.context.compiler_context.exported_symbols()
+    }
+
+    /// 获取本模块引用但未定义的符号，用于多模块链接
+    pub fn undefined_symbols(&self) -> Vec<String> {
+        self.context.compiler_context.undefined_symbols()
+    }
+
+    /// 将src模块链接进dest模块，src模块中定义的符号可以解析dest模块中的外部声明
+    pub fn link_modules(dest: &Module<'ctx>, src: Module<'ctx>) -> Result<()> {
+        dest.link_in_module(src)
+            .map_err(|e| CompileError::LinkError { message: e.to_string() }.to_leerror(Position { range: 0..0 }))
+    }
+
+    /// 将多个源文件解析后合并，编译到同一个模块中，一个文件中的定义对其他所有文件可见
+    /// 成功时同时返回所有警告，警告和错误都附带其所在源文件的下标，位置是在该源文件中的位置
+    pub fn compile_files(context: &'ctx Context, sources: &[&str]) -> std::result::Result<(Module<'ctx>, Vec<(usize, Warning)>), (usize, LEError)> {
+        //每个源文件的位置从前面所有源文件的总长度开始，保证不同源文件中的位置互不重叠
+        let mut offsets = Vec::with_capacity(sources.len());
+        let mut offset = 0;
+        for source in sources {
+            offsets.push(offset);
+            offset += source.len();
+        }
+        let mut ast = Ast::default();
+        for (index, source) in sources.iter().enumerate() {
+            let file_ast = parse_program_at(source, offsets[index]).map_err(|e| locate_error(&offsets, e))?;
+            ast.merge(file_ast);
+        }
+        let module = context.create_module("main");
+        let warnings = CodeGenerator::create(context).compile(&module, &ast).map_err(|e| locate_error(&offsets, e))?;
+        let warnings = warnings.into_iter().map(|mut warning| {
+            let index = locate_position(&offsets, warning.position_mut());
+            (index, warning)
+        }).collect();
+        Ok((module, warnings))
+    }
+
+    /// 将模块以LLVM bitcode格式写入文件
+    pub fn write_bitcode(&self, module: &Module<'ctx>, path: &Path) -> Result<()> {
+        if module.write_bitcode_to_path(path) {
+            Ok(())
+        } else {
+            Err(LEError::IOError {
+                other: Box::new(std::io::Error::new(std::io::ErrorKind::Other, format!("failed to write bitcode to {}", path.display())))
+            })
+        }
+    }
+
+    /// 为triple指定的目标生成目标文件，直接返回目标文件的字节而不写入临时文件
+    pub fn emit_object_to_buffer(&self, module: &Module<'ctx>, triple: &TargetTriple) -> Result<Vec<u8>> {
+        let target_error = |message: String| CompileError::TargetError { message }.to_leerror(Position { range: 0..0 });
+        Target::initialize_all(&InitializationConfig::default());
+        let target = Target::from_triple(triple).map_err(|e| target_error(e.to_string()))?;
+        let target_machine = target.create_target_machine(triple, "generic", "", OptimizationLevel::Default, RelocMode::PIC, CodeModel::Default)
+            .ok_or_else(|| target_error(format!("unsupported target `{}`", triple.as_str().to_string_lossy())))?;
+        let buffer = target_machine.write_to_memory_buffer(module, FileType::Object).map_err(|e| target_error(e.to_string()))?;
+        Ok(buffer.as_slice().to_vec())
+    }
+
+    /// 将模块以LLVM bitcode格式写入内存，返回bitcode的字节
+    pub fn bitcode_to_memory_buffer(&self, module: &Module<'ctx>) -> Vec<u8> {
+        module.write_bitcode_to_memory().as_slice().to_vec()
     }
 
     pub fn create(context: &'ctx Context) -> Self {
@@ -653,8 +2939,1471 @@ impl<'ctx> CodeGenerator<'ctx> {
         Self {
             builder: LEBuilder::new(llvm_builder),
             context: LEContext::new(context),
+            implicit_bool: false,
+            build_profile: BuildProfile::Default,
+            safety_checks: false,
+            freestanding: false,
+            max_expression_depth: DEFAULT_MAX_EXPRESSION_DEPTH,
+            expression_depth: 0,
+            max_struct_fields: DEFAULT_MAX_STRUCT_FIELDS,
+            max_struct_nesting: DEFAULT_MAX_STRUCT_NESTING,
+            target_data: TargetData::create(""),
+            features: HashSet::new(),
+            const_functions: HashMap::new(),
+            const_frames: vec![],
+            const_context: false,
+            warnings: vec![],
+            noreturn_block: None,
+            module: None,
+            overloaded_functions: HashSet::new(),
         }
     }
 }
 
 
+
+#[cfg(test)]
+mod test {
+    use inkwell::attributes::{Attribute, AttributeLoc};
+    use inkwell::context::Context;
+    use inkwell::module::Linkage;
+    use inkwell::OptimizationLevel;
+    use inkwell::targets::{InitializationConfig, Target, TargetData, TargetMachine, TargetTriple};
+
+    use crate::ast::{Ast, parse_program};
+    use crate::code_generator::generator::{BuildProfile, CALL_CONV_C, CodeGenerator};
+    use crate::code_generator::builder::{LEBasicType, LEBasicValue, LEBoolValue, LEIntegerValue, LEStructType, LEType};
+    use crate::code_generator::context::LEContext;
+    use crate::error::{CompileError, LEError, Result, SyntaxError, Warning};
+    use crate::lexer::{LELexer, Operator, Position};
+    use crate::optimizer::Optimizer;
+
+    fn compile_source(source: &str) -> Result<String> {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let ast = Ast::from_lexer(LELexer::new(source).unwrap())?;
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.compile(&module, &ast)?;
+        Ok(module.print_to_string().to_string())
+    }
+
+    fn run_i32_function(source: &str, name: &str) -> i32 {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let ast = Ast::from_lexer(LELexer::new(source).unwrap()).unwrap();
+        CodeGenerator::create(&context).compile(&module, &ast).unwrap();
+        Target::initialize_native(&InitializationConfig::default()).unwrap();
+        let engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+        unsafe { engine.get_function::<unsafe extern "C" fn() -> i32>(name).unwrap().call() }
+    }
+
+    #[test]
+    fn test_return_type_mismatch() {
+        let result = compile_source("le test()->i32{ ret 1.0; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::ReturnTypeMismatch { .. }, .. })));
+        assert!(compile_source("le test()->i32{ ret 1; }").is_ok());
+    }
+
+    #[test]
+    fn test_return_narrowing_needs_cast() {
+        let result = compile_source("le test(a:i64)->i32{ ret a; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::ReturnTypeMismatch { .. }, .. })));
+        assert!(compile_source("le test(a:i64)->i32{ ret a as i32; }").is_ok());
+    }
+
+    #[test]
+    fn test_return_value_in_void_function() {
+        let result = compile_source("le test(){ ret 1; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::ReturnTypeMismatch { .. }, .. })));
+    }
+
+    #[test]
+    fn test_chained_assignment() {
+        let ir = compile_source("le test()->i32{ var a:i32 = 0; var b:i32 = 0; a = b = 5; ret a; }").unwrap();
+        assert_eq!(ir.matches("store i32 5").count(), 2);
+        let ir = compile_source("le test()->i32{ var a:i32 = 0; a = 5; ret a; }").unwrap();
+        assert_eq!(ir.matches("store i32 5").count(), 1);
+    }
+
+    #[test]
+    fn test_module_symbols() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let source = "decl le helper(i32)->i32; le test()->i32{ ret helper(1); }";
+        let ast = Ast::from_lexer(LELexer::new(source).unwrap()).unwrap();
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.compile(&module, &ast).unwrap();
+        assert_eq!(code_generator.exported_symbols(), vec!["test".to_string()]);
+        assert_eq!(code_generator.undefined_symbols(), vec!["helper".to_string()]);
+    }
+
+    #[test]
+    fn test_link_modules() {
+        let context = Context::create();
+        let module_a = context.create_module("a");
+        let module_b = context.create_module("b");
+        let ast_a = Ast::from_lexer(LELexer::new("decl le helper()->i32; le test()->i32{ ret helper(); }").unwrap()).unwrap();
+        let ast_b = Ast::from_lexer(LELexer::new("le helper()->i32{ ret 1; }").unwrap()).unwrap();
+        CodeGenerator::create(&context).compile(&module_a, &ast_a).unwrap();
+        CodeGenerator::create(&context).compile(&module_b, &ast_b).unwrap();
+        CodeGenerator::link_modules(&module_a, module_b).unwrap();
+        assert!(module_a.get_function("helper").unwrap().count_basic_blocks() > 0);
+    }
+
+    #[test]
+    fn test_compile_files() {
+        let context = Context::create();
+        let test = "le test()->i32{ ret helper(21); ret 0; }";
+        let (module, warnings) = CodeGenerator::compile_files(&context, &["le helper(x:i32)->i32{ ret x * 2; }", test]).unwrap();
+        assert!(module.get_function("helper").unwrap().count_basic_blocks() > 0);
+        assert!(module.print_to_string().to_string().contains("call i32 @helper(i32 21)"));
+        //警告附带所在源文件的下标，位置是在该源文件中的位置
+        assert!(matches!(warnings.as_slice(), [(1, Warning::UnreachableStatement { position })] if test[position.range.start..].starts_with("ret 0")));
+        let second = "le helper()->i32{ ret 2; }";
+        match CodeGenerator::compile_files(&context, &["le helper()->i32{ ret 1; }", second]) {
+            Err((1, LEError::CompileError { compile_error: CompileError::DuplicateFunction { .. }, position })) => {
+                assert!(position.range.end <= second.len());
+            }
+            _ => { panic!("expected duplicate function in the second file") }
+        }
+        let result = CodeGenerator::compile_files(&context, &["le a()->i32{ ret 1; }", "le b()->i32{ ret 1 }"]);
+        assert!(matches!(result, Err((1, LEError::SyntaxError { position, .. })) if position.range.end <= 20));
+    }
+
+    #[test]
+    fn test_unreachable_statement() {
+        let source = "le sign(x:i32)->i32{ if(x > 0){ ret 1; } if(x < 0){ ret -1; } if(x == 0){ ret 0; } unreachable; }
+            le test()->i32{ ret sign(-5) + sign(7) * 10; }";
+        let ir = compile_source(source).unwrap();
+        assert!(ir.contains("\n  unreachable\n"));
+        assert_eq!(run_i32_function(source, "test"), 9);
+        assert!(compile_source("le test()->i32{ unreachable; ret 1; }").is_ok());
+    }
+
+    #[test]
+    fn test_compile_warnings() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let ast = parse_program("le test(x:i32)->i32{ ret x; x = x + 1; ; ret 0; }").unwrap();
+        let warnings = CodeGenerator::create(&context).compile(&module, &ast).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(matches!(&warnings[0], Warning::UnreachableStatement { .. }));
+        let module = context.create_module("test");
+        let ast = parse_program("le test(x:i32)->i32{ x = x; ret x; }").unwrap();
+        let warnings = CodeGenerator::create(&context).compile(&module, &ast).unwrap();
+        assert!(matches!(warnings.as_slice(), [Warning::SelfAssignment { .. }]));
+    }
+
+    #[test]
+    fn test_fastcc_attribute() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let source = "@fastcc le foo()->i32{ ret 1; } le test()->i32{ ret foo(); }";
+        let ast = Ast::from_lexer(LELexer::new(source).unwrap()).unwrap();
+        CodeGenerator::create(&context).compile(&module, &ast).unwrap();
+        assert_eq!(module.get_function("foo").unwrap().get_call_conventions(), 8);
+        assert!(module.print_to_string().to_string().contains("call fastcc i32 @foo"));
+        //函数指针调用使用C调用约定，不能对fastcc函数取地址
+        let result = compile_source("@fastcc le foo()->i32{ ret 1; } le test()->i32{ var f = foo; ret f(); }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::FunctionPointerCallConvention { .. }, .. })));
+    }
+
+    #[test]
+    fn test_inline_asm() {
+        let ir = compile_source("le test(){ asm(\"nop\", \"\"); }").unwrap();
+        assert!(ir.contains("call void asm sideeffect \"nop\", \"\"()"));
+    }
+
+    #[test]
+    fn test_volatile_store() {
+        let ir = compile_source("le test(){ var x:i32 = 0; volatile x = 5; x = 6; }").unwrap();
+        assert!(ir.contains("store volatile i32 5"));
+        assert!(!ir.contains("store volatile i32 6"));
+        //成员、下标和解引用得到的左值也可以是volatile的
+        let source = "struct P{ x:i32, y:i32 }
+            le test(a:ref i32)->i32{ var p = P{ x:0, y:0 }; var arr = [0, 0]; volatile p.x = 1; volatile arr[1] = 2; volatile *a = 3; ret volatile p.y; }";
+        let ir = compile_source(source).unwrap();
+        assert!(ir.contains("store volatile i32 1"));
+        assert!(ir.contains("store volatile i32 2"));
+        assert!(ir.contains("store volatile i32 3"));
+        assert!(ir.contains("load volatile i32"));
+        //右值不能作为volatile赋值的目标
+        let result = compile_source("le test()->i32{ volatile 1 = 2; ret 0; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::ExpressionIsNotLeftValueExpression, .. })));
+    }
+
+    #[test]
+    fn test_atomic_add() {
+        let ir = compile_source("le test(p:ref i32){ atomic_add(p, 1); }").unwrap();
+        assert!(ir.contains("atomicrmw add i32*"));
+    }
+
+    #[test]
+    fn test_method_call() {
+        let source = "struct Point{ x:i32 } impl Point{ le get(self)->i32{ ret 1; } } le test()->i32{ var p = Point{ x:1 }; ret p.get(); }";
+        let ir = compile_source(source).unwrap();
+        assert!(ir.contains("define i32 @Point.get(%Point*"));
+        assert!(ir.contains("call i32 @Point.get(%Point*"));
+    }
+
+    #[test]
+    fn test_assignment_in_condition() {
+        let result = compile_source("le test(x:i32){ if(x = 1){} }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::AssignmentInCondition, .. })));
+        let result = compile_source("le test(x:i32){ while(x = 1){} }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::AssignmentInCondition, .. })));
+    }
+
+    #[test]
+    fn test_non_bool_condition() {
+        let result = compile_source("le test(){ if(1.0){} }");
+        if let Err(LEError::CompileError { compile_error: CompileError::TypeMismatched { expect, found }, .. }) = result {
+            assert_eq!(expect, "Bool");
+            assert_eq!(found, "f64");
+        } else {
+            panic!("expect a TypeMismatched error");
+        }
+    }
+
+    #[test]
+    fn test_function_not_found_suggestion() {
+        let result = compile_source("decl le print(i32)->i32; le test(){ prnt(1); }");
+        if let Err(LEError::CompileError { compile_error: CompileError::FunctionNotFound { name, suggestion }, .. }) = result {
+            assert_eq!(name, "prnt");
+            assert_eq!(suggestion, Some("print".to_string()));
+        } else {
+            panic!("expect a FunctionNotFound error");
+        }
+    }
+
+    #[test]
+    fn test_intern_global_string() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let mut code_generator = CodeGenerator::create(&context);
+        let first = code_generator.intern_global_string(&module, "x");
+        let second = code_generator.intern_global_string(&module, "x");
+        assert_eq!(first, second);
+        assert!(module.get_first_global().unwrap().get_next_global().is_none());
+        //字符串字面量可以用在全局变量的初始值中，相同内容的字面量共享同一个全局变量
+        let source = "var greeting = \"hi\"; le test()->i32{ var a = \"hi\"; ret (strlen(greeting) + strlen(a)) as i32; }";
+        let ir = compile_source(source).unwrap();
+        assert_eq!(ir.matches("c\"hi\\00\"").count(), 1);
+        assert_eq!(run_i32_function(source, "test"), 4);
+        //用户声明的运行时函数签名不一致时在声明处报错
+        let source = "decl le strlen(ref i8)->i32; le test()->i32{ ret strlen(\"abc\"); }";
+        match compile_source(source) {
+            Err(LEError::CompileError { compile_error: CompileError::TypeMismatched { .. }, position }) => {
+                assert!(position.range.end <= source.find("le test").unwrap());
+            }
+            other => panic!("expect TypeMismatched, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_constant_global_deduplication() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let mut code_generator = CodeGenerator::create(&context);
+        let i32_type = code_generator.context.i32_type();
+        let elements = [1, 2, 3].iter()
+            .map(|i| LEIntegerValue { ty: i32_type.clone(), llvm_value: i32_type.get_llvm_type().const_int(*i, false) })
+            .collect::<Vec<_>>();
+        let array = i32_type.const_array(&elements).to_le_value_enum();
+        let first = code_generator.create_constant_global("a".into(), array.clone(), &module, Position { range: 0..0 }).unwrap();
+        let second = code_generator.create_constant_global("b".into(), array, &module, Position { range: 0..0 }).unwrap();
+        assert_eq!(first, second);
+        assert!(module.get_first_global().unwrap().get_next_global().is_none());
+        //源码中`@const`的全局变量也共享相同的常量
+        let source = "@const var a = [1, 2, 3]; @const var b = [1, 2, 3]; var c = [1, 2, 3];
+            le test()->i32{ ret a[2] + b[1] + c[0]; }";
+        let ir = compile_source(source).unwrap();
+        assert_eq!(ir.matches("private unnamed_addr constant [3 x i32] [i32 1, i32 2, i32 3]").count(), 1);
+        assert_eq!(ir.matches("[3 x i32] [i32 1, i32 2, i32 3]").count(), 2);
+        assert_eq!(run_i32_function(source, "test"), 6);
+        let result = compile_source("@inline var a = 1;");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::UnknownAttribute { .. }, .. })));
+    }
+
+    #[test]
+    fn test_build_phi() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let code_generator = CodeGenerator::create(&context);
+        let i32_type = code_generator.context.i32_type();
+        let function = module.add_function("test", i32_type.get_llvm_type().fn_type(&[context.bool_type().into()], false), None);
+        let entry = context.append_basic_block(function, "entry");
+        let then_block = context.append_basic_block(function, "then");
+        let merge_block = context.append_basic_block(function, "merge");
+        let builder = &code_generator.builder;
+        builder.llvm_builder.position_at_end(entry);
+        builder.llvm_builder.build_conditional_branch(function.get_first_param().unwrap().into_int_value(), then_block, merge_block);
+        builder.llvm_builder.position_at_end(then_block);
+        builder.llvm_builder.build_unconditional_branch(merge_block);
+        builder.llvm_builder.position_at_end(merge_block);
+        let one = LEIntegerValue { ty: i32_type.clone(), llvm_value: i32_type.get_llvm_type().const_int(1, false) };
+        let two = LEIntegerValue { ty: i32_type.clone(), llvm_value: i32_type.get_llvm_type().const_int(2, false) };
+        let phi = builder.build_phi(&[(one.to_le_value_enum(), then_block), (two.to_le_value_enum(), entry)]).unwrap();
+        let phi: LEIntegerValue = phi.try_into().unwrap();
+        assert_eq!(phi.ty, i32_type);
+        builder.llvm_builder.build_return(Some(&phi.llvm_value));
+        assert!(function.verify(false));
+        //来源的值类型不一致时报错
+        let flag = code_generator.context.bool_type().get_llvm_type().const_int(1, false);
+        let flag = LEBoolValue { ty: code_generator.context.bool_type(), llvm_value: flag };
+        let result = builder.build_phi(&[(one.to_le_value_enum(), then_block), (flag.to_le_value_enum(), entry)]);
+        assert!(matches!(result, Err(CompileError::TypeMismatched { .. })));
+    }
+
+    #[test]
+    fn test_loop_else() {
+        let source = "le test()->i32{
+            var result = 0;
+            for(var i = 0; i < 3; i = i + 1;){ if(i == 1){ break; } } el { result = result + 1; }
+            while(result < 10){ result = result + 10; } el { result = result + 100; }
+            ret result;
+        }";
+        assert_eq!(run_i32_function(source, "test"), 110);
+    }
+
+    #[test]
+    fn test_write_bitcode() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let ast = Ast::from_lexer(LELexer::new("le main()->i32{ ret 0; }").unwrap()).unwrap();
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.compile(&module, &ast).unwrap();
+        let path = std::env::temp_dir().join("lelang_test_write_bitcode.bc");
+        code_generator.write_bitcode(&module, &path).unwrap();
+        let bitcode = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(bitcode.starts_with(b"BC\xC0\xDE"));
+        assert!(code_generator.bitcode_to_memory_buffer(&module).starts_with(b"BC\xC0\xDE"));
+    }
+
+    #[test]
+    fn test_integer_literal_suffix() {
+        let ir = compile_source("le main()->i32{ var a = 5i64; ret 0; }").unwrap();
+        assert!(ir.contains("alloca i64"));
+        assert!(ir.contains("store i64 5"));
+        match compile_source("le main()->i32{ var a = 256u8; ret 0; }") {
+            Err(LEError::CompileError { compile_error: CompileError::IntegerLiteralOutOfRange { .. }, .. }) => {}
+            other => panic!("expect IntegerLiteralOutOfRange, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_bit_field_member_access() {
+        let ir = compile_source("struct Flags{ a:i1, b:i3 }
+            le read(f:Flags)->i32{ ret f.b as i32; }").unwrap();
+        assert!(ir.contains("<{ i8 }>"));
+        assert!(ir.contains("lshr i8"));
+        assert!(ir.contains(", 7"));
+        let ir = compile_source("struct Flags{ a:i1, b:i3 }
+            le write(f:Flags)->i32{ f.b = 5; ret 0; }").unwrap();
+        //清除第1到3位后或上新值
+        assert!(ir.contains("and i8"));
+        assert!(ir.contains(", -15"));
+        assert!(ir.contains("or i8"));
+    }
+
+    #[test]
+    fn test_constant_condition_elimination() {
+        let ir = compile_source("decl le a(); decl le b();
+            le main()->i32{ if(3 < 5 && 2 > 1){ a(); } el { b(); } ret 0; }").unwrap();
+        assert!(ir.contains("call void @a()"));
+        assert!(!ir.contains("call void @b()"));
+        assert!(!ir.contains("icmp"));
+    }
+
+    #[test]
+    fn test_continue_targets() {
+        //for循环中continue后仍然执行步进语句，循环正常结束
+        let source = "le test()->i32{
+            var count = 0;
+            for(var i = 0; i < 5; i = i + 1;){ if(i == 2){ continue; } count = count + 1; }
+            ret count;
+        }";
+        assert_eq!(run_i32_function(source, "test"), 4);
+        //while循环中continue直接重新判断条件，不会重复执行循环体末尾的语句
+        let source = "le test()->i32{
+            var i = 0;
+            var side_effects = 0;
+            while(i < 5){ i = i + 1; if(i == 2){ continue; } side_effects = side_effects + 1; }
+            ret side_effects;
+        }";
+        assert_eq!(run_i32_function(source, "test"), 4);
+    }
+
+    #[test]
+    fn test_tail_call_hint() {
+        let ir = compile_source("le fact(n:i32, acc:i32)->i32{ if(n == 0){ ret acc; } ret fact(n - 1, n * acc); }").unwrap();
+        assert!(ir.contains("tail call i32 @fact("));
+    }
+
+    #[test]
+    fn test_struct_return() {
+        let ir = compile_source("struct Big{ a:i32, b:i32, c:i32, d:i32 }
+            le make()->Big{ ret Big{ a:1, b:2, c:3, d:4 }; }
+            le test()->i32{ var v = make(); ret v.a; }").unwrap();
+        assert!(ir.contains("define void @make(%Big* sret(%Big)"));
+        assert!(ir.contains("alloca %Big"));
+        assert!(ir.contains("call void @make(%Big* sret(%Big)"));
+    }
+
+    #[test]
+    fn test_iterate_functions() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let ast = Ast::from_lexer(LELexer::new("decl le helper(i32)->i32; le main()->i32{ ret helper(1); }").unwrap()).unwrap();
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.compile(&module, &ast).unwrap();
+        let mut functions = code_generator.functions().collect::<Vec<_>>();
+        functions.sort_by(|x, y| x.0.cmp(y.0));
+        let names = functions.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        assert_eq!(names, vec!["helper", "main"]);
+        for (name, function) in functions.iter() {
+            assert_eq!(function.get_name().to_str().unwrap(), *name);
+            assert!(function.verify(false));
+        }
+        assert_eq!(code_generator.get_function("main").unwrap().llvm_value, module.get_function("main").unwrap());
+        assert!(code_generator.get_function("missing").is_none());
+    }
+
+    #[test]
+    fn test_duplicate_function() {
+        let result = compile_source("le f()->i32{ ret 1; } le f()->i32{ ret 2; }");
+        match result {
+            Err(LEError::CompileError { compile_error: CompileError::DuplicateFunction { name }, .. }) => { assert_eq!(name, "f"); }
+            other => panic!("expect DuplicateFunction, found {:?}", other),
+        }
+        //参数类型不同的重载仍然允许
+        assert!(compile_source("le f(a:i32)->i32{ ret 1; } le f(a:f64)->i32{ ret 2; }").is_ok());
+    }
+
+    #[test]
+    fn test_duplicate_struct_member() {
+        match compile_source("struct S{ x:i32, x:f64 } le main()->i32{ ret 0; }") {
+            Err(LEError::CompileError { compile_error: CompileError::DuplicateStructMember { struct_name, member }, .. }) => {
+                assert_eq!(struct_name, "S");
+                assert_eq!(member, "x");
+            }
+            other => panic!("expect DuplicateStructMember, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_array_length_mismatch() {
+        match compile_source("le main()->i32{ var a = [1, 2, 3, 4]; a = [1, 2, 3]; ret 0; }") {
+            Err(LEError::CompileError { compile_error: CompileError::ArrayLengthMismatch { expect, found }, .. }) => {
+                assert_eq!((expect, found), (4, 3));
+            }
+            other => panic!("expect ArrayLengthMismatch, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_struct_pointer_auto_deref() {
+        let ir = compile_source("struct Point{ x:i32 } le get(p:ref Point)->i32{ ret p.x; }").unwrap();
+        assert!(ir.contains("load %Point*, %Point**"));
+        assert!(ir.contains("getelementptr inbounds %Point, %Point*"));
+    }
+
+    #[test]
+    fn test_pointer_in_bool_context() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let source = "le check(p:ref i32)->i32{ if(!p){ ret 0; } if(p){ ret 1; } ret 2; }";
+        let ast = Ast::from_lexer(LELexer::new(source).unwrap()).unwrap();
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.implicit_bool = true;
+        code_generator.compile(&module, &ast).unwrap();
+        let ir = module.print_to_string().to_string();
+        assert!(ir.contains("icmp ne i32* "));
+        assert!(ir.contains(", null"));
+        //默认模式下指针不能作为条件
+        assert!(compile_source(source).is_err());
+    }
+
+    #[test]
+    fn test_aggregate_literal_arguments() {
+        let source = "struct Point{ x:i32, y:i32 }
+            le sum(p:Point)->i32{ ret p.x + p.y; }
+            le test()->i32{ var a = 10; ret sum(Point{ x:a, y:2 }) + sum(Point{ x:1, y:2 }); }";
+        assert_eq!(run_i32_function(source, "test"), 15);
+    }
+
+    #[test]
+    fn test_hardened_build_profile() {
+        let source = "le foo()->i32{ ret 1; }";
+        let context = Context::create();
+        let module = context.create_module("test");
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.build_profile = BuildProfile::Hardened;
+        let ast = Ast::from_lexer(LELexer::new(source).unwrap()).unwrap();
+        code_generator.compile(&module, &ast).unwrap();
+        let ir = module.print_to_string().to_string();
+        assert!(ir.contains("sspstrong"));
+        assert!(ir.contains("\"frame-pointer\"=\"all\""));
+    }
+
+    #[test]
+    fn test_debug_and_release_profiles() {
+        let source = "le add_one(x:i32)->i32{ ret x + 1; } le test(y:i32)->i32{ var z = add_one(y); ret z; }";
+        let ast = parse_program(source).unwrap();
+        let context = Context::create();
+        let debug_module = context.create_module("debug");
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.build_profile = BuildProfile::Debug;
+        code_generator.compile(&debug_module, &ast).unwrap();
+        let test_ir = debug_module.get_function("test").unwrap().print_to_string().to_string();
+        assert!(test_ir.contains("alloca i32"));
+        assert!(test_ir.contains("call i32 @add_one("));
+        let ir = debug_module.print_to_string().to_string();
+        assert!(ir.contains("optnone"));
+        assert!(ir.contains("\"frame-pointer\"=\"all\""));
+        let release_module = context.create_module("release");
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.build_profile = BuildProfile::Release;
+        code_generator.compile(&release_module, &ast).unwrap();
+        let test_ir = release_module.get_function("test").unwrap().print_to_string().to_string();
+        assert!(!test_ir.contains("alloca"));
+        assert!(!test_ir.contains("call"));
+    }
+
+    #[test]
+    fn test_c_main_wrapper() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let ast = Ast::from_lexer(LELexer::new("le start()->i32{ ret 42; }").unwrap()).unwrap();
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.compile(&module, &ast).unwrap();
+        code_generator.emit_c_main_wrapper(&module, "start").unwrap();
+        let ir = module.print_to_string().to_string();
+        assert!(ir.contains("define i32 @main(i32 %0, i8** %1)"));
+        assert!(ir.contains("tail call i32 @start()"));
+        Target::initialize_native(&InitializationConfig::default()).unwrap();
+        let engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+        assert_eq!(unsafe { engine.get_function::<unsafe extern "C" fn(i32, *const *const i8) -> i32>("main").unwrap().call(0, std::ptr::null()) }, 42);
+        //用户的入口函数名为`main`时改名，并接收argc和argv
+        let module = context.create_module("test");
+        let ast = parse_program("le main(argc:i32, argv:ref ref i8)->i32{ ret argc; }").unwrap();
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.compile(&module, &ast).unwrap();
+        code_generator.emit_c_main_wrapper(&module, "main").unwrap();
+        assert!(module.verify().is_ok());
+        assert_eq!(module.get_function("main.entry").unwrap().get_linkage(), Linkage::Internal);
+        let engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+        assert_eq!(unsafe { engine.get_function::<unsafe extern "C" fn(i32, *const *const i8) -> i32>("main").unwrap().call(3, std::ptr::null()) }, 3);
+        //错误指向入口函数的定义
+        let module = context.create_module("test");
+        let source = "le start(a:i32)->i32{ ret a; }";
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.compile(&module, &parse_program(source).unwrap()).unwrap();
+        match code_generator.emit_c_main_wrapper(&module, "start") {
+            Err(LEError::CompileError { compile_error: CompileError::ArgumentCountMismatch { .. }, position }) => { assert_eq!(&source[position.range], "start"); }
+            other => panic!("expect ArgumentCountMismatch, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_exponent_float_literals() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let source = "le hex()->f64{ ret 0x1p4; } le scientific()->f64{ ret 1.5e-2; }";
+        let ast = Ast::from_lexer(LELexer::new(source).unwrap()).unwrap();
+        CodeGenerator::create(&context).compile(&module, &ast).unwrap();
+        Target::initialize_native(&InitializationConfig::default()).unwrap();
+        let engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+        unsafe {
+            assert_eq!(engine.get_function::<unsafe extern "C" fn() -> f64>("hex").unwrap().call(), 16.0);
+            assert_eq!(engine.get_function::<unsafe extern "C" fn() -> f64>("scientific").unwrap().call(), 0.015);
+        }
+        assert!(compile_source("le test()->f64{ ret 1e+; }").is_err());
+    }
+
+    #[test]
+    fn test_forward_declaration() {
+        let source = "le f(a:i32)->i32; le g()->i32{ ret f(2) + 1; } le f(a:i32)->i32{ ret a * 10; }";
+        assert_eq!(run_i32_function(source, "g"), 21);
+        let result = compile_source("le f()->i32; le f()->f64{ ret 1.0; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::ReturnTypeMismatch { .. }, .. })));
+    }
+
+    #[test]
+    fn test_mutual_recursion() {
+        let source = "le is_even(n:i32)->i32{ if(n == 0){ ret 1; } ret is_odd(n - 1); } \
+            le is_odd(n:i32)->i32{ if(n == 0){ ret 0; } ret is_even(n - 1); } \
+            le test()->i32{ ret is_even(10) * 10 + is_odd(7); }";
+        assert_eq!(run_i32_function(source, "test"), 11);
+    }
+
+    #[test]
+    fn test_type_function_name_collision() {
+        let result = compile_source("struct foo{ a:i32 } le foo(){ }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::NameCollision { .. }, .. })));
+    }
+
+    #[test]
+    fn test_bare_return_in_void_function() {
+        let ir = compile_source("le check(a:i32){ if(a > 0){ ret; } a = 1; }").unwrap();
+        assert!(ir.contains("ret void"));
+        let result = compile_source("le test()->i32{ ret; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::ReturnTypeMismatch { .. }, .. })));
+    }
+
+    #[test]
+    fn test_bool_integer_casts() {
+        let ir = compile_source("le test(x:i32)->i32{ var b = x as bool; ret b as i32; }").unwrap();
+        assert!(ir.contains("icmp ne i32 "));
+        assert!(ir.contains("zext i1 "));
+        assert_eq!(run_i32_function("le test()->i32{ var x = 2; ret (x as bool) as i32; }", "test"), 1);
+    }
+
+    #[test]
+    fn test_if_expression_phi() {
+        let ir = compile_source("le max(a:i32, b:i32)->i32{ var m = if(a > b){ a }el{ b }; ret m; }").unwrap();
+        assert!(ir.contains("phi i32 "));
+        let source = "le test()->i32{ var c = 3; ret if(c > 2){ var t = c * 2; t + 1 }el{ 0 }; }";
+        assert_eq!(run_i32_function(source, "test"), 7);
+        let result = compile_source("le test(a:i32)->i32{ ret if(a > 0){ 1 }el{ 2.0 }; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::TypeMismatched { .. }, .. })));
+    }
+
+    #[test]
+    fn test_structure_update() {
+        let source = "struct Point{ x:i32, y:i32, z:i32 }
+            le test()->i32{ var base = Point{ x:1, y:2, z:3 }; var p = Point{ ..base, x:5 }; ret p.x * 100 + p.y * 10 + p.z; }";
+        assert_eq!(run_i32_function(source, "test"), 523);
+        let result = compile_source("struct A{ x:i32 } struct B{ x:i32 } le test()->i32{ var b = B{ x:1 }; var a = A{ ..b, x:2 }; ret a.x; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::TypeMismatched { .. }, .. })));
+    }
+
+    #[test]
+    fn test_noreturn_call() {
+        let ir = compile_source("@noreturn decl le panic();
+            le test(a:i32)->i32{ if(a > 0){ panic(); a = 5; } ret a; }").unwrap();
+        assert!(ir.contains("call void @panic()\n  unreachable"));
+        assert!(!ir.contains("store i32 5"));
+        //不返回的函数调用作为表达式的一部分时，表达式的剩余部分生成在不可达的块中
+        let source = "@noreturn decl le fail()->i32;
+            le a(x:i32)->i32{ if(x > 0){ ret fail(); } ret 0; }
+            le b(x:i32)->i32{ if(x > 0){ var y = fail(); ret 1 + fail() + y; } ret 0; }
+            le c(x:i32)->i32{ if(x > 0){ if(fail() > 0){ ret 1; } } ret 0; }";
+        let context = Context::create();
+        let module = context.create_module("test");
+        CodeGenerator::create(&context).compile(&module, &parse_program(source).unwrap()).unwrap();
+        assert!(module.verify().is_ok());
+    }
+
+    #[test]
+    fn test_string_builtins() {
+        let ir = compile_source("le test()->u64{ ret strlen(\"abc\"); }").unwrap();
+        assert!(ir.contains("declare i64 @strlen(i8*)"));
+        assert!(ir.contains("call i64 @strlen(i8* "));
+        assert!(!ir.contains("@strcmp"));
+        let ir = compile_source("le test(a:ref i8, b:ref i8)->bool{ ret streq(a, b); }").unwrap();
+        assert!(ir.contains("call i32 @strcmp(i8* "));
+        assert!(ir.contains("icmp eq i32 "));
+        let result = compile_source("le test(a:ref i32)->u64{ ret strlen(a); }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::TypeMismatched { .. }, .. })));
+    }
+
+    #[test]
+    fn test_null_pointer_check() {
+        let source = "struct Point{ x:i32 } le get(p:ref Point)->i32{ ret p.x; }";
+        let context = Context::create();
+        let module = context.create_module("test");
+        let ast = Ast::from_lexer(LELexer::new(source).unwrap()).unwrap();
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.safety_checks = true;
+        code_generator.compile(&module, &ast).unwrap();
+        let ir = module.print_to_string().to_string();
+        assert!(ir.contains("icmp eq %Point* "));
+        assert!(ir.contains(", null"));
+        assert!(ir.contains("br i1 "));
+        assert!(ir.contains("call void @llvm.trap()"));
+        //默认不生成空指针检查
+        let ir = compile_source(source).unwrap();
+        assert!(!ir.contains("llvm.trap"));
+    }
+
+    #[test]
+    fn test_structure_initializer_reproducible() {
+        let source = "struct Point{ x:i32, y:i32, z:i32 } le test(a:i32)->i32{ var p = Point{ z:a, x:1, y:2 }; ret p.y; }";
+        assert_eq!(compile_source(source).unwrap(), compile_source(source).unwrap());
+    }
+
+    #[test]
+    fn test_deterministic_output() {
+        let source = "var g = 1; var h = 2.0;
+            struct A{ x:i32 } struct B{ a:A, y:f64 }
+            decl le ext(i32)->i32;
+            le f(a:i32)->i32{ ret ext(a) + g; } le f(a:f64)->i32{ ret 2; }
+            le g2()->u64{ ret strlen(\"abc\") + strlen(\"de\"); }
+            le test()->i32{ var b = B{ a:A{ x:1 }, y:h }; ret f(b.a.x); }";
+        let ast = Ast::from_lexer(LELexer::new(source).unwrap()).unwrap();
+        let compile = |ast: &Ast| {
+            let context = Context::create();
+            let module = context.create_module("test");
+            let mut code_generator = CodeGenerator::create(&context);
+            code_generator.compile(&module, ast).unwrap();
+            let names = code_generator.functions().map(|(name, _)| name.to_string()).collect::<Vec<_>>();
+            (module.print_to_string().to_string(), names)
+        };
+        assert_eq!(compile(&ast), compile(&ast));
+    }
+
+    #[test]
+    fn test_constant_negative_index() {
+        match compile_source("le test()->i32{ var arr = [1, 2, 3]; ret arr[-1]; }") {
+            Err(LEError::CompileError { compile_error: CompileError::ArrayIndexOutOfBounds { index, length }, .. }) => {
+                assert_eq!((index, length), (-1, 3));
+            }
+            other => panic!("expect ArrayIndexOutOfBounds, found {:?}", other),
+        }
+        //运行时的有符号下标允许使用
+        assert!(compile_source("le test(i:i32)->i32{ var arr = [1, 2, 3]; ret arr[i]; }").is_ok());
+        assert_eq!(run_i32_function("le test()->i32{ var arr = [1, 2, 3]; ret arr[2]; }", "test"), 3);
+    }
+
+    #[test]
+    fn test_invalid_member_access() {
+        match compile_source("struct Point{ x:i32 } le test()->i32{ var p = Point{ x:1 }; ret p. 1; }") {
+            Err(LEError::CompileError { compile_error: CompileError::InvalidMemberAccess { left_type, member }, .. }) => {
+                assert!(!left_type.is_empty());
+                assert_eq!(member, "number literal `1`");
+            }
+            other => panic!("expect InvalidMemberAccess, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_constant_array_concat_and_repeat() {
+        let ir = compile_source("var table = [1, 2] ++ [3, 4]; var zeros = [7; 5]; le main()->i32{ ret 0; }").unwrap();
+        assert!(ir.contains("[4 x i32] [i32 1, i32 2, i32 3, i32 4]"));
+        assert!(ir.contains("[5 x i32] [i32 7, i32 7, i32 7, i32 7, i32 7]"));
+        let result = compile_source("var table = [1, 2] ++ [3.0]; le main()->i32{ ret 0; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::TypeMismatched { .. }, .. })));
+        let result = compile_source("le test(a:i32)->i32{ var t = [a] ++ [1]; ret 0; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::ExpressionIsNotConstant, .. })));
+        let ir = compile_source("var mixed = [2; 2] ++ [3]; le main()->i32{ ret 0; }").unwrap();
+        assert!(ir.contains("[3 x i32] [i32 2, i32 2, i32 3]"));
+        //非常量元素只求值一次，在循环中写入每个位置
+        let source = "var counter = 0; le bump()->i32{ counter = counter + 1; ret counter; }
+            le test()->i32{ var a = [bump(); 1000]; ret a[0] + a[999] + counter * 10; }";
+        assert_eq!(compile_source(source).unwrap().matches("call i32 @bump()").count(), 1);
+        assert_eq!(run_i32_function(source, "test"), 12);
+        let result = compile_source("le test()->i32{ var a = [1; 0]; ret 0; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::NotAllowZeroLengthArray, .. })));
+    }
+
+    #[test]
+    fn test_power_of_two_strength_reduction() {
+        let ir = compile_source("le test(x:i32)->i32{ ret x * 8; }").unwrap();
+        assert!(ir.contains("shl i32 "));
+        assert!(ir.contains(", 3\n"));
+        assert!(!ir.contains("mul i32 "));
+        let ir = compile_source("le test(x:u32)->u32{ ret x / 4u32; }").unwrap();
+        assert!(ir.contains("lshr i32 "));
+        assert!(ir.contains(", 2\n"));
+        assert!(!ir.contains("udiv i32 "));
+        //有符号除法保持sdiv
+        let ir = compile_source("le test(x:i32)->i32{ ret x / 4; }").unwrap();
+        assert!(ir.contains("sdiv i32 "));
+        assert_eq!(run_i32_function("le test()->i32{ var x = 5; ret x * 8 + x / 4; }", "test"), 41);
+    }
+
+    #[test]
+    fn test_unsupported_prefix_operator() {
+        for source in ["le test(x:i32)->i32{ ret /x; }", "le test(x:i32)->i32{ ret <x; }"] {
+            let result = compile_source(source);
+            assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::NoSuitableUnaryOperator { .. }, .. })));
+        }
+    }
+
+    #[test]
+    fn test_ternary_select() {
+        let ir = compile_source("le test(a:i32)->i32{ ret a > 0 ? 1 : 2; }").unwrap();
+        assert!(ir.contains("select i1 "));
+        assert!(!ir.contains("br i1 "));
+        //分支中有函数调用时按分支和phi生成
+        let ir = compile_source("le one()->i32{ ret 1; } le test(a:i32)->i32{ ret a > 0 ? one() : 2; }").unwrap();
+        assert!(ir.contains("phi i32 "));
+        assert!(!ir.contains("select i1 "));
+        assert_eq!(run_i32_function("le test()->i32{ var a = 3; var m = a > 2 ? a * 2 : 0; ret m; }", "test"), 6);
+        //解引用可能是空指针，不能无条件求值
+        let ir = compile_source("le test(p:ref i32, a:i32)->i32{ ret a > 0 ? *p : 0; }").unwrap();
+        assert!(ir.contains("br i1 "));
+        assert!(!ir.contains("select i1 "));
+    }
+
+    #[test]
+    fn test_block_expression() {
+        assert_eq!(run_i32_function("le test()->i32{ var a = 3; var x = { var t = a * 2; t + 1 }; ret x; }", "test"), 7);
+        //块中的变量可以遮蔽外层变量，离开块后不可见
+        assert_eq!(run_i32_function("le test()->i32{ var t = 1; var x = { var t = 10; t + 1 }; ret x + t; }", "test"), 12);
+        let result = compile_source("le test()->i32{ var x = { var t = 2; t }; ret t; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::UnknownIdentifier { .. }, .. })));
+    }
+
+    #[test]
+    fn test_void_statement() {
+        let ir = compile_source("le test(a:i32)->i32{ var b = a + 1; ret b; }").unwrap();
+        let ir_with_void = compile_source("le test(a:i32)->i32{ ; var b = a + 1;; ; ret b; ; }").unwrap();
+        assert_eq!(ir, ir_with_void);
+        assert_eq!(run_i32_function("le test()->i32{ if(1 > 0){ ret 1; ; } ; ret 2; }", "test"), 1);
+        //省略条件的for循环为无限循环
+        assert_eq!(run_i32_function("le test()->i32{ var i = 0; for(;;){ i = i + 1; if(i > 4){ break; } ; } ret i; }", "test"), 5);
+    }
+
+    #[test]
+    fn test_implicit_return() {
+        assert_eq!(run_i32_function("le test()->i32{ 5 }", "test"), 5);
+        assert_eq!(run_i32_function("le add(a:i32, b:i32)->i32{ var c = a + b; c * 2 } le test()->i32{ add(1, 2) }", "test"), 6);
+        //最后的表达式同样要与返回类型一致
+        let result = compile_source("le test()->i32{ 1 > 0 }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::ReturnTypeMismatch { .. }, .. })));
+    }
+
+    #[test]
+    fn test_unsupported_struct_operation() {
+        //结构体没有定义算术运算，错误指向使用运算符的表达式
+        let source = "struct Point{ x:i32 } le add(a:Point, b:Point)->Point{ ret a + b; }";
+        let result = compile_source(source);
+        match result {
+            Err(LEError::CompileError { compile_error: CompileError::NoSuitableBinaryOperator { op: Operator::Plus, left_type, .. }, position }) => {
+                assert_eq!(left_type, "struct Point");
+                assert_eq!(&source[position.range], "a + b");
+            }
+            _ => { panic!("expect NoSuitableBinaryOperator") }
+        }
+    }
+
+    #[test]
+    fn test_struct_equality() {
+        let source = "struct Point{ x:i32, y:i32 }
+            le same(a:Point, b:Point)->i32{ if(a == b){ ret 1; } ret 0; }
+            le differ(a:Point, b:Point)->i32{ if(a != b){ ret 1; } ret 0; }
+            le test()->i32{
+                var p = Point{ x: 1, y: 2 };
+                var q = Point{ x: 1, y: 3 };
+                ret same(p, p) * 100 + same(p, q) * 10 + differ(p, q);
+            }";
+        let ir = compile_source(source).unwrap();
+        let same = &ir[ir.find("@same").unwrap()..ir.find("@differ").unwrap()];
+        assert_eq!(same.matches("icmp eq i32").count(), 2);
+        assert!(same.contains("and i1"));
+        assert_eq!(run_i32_function(source, "test"), 101);
+    }
+
+    #[test]
+    fn test_add_function() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.compile(&module, &parse_program("var base = 40; le a()->i32{ ret base + 1; }").unwrap()).unwrap();
+        let ast = parse_program("le b()->i32{ ret a() + 1; }").unwrap();
+        code_generator.add_function(&module, &ast.function_definitions[0]).unwrap();
+        assert!(code_generator.get_function("b").is_some());
+        //再次添加同名函数是重复定义
+        let result = code_generator.add_function(&module, &ast.function_definitions[0]);
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::DuplicateFunction { .. }, .. })));
+        Target::initialize_native(&InitializationConfig::default()).unwrap();
+        let engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+        assert_eq!(unsafe { engine.get_function::<unsafe extern "C" fn() -> i32>("b").unwrap().call() }, 42);
+    }
+
+    #[test]
+    fn test_replace_function() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.compile(&module, &parse_program("le f()->i32{ ret 1; } le g()->i32{ ret f() + 10; }").unwrap()).unwrap();
+        Target::initialize_native(&InitializationConfig::default()).unwrap();
+        let engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+        assert_eq!(unsafe { engine.get_function::<unsafe extern "C" fn() -> i32>("g").unwrap().call() }, 11);
+        engine.remove_module(&module).unwrap();
+        let ast = parse_program("le f()->i32{ ret 2; }").unwrap();
+        code_generator.replace_function(&module, &ast.function_definitions[0]).unwrap();
+        assert!(module.verify().is_ok());
+        let engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+        assert_eq!(unsafe { engine.get_function::<unsafe extern "C" fn() -> i32>("f").unwrap().call() }, 2);
+        assert_eq!(unsafe { engine.get_function::<unsafe extern "C" fn() -> i32>("g").unwrap().call() }, 12);
+        //已被调用的函数不能改变类型
+        let ast = parse_program("le f()->i64{ ret 2; }").unwrap();
+        let result = code_generator.replace_function(&module, &ast.function_definitions[0]);
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::TypeMismatched { .. }, .. })));
+        //新定义编译失败时保留原函数
+        engine.remove_module(&module).unwrap();
+        let ast = parse_program("le f()->i32{ var x = 1; ret y; }").unwrap();
+        assert!(code_generator.replace_function(&module, &ast.function_definitions[0]).is_err());
+        assert!(module.verify().is_ok());
+        assert!(code_generator.get_function("f").is_some());
+        let ast = parse_program("le f()->i32{ var x = 3; ret x; }").unwrap();
+        code_generator.replace_function(&module, &ast.function_definitions[0]).unwrap();
+        assert!(module.verify().is_ok());
+        let engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+        assert_eq!(unsafe { engine.get_function::<unsafe extern "C" fn() -> i32>("g").unwrap().call() }, 13);
+        //已被调用的函数不能改变调用约定
+        engine.remove_module(&module).unwrap();
+        let ast = parse_program("@fastcc le f()->i32{ ret 4; }").unwrap();
+        let result = code_generator.replace_function(&module, &ast.function_definitions[0]);
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::CallConventionChanged { .. }, .. })));
+        assert!(module.verify().is_ok());
+        assert_eq!(module.get_function("f").unwrap().get_call_conventions(), CALL_CONV_C);
+    }
+
+    #[test]
+    fn test_replace_pure_function_with_side_effect() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.compile(&module, &parse_program("var counter = 0; le f()->i32{ ret 1; } le g()->i32{ ret f() + f(); } le h()->i32{ ret g(); }").unwrap()).unwrap();
+        let has_attribute = |name: &str, attribute: &str| {
+            let kind = Attribute::get_named_enum_kind_id(attribute);
+            module.get_function(name).unwrap().get_enum_attribute(AttributeLoc::Function, kind).is_some()
+        };
+        assert!(has_attribute("g", "readnone"));
+        assert!(has_attribute("h", "readnone"));
+        let ast = parse_program("le f()->i32{ counter = counter + 1; ret counter; }").unwrap();
+        code_generator.replace_function(&module, &ast.function_definitions[0]).unwrap();
+        //调用者的内存属性按新函数重新推断
+        for name in ["f", "g", "h"] {
+            assert!(!has_attribute(name, "readnone"));
+            assert!(!has_attribute(name, "readonly"));
+        }
+        assert!(module.verify().is_ok());
+        Optimizer::run_module_passes(&module, OptimizationLevel::Default);
+        Target::initialize_native(&InitializationConfig::default()).unwrap();
+        let engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+        assert_eq!(unsafe { engine.get_function::<unsafe extern "C" fn() -> i32>("h").unwrap().call() }, 3);
+    }
+
+    #[test]
+    fn test_symbol_name_round_trip() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.compile(&module, &parse_program("le add(a:i32, b:f64)->f64{ ret b; }
+            le add(a:f64, b:i32)->f64{ ret a; }").unwrap()).unwrap();
+        let i32_type = code_generator.context.i32_type().to_le_type_enum();
+        let f64_type = code_generator.context.double_type().to_le_type_enum();
+        //重载的函数都使用重整后的名字，与定义的顺序无关
+        let first = code_generator.llvm_symbol_name("add", &[i32_type.clone(), f64_type.clone()]);
+        let second = code_generator.llvm_symbol_name("add", &[f64_type, i32_type]);
+        assert_ne!(first, "add");
+        assert_ne!(first, second);
+        assert!(module.get_function("add").is_none());
+        assert!(module.get_function(&first).is_some());
+        assert!(module.get_function(&second).is_some());
+        assert_eq!(code_generator.source_symbol_name(&first), Some(("add".to_string(), vec!["i32".to_string(), "f64".to_string()])));
+        assert_eq!(code_generator.source_symbol_name(&second), Some(("add".to_string(), vec!["f64".to_string(), "i32".to_string()])));
+        assert_eq!(code_generator.source_symbol_name("missing"), None);
+        //没有与实参类型匹配的重载时报错，而不是调用原名的函数
+        let source = "le add(a:i32, b:f64)->f64{ ret b; } le add(a:f64, b:i32)->f64{ ret a; }";
+        let result = compile_source(&format!("{} le test()->f64{{ ret add(1.0, 2.0); }}", source));
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::NoMatchingOverload { .. }, .. })));
+        let context = Context::create();
+        let module = context.create_module("test");
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.compile(&module, &parse_program("le f(a:i32)->i32{ ret a; } le test()->i32{ ret f(1); }").unwrap()).unwrap();
+        code_generator.add_function(&module, &parse_program("le f(a:i64)->i64{ ret a; }").unwrap().function_definitions[0]).unwrap();
+        code_generator.add_function(&module, &parse_program("le g()->i64{ ret f(2i64) + (f(3) as i64); }").unwrap().function_definitions[0]).unwrap();
+        assert!(module.verify().is_ok());
+    }
+
+    #[test]
+    fn test_static_assert() {
+        let ir = compile_source("le f()->i32{ static_assert(layout(i64).size == 8i64); static_assert(1 + 1 == 2, \"math\"); ret 0; }").unwrap();
+        assert!(!ir.contains("static_assert"));
+        let result = compile_source("le f()->i32{ static_assert(1 == 2, \"one is not two\"); ret 0; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::StaticAssertionFailed { message }, .. }) if message == "one is not two"));
+        let result = compile_source("le f(a:i32)->i32{ static_assert(a == 2); ret 0; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::ExpressionIsNotConstant, .. })));
+    }
+
+    #[test]
+    fn test_loop_expression_break_value() {
+        let source = "le pick(n:i32)->i32{
+                var i = 0;
+                var x = while(true){
+                    i = i + 1;
+                    if(i > n){ break i * 10; }
+                    if(i == 3){ break -1; }
+                };
+                ret x;
+            }
+            le run()->i32{ ret pick(1) + pick(5); }";
+        let ir = compile_source(source).unwrap();
+        assert!(ir.contains("phi i32"));
+        assert_eq!(run_i32_function(source, "run"), 19);
+        let result = compile_source("le f()->i32{ var x = while(true){ break 1; break 2.0; }; ret 0; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::TypeMismatched { .. }, .. })));
+        let result = compile_source("le f(n:i32)->i32{ var x = while(n > 0){ break 1; }; ret x; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::LoopExpressionWithoutValue, .. })));
+        let result = compile_source("le f()->i32{ while(true){ break 1; } ret 0; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::BreakValueInLoopStatement, .. })));
+    }
+
+    #[test]
+    fn test_self_assignment() {
+        let count_stores = |source: &str| compile_source(source).unwrap().matches("store ").count();
+        let baseline = count_stores("le f(p:i32)->i32{ var x = p; ret x; }");
+        assert_eq!(count_stores("le f(p:i32)->i32{ var x = p; x = x; ret x; }"), baseline);
+        assert_eq!(count_stores("le f(p:i32)->i32{ var x = p; x = x + 0; ret x; }"), baseline + 1);
+        let source = "struct Point{ x:i32, y:i32 }
+            le run()->i32{ var p = Point{ x: 1, y: 2 }; var a = [1, 2, 3]; var i = 1; p.x = p.x; a[i] = a[i]; a[0] = a[0]; p.y = p.x; ret p.y + a[i]; }";
+        assert_eq!(run_i32_function(source, "run"), 3);
+    }
+
+    #[test]
+    fn test_compound_assignment() {
+        let ir = compile_source("le test(x:i32)->i32{ x &= 3; ret x; }").unwrap();
+        let load = ir.find("load i32, i32* %").unwrap();
+        let and = ir.find("and i32").unwrap();
+        let store = ir[and..].find("store i32").unwrap() + and;
+        assert!(load < and && and < store);
+        let ir = compile_source("le test(x:i32)->i32{ x <<= 2; ret x; }").unwrap();
+        assert!(ir.contains("shl i32"));
+        let source = "le test()->i32{ var x = 13; x %= 5; x |= 8; x ^= 1; x >>= 1; x -= 1; ret x; }";
+        assert_eq!(run_i32_function(source, "test"), 4);
+        let result = compile_source("le test(x:f64)->f64{ x &= 1.0; ret x; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::NoSuitableBinaryOperator { op: Operator::BitAnd, .. }, .. })));
+        //左值中的函数调用只求值一次
+        let source = "var calls = 0;
+            le index()->i32{ calls = calls + 1; ret 0; }
+            le test()->i32{ var a = [1, 2]; a[index()] += 5; a[index()] *= 2; ret a[0] * 10 + calls; }";
+        assert_eq!(run_i32_function(source, "test"), 122);
+        //`@const`函数中可以对局部变量复合赋值
+        assert!(compile_source("@const le f(x:i32)->i32{ x += 1; ret x; } var y = f(1);").unwrap().contains("global i32 2"));
+    }
+
+    #[test]
+    fn test_xor_precedence() {
+        //`^`的优先级在`&`和`|`之间
+        assert_eq!(run_i32_function("le test()->i32{ ret 1 | 2 ^ 3; }", "test"), 1);
+        assert_eq!(run_i32_function("le test()->i32{ ret 6 & 3 ^ 1; }", "test"), 3);
+    }
+
+    #[test]
+    fn test_struct_too_complex() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.max_struct_fields = 2;
+        let ast = parse_program("struct Small{ a:i32, b:i32 } struct Big{ a:i32, b:i32, c:i32 }").unwrap();
+        let result = code_generator.compile(&module, &ast);
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::StructTooComplex { ref name, limit: 2, .. }, .. }) if name == "Big"));
+        let module = context.create_module("test");
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.max_struct_nesting = 2;
+        let ast = parse_program("struct A{ a:i32 } struct B{ a:A, p:ref A } struct C{ b:[B; 2] }").unwrap();
+        let result = code_generator.compile(&module, &ast);
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::StructTooComplex { ref name, limit: 2, .. }, .. }) if name == "C"));
+    }
+
+    #[test]
+    fn test_function_pointer_array() {
+        let source = "le add(a:i32)->i32{ ret a + 1; }
+            le double(a:i32)->i32{ ret a * 2; }
+            le square(a:i32)->i32{ ret a * a; }
+            le test(x:i32)->i32{
+                var vt = [add, double, square];
+                var f = vt[2];
+                ret vt[1](x) + f(x);
+            }
+            le run()->i32{ ret test(5); }";
+        let ir = compile_source(source).unwrap();
+        assert!(ir.contains("[3 x i32 (i32)*] [i32 (i32)* @add, i32 (i32)* @double, i32 (i32)* @square]"));
+        assert_eq!(run_i32_function(source, "run"), 35);
+        //签名不同的函数不能放在同一个数组中
+        let result = compile_source("le a(x:i32)->i32{ ret x; } le b(x:i64)->i32{ ret 0; } le test(){ var vt = [a, b]; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::TypeMismatched { .. }, .. })));
+    }
+
+    #[test]
+    fn test_vector_arithmetic() {
+        let ir = compile_source("le add(a:vec<i32, 4>, b:vec<i32, 4>)->vec<i32, 4>{ ret a + b; }
+            le scale(a:vec<f32, 2>)->vec<f32, 2>{ ret a * vec(2.0f32, 3.0f32); }
+            le swap(a:vec<i32, 4>)->vec<i32, 2>{ ret vec_shuffle(a, a, 1, 4); }").unwrap();
+        assert!(ir.contains("add <4 x i32>"));
+        assert!(ir.contains("fmul <2 x float>"));
+        assert!(ir.contains("shufflevector <4 x i32>"));
+        let result = compile_source("le f(a:vec<i32, 4>, b:vec<i32, 2>)->vec<i32, 4>{ ret a + b; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::NoSuitableBinaryOperator { .. }, .. })));
+        let result = compile_source("le f(a:vec<i32, 4>)->vec<i32, 1>{ ret vec_shuffle(a, a, 8); }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::ArrayIndexOutOfBounds { .. }, .. })));
+        let result = compile_source("le f(a:vec<i32, 0>)->i32{ ret 0; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::InvalidVectorLength { len: 0 }, .. })));
+        let result = compile_source("le f(a:vec<i32, 4294967297>)->i32{ ret 0; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::InvalidVectorLength { len: 4294967297 }, .. })));
+    }
+
+    #[test]
+    fn test_vector_elements() {
+        let ir = compile_source("le third(v:vec<i64, 4>)->i64{ ret vec_extract(v, 2); }
+            le at(v:vec<f64, 2>, i:i32)->f64{ ret vec_extract(v, i); }").unwrap();
+        assert!(ir.contains("extractelement <4 x i64>"));
+        assert!(ir.contains("extractelement <2 x double>"));
+        let source = "le run()->i32{
+                var v = vec(1, 2, 3, 4);
+                var i = 3;
+                var w = vec_insert(v, i, 40);
+                ret vec_extract(w, 2) + vec_extract(w, i) + vec_extract(v, 0);
+            }";
+        assert!(compile_source(source).unwrap().contains("insertelement <4 x i32>"));
+        assert_eq!(run_i32_function(source, "run"), 44);
+        //元素类型为i32，不能作为f64返回
+        let result = compile_source("le f(v:vec<i32, 4>)->f64{ ret vec_extract(v, 0); }");
+        assert!(result.is_err());
+        let result = compile_source("le f(v:vec<i32, 4>)->i32{ ret vec_extract(v, 4); }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::ArrayIndexOutOfBounds { .. }, .. })));
+    }
+
+    #[test]
+    fn test_global_address_of_global() {
+        let source = "var g = 5;
+            var p = &g;
+            var q:ref ref i32 = &p;
+            le run()->i32{ *p = *p + 1; ret **q + g; }";
+        let ir = compile_source(source).unwrap();
+        assert!(ir.contains("global i32* @g"));
+        assert!(ir.contains("global i32** @p"));
+        assert_eq!(run_i32_function(source, "run"), 12);
+        let result = compile_source("le f()->ref i32{ ret &1; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::ExpressionIsNotLeftValueExpression, .. })));
+    }
+
+    #[test]
+    fn test_pointer_cast() {
+        let ir = compile_source("le bytes(p:ref i32)->ref i8{ ret p as ref i8; }
+            le address(p:ref i32)->i64{ ret p as i64; }
+            le from_address(a:i64)->ref i32{ ret a as ref i32; }").unwrap();
+        assert!(ir.contains("bitcast i32* "));
+        assert!(ir.contains("ptrtoint i32* "));
+        assert!(ir.contains("inttoptr i64 "));
+        let result = compile_source("le f(p:ref i32)->f64{ ret p as f64; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::InvalidTypeCast { .. }, .. })));
+    }
+
+    #[test]
+    fn test_inline_function_comdat() {
+        let source = "@inline le twice(a:i32)->i32{ ret a * 2; }
+            le plain(a:i32)->i32{ ret a; }
+            le run()->i32{ ret twice(21) + plain(0); }";
+        let ir = compile_source(source).unwrap();
+        assert!(ir.contains("$twice = comdat any"));
+        let definition = ir.lines().find(|line| line.starts_with("define") && line.contains("@twice(")).unwrap();
+        assert!(definition.contains("linkonce_odr"));
+        assert!(definition.contains("comdat"));
+        assert!(!ir.contains("$plain"));
+        assert_eq!(run_i32_function(source, "run"), 42);
+        //Mach-O不支持comdat
+        let context = Context::create();
+        let module = context.create_module("test");
+        module.set_triple(&TargetTriple::create("x86_64-apple-darwin"));
+        CodeGenerator::create(&context).compile(&module, &parse_program(source).unwrap()).unwrap();
+        let ir = module.print_to_string().to_string();
+        assert!(!ir.contains("comdat"));
+        assert_eq!(module.get_function("twice").unwrap().get_linkage(), Linkage::LinkOnceODR);
+    }
+
+    #[test]
+    fn test_overflow_builtins() {
+        let source = "le wrap(a:i32, b:i32)->i32{ ret wrapping_add(a, b); }
+            le check(a:i32, b:i32)->bool{ var r = checked_add(a, b); ret r.overflowed; }
+            le saturate(a:u8, b:u8)->u8{ ret saturating_add(a, b); }";
+        let ir = compile_source(source).unwrap();
+        let wrap = ir.split("define").find(|function| function.contains("@wrap(")).unwrap();
+        assert!(wrap.contains("add i32"));
+        assert!(!wrap.contains("call"));
+        assert!(ir.contains("call { i32, i1 } @llvm.sadd.with.overflow.i32"));
+        assert!(ir.contains("%Checked.i32 = type"));
+        assert!(ir.contains("call i8 @llvm.uadd.sat.i8"));
+        //没有用到的intrinsic不会留在模块中
+        assert!(!ir.contains("llvm.sadd.with.overflow.i64"));
+        let source = "le run()->i32{
+                var big = 2147483647;
+                var r = checked_add(big, 1);
+                var s = checked_add(1, 2);
+                ret (r.overflowed ? 100 : 0) + (s.overflowed ? 10 : 0) + s.value + wrapping_add(big, 1) - (-2147483647 - 1);
+            }";
+        assert_eq!(run_i32_function(source, "run"), 103);
+        let result = compile_source("le f(a:i32, b:i64)->i32{ ret wrapping_add(a, b); }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::NoSuitableBinaryOperator { .. }, .. })));
+    }
+
+    #[test]
+    fn test_const_function() {
+        let source = "var sixteen = square(4);
+            var fact = factorial(5);
+            @const le square(x:i32)->i32{ ret x * x; }
+            @const le factorial(n:i32)->i32{ if(n <= 1){ ret 1; } var rest = factorial(n - 1); ret n * rest; }
+            le test(a:i32)->i32{ ret sixteen + fact + square(a); }
+            le run()->i32{ ret test(3); }";
+        let ir = compile_source(source).unwrap();
+        assert!(ir.contains("global i32 16"));
+        assert!(ir.contains("global i32 120"));
+        assert_eq!(run_i32_function(source, "run"), 145);
+        //@const函数中不能有循环
+        let result = compile_source("@const le count(n:i32)->i32{ while(n > 0){ n = n - 1; } ret n; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::ExpressionIsNotConstant, .. })));
+        //不在常量上下文中时，编译期求值失败的调用生成普通的函数调用
+        let source = "@const le sum_to(n:i32)->i32{ if(n <= 0){ ret 0; } ret n + sum_to(n - 1); }
+            @const le twice(x:i64)->i64{ ret x * 2; }
+            le run()->i32{ ret sum_to(1000) + twice(3) as i32; }";
+        let ir = compile_source(source).unwrap();
+        assert!(ir.contains("call i32 @sum_to("));
+        assert!(ir.contains("call i64 @twice("));
+        assert_eq!(run_i32_function(source, "run"), 500506);
+        let result = compile_source("@const le sum_to(n:i32)->i32{ if(n <= 0){ ret 0; } ret n + sum_to(n - 1); } var total = sum_to(1000);");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::ExpressionTooDeep { .. }, .. })));
+    }
+
+    #[test]
+    fn test_assign_through_returned_pointer() {
+        let source = "le first(p:ref i32)->ref i32{ ret p; }
+            le test()->i32{ var arr = [1, 2]; *first(arr) = 5; ret *first(arr) + arr[1]; }";
+        let ir = compile_source(source).unwrap();
+        assert!(ir.contains("store i32 5, i32* %"));
+        assert_eq!(run_i32_function(source, "test"), 7);
+        let result = compile_source("le test()->i32{ var a = 1; *a = 2; ret a; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::NoSuitableUnaryOperator { op: Operator::Mul, .. }, .. })));
+    }
+
+    #[test]
+    fn test_memory_attribute_inference() {
+        let source = "le add(a:i32, b:i32)->i32{ var c = a + b; ret c; }
+            le twice(a:i32)->i32{ ret add(a, a); }
+            le get(p:ref i32)->i32{ ret *p; }
+            le set(p:ref i32){ *p = 1; }";
+        let context = Context::create();
+        let module = context.create_module("test");
+        let ast = Ast::from_lexer(LELexer::new(source).unwrap()).unwrap();
+        CodeGenerator::create(&context).compile(&module, &ast).unwrap();
+        let has_attribute = |name: &str, attribute: &str| {
+            module.get_function(name).unwrap().get_enum_attribute(AttributeLoc::Function, Attribute::get_named_enum_kind_id(attribute)).is_some()
+        };
+        assert!(has_attribute("add", "readnone"));
+        assert!(has_attribute("twice", "readnone"));
+        assert!(has_attribute("get", "readonly"));
+        assert!(!has_attribute("get", "readnone"));
+        assert!(!has_attribute("set", "readnone"));
+        assert!(!has_attribute("set", "readonly"));
+    }
+
+    #[test]
+    fn test_positional_member_access() {
+        let source = "struct Pair{ a:i32, b:i32 } le test()->i32{ var p = Pair{ a:3, b:4 }; p.1 = p.1 * 10; ret p.0 + p.1; }";
+        assert_eq!(run_i32_function(source, "test"), 43);
+        //以点开头的浮点数字面量不受影响
+        assert_eq!(run_i32_function("struct Pair{ a:f64, b:f64 } le test()->i32{ var p = Pair{ a:.5, b:.25 }; ret ((p.0 + p.1) * 4.0) as i32; }", "test"), 3);
+        let result = compile_source("struct Pair{ a:i32, b:i32 } le test()->i32{ var p = Pair{ a:3, b:4 }; ret p.2; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::InvalidMemberAccess { .. }, .. })));
+    }
+
+    #[test]
+    fn test_extern_block() {
+        let ir = compile_source("decl { le first(); le second(i32)->i32; } le test()->i32{ first(); ret second(1); }").unwrap();
+        assert!(ir.contains("declare void @first()"));
+        assert!(ir.contains("declare i32 @second(i32)"));
+    }
+
+    #[test]
+    fn test_member_of_returned_struct() {
+        let source = "struct P{ x:i32, y:i32 } le make()->P{ ret P{ x:3, y:4 }; } le test()->i32{ ret make().x * 10 + make().y; }";
+        assert_eq!(run_i32_function(source, "test"), 34);
+        let result = compile_source("struct P{ x:i32, y:i32 } le make()->P{ ret P{ x:3, y:4 }; } le test()->i32{ make().x = 1; ret 0; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::ExpressionIsNotLeftValueExpression, .. })));
+        //通过sret返回的结构体同样不能赋值
+        let result = compile_source("struct Q{ x:i32, y:i32, z:i32 } le make()->Q{ ret Q{ x:1, y:2, z:3 }; } le test()->i32{ make().z = 1; ret 0; }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::ExpressionIsNotLeftValueExpression, .. })));
+    }
+
+    #[test]
+    fn test_array_decay_in_call() {
+        let source = "le sum(p:ref i32)->i32{ ret p[0] + p[2]; } le test()->i32{ var a = [1, 2, 3]; ret sum(a); }";
+        let ir = compile_source(source).unwrap();
+        assert!(ir.contains("getelementptr inbounds [3 x i32], [3 x i32]* "));
+        assert_eq!(run_i32_function(source, "test"), 4);
+    }
+
+    #[test]
+    fn test_array_len() {
+        let ir = compile_source("le test()->i64{ var a:[i32;5] = [1, 2, 3, 4, 5]; ret len(a); }").unwrap();
+        assert!(ir.contains("i64 5"));
+        //运行时长度的数组以指针和长度两个参数传递
+        let source = "le sum(p:ref i32, n:i64)->i32{ var s = 0; for(var i = 0i64; i < n; i = i + 1i64;){ s = s + p[i]; } ret s; }
+            le test()->i32{ var a = [1, 2, 3, 4]; ret sum(a, len(a)); }";
+        assert_eq!(run_i32_function(source, "test"), 10);
+        let result = compile_source("le test(p:ref i32)->i64{ ret len(p); }");
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::TypeMismatched { .. }, .. })));
+        //名为`len`的方法和用户函数不受内置函数影响
+        let source = "struct Pair{ a:i32, b:i32 } impl Pair{ le len(self)->i32{ ret 2; } }
+            le test()->i32{ var p = Pair{ a:1, b:2 }; var a = [1, 2, 3]; ret p.len() * 10 + (len(a) as i32); }";
+        assert_eq!(run_i32_function(source, "test"), 23);
+        assert_eq!(run_i32_function("le len(x:i32)->i32{ ret x + 1; } le test()->i32{ ret len(4); }", "test"), 5);
+    }
+
+    #[test]
+    fn test_type_display_names() {
+        let context = Context::create();
+        let le_context = LEContext::new(&context);
+        let i32_type = le_context.i32_type().to_le_type_enum();
+        let struct_type = LEStructType::from_llvm_type(&le_context, "Point", &["x"], &[i32_type.clone()]);
+        assert_eq!(i32_type.to_string(), "i32");
+        assert_eq!(le_context.u8_type().to_string(), "u8");
+        assert_eq!(le_context.double_type().to_string(), "f64");
+        assert_eq!(le_context.bool_type().to_string(), "bool");
+        assert_eq!(i32_type.get_pointer_type().to_string(), "ptr<i32>");
+        assert_eq!(i32_type.get_array_type(3).to_string(), "[i32; 3]");
+        assert_eq!(struct_type.to_string(), "struct Point");
+        assert_eq!(struct_type.get_pointer_type().get_array_type(2).to_string(), "[ptr<struct Point>; 2]");
+    }
+
+    #[test]
+    fn test_layout_builtin() {
+        let source = "@repr(C) struct S{ a:i8, b:i32 }
+            le test()->i32{ var l = layout(i64); var s = layout(S); ret (l.size * 1000 + l.align * 100 + s.size * 10 + s.align) as i32; }";
+        let context = Context::create();
+        let module = context.create_module("test");
+        let ast = Ast::from_lexer(LELexer::new(source).unwrap()).unwrap();
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.target_data = TargetData::create("e-m:e-i64:64-f80:128-n8:16:32:64-S128");
+        code_generator.compile(&module, &ast).unwrap();
+        Target::initialize_native(&InitializationConfig::default()).unwrap();
+        let engine = module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+        let result = unsafe { engine.get_function::<unsafe extern "C" fn() -> i32>("test").unwrap().call() };
+        //`@repr(C)`的结构体按C的规则对齐，成员之间有填充
+        assert_eq!(result, 8884);
+    }
+
+    #[test]
+    fn test_for_statement_list() {
+        let source = "le test()->i32{ var s = 0; for(var i = 0, j = 10; i < j; i = i + 1, j = j - 1;){ s = s + 1; } ret s; }";
+        assert_eq!(run_i32_function(source, "test"), 5);
+        //各项之间必须有逗号
+        let result = compile_source("le test()->i32{ var s = 0; for(var i = 0 s = 1; i < 3; i = i + 1;){} ret s; }");
+        assert!(matches!(result, Err(LEError::SyntaxError { syntax_error: SyntaxError::UnexpectToken { .. }, .. })));
+        let result = compile_source("le test()->i32{ var s = 0; for(var i = 0; i < 3; , i = i + 1;){} ret s; }");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_named_basic_blocks() {
+        let ir = compile_source("le test(a:i32)->i32{ if(a > 0){ a = 1; } ret a; }").unwrap();
+        assert!(ir.contains("if.then:"));
+        assert!(ir.contains("if.merge:"));
+        let ir = compile_source("le test()->i32{ var s = 0; for(var i = 0; i < 3; i = i + 1;){ s = s + i; } ret s; }").unwrap();
+        assert!(ir.contains("for.cond:"));
+        assert!(ir.contains("for.body:"));
+        assert!(ir.contains("for.end:"));
+    }
+
+    #[test]
+    fn test_freestanding_mode() {
+        let source = "le test(s:ref i8)->u64{ ret strlen(s); }";
+        assert!(compile_source(source).is_ok());
+        let context = Context::create();
+        let module = context.create_module("test");
+        let ast = Ast::from_lexer(LELexer::new(source).unwrap()).unwrap();
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.freestanding = true;
+        let result = code_generator.compile(&module, &ast);
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::FeatureUnavailableInFreestanding { .. }, .. })));
+        //用户自己声明的运行时函数仍然可用
+        let source = "decl le strlen(ref i8)->u64; le test(s:ref i8)->u64{ ret strlen(s); }";
+        let context = Context::create();
+        let module = context.create_module("test");
+        let ast = Ast::from_lexer(LELexer::new(source).unwrap()).unwrap();
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.freestanding = true;
+        code_generator.compile(&module, &ast).unwrap();
+        assert!(module.print_to_string().to_string().contains("call i64 @strlen("));
+    }
+
+    #[test]
+    fn test_cfg_attribute() {
+        let source = "@cfg(linux) le platform()->i32{ ret 1; }
+            @cfg(windows) le platform()->i32{ ret 2; }
+            @cfg(windows) decl le GetTickCount()->u32;
+            @cfg(linux) struct Handle{ raw:i32 }
+            @cfg(windows) struct Handle{ raw:ref i8 }
+            le raw(h:Handle)->i32{ ret h.raw; }
+            le test()->i32{ ret platform(); }";
+        let context = Context::create();
+        let module = context.create_module("test");
+        let ast = Ast::from_lexer(LELexer::new(source).unwrap()).unwrap();
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.features.insert("linux".into());
+        code_generator.compile(&module, &ast).unwrap();
+        assert!(module.get_function("platform").is_some());
+        assert!(module.get_function("GetTickCount").is_none());
+        assert!(module.print_to_string().to_string().contains("ret i32 1"));
+    }
+
+    #[test]
+    fn test_expression_too_deep() {
+        //过深的表达式在解析时就被拒绝，不会构造出过深的AST
+        let result = compile_source(&format!("le test()->i32{{ ret 1{}; }}", " + 1".repeat(10000)));
+        assert!(matches!(result, Err(LEError::SyntaxError { syntax_error: SyntaxError::ExpressionTooDeep { limit: 256 }, .. })));
+        let result = compile_source(&format!("le test()->i32{{ ret {}1{}; }}", "(".repeat(10000), ")".repeat(10000)));
+        assert!(matches!(result, Err(LEError::SyntaxError { syntax_error: SyntaxError::ExpressionTooDeep { .. }, .. })));
+        assert!(compile_source(&format!("le test()->i32{{ ret 1{}; }}", " + 1".repeat(200))).is_ok());
+        //生成代码时的嵌套上限可以单独设置
+        let context = Context::create();
+        let module = context.create_module("test");
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.max_expression_depth = 16;
+        let ast = parse_program(&format!("le test()->i32{{ ret 1{}; }}", " + 1".repeat(100))).unwrap();
+        let result = code_generator.compile(&module, &ast);
+        assert!(matches!(result, Err(LEError::CompileError { compile_error: CompileError::ExpressionTooDeep { limit: 16 }, .. })));
+    }
+
+    #[test]
+    fn test_emit_object_to_buffer() {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let ast = Ast::from_lexer(LELexer::new("le test()->i32{ ret 1; }").unwrap()).unwrap();
+        let mut code_generator = CodeGenerator::create(&context);
+        code_generator.compile(&module, &ast).unwrap();
+        let buffer = code_generator.emit_object_to_buffer(&module, &TargetMachine::get_default_triple()).unwrap();
+        assert!(!buffer.is_empty());
+        if cfg!(target_os = "linux") {
+            assert!(buffer.starts_with(b"\x7fELF"));
+        } else if cfg!(target_os = "macos") {
+            assert!(buffer.starts_with(&[0xcf, 0xfa, 0xed, 0xfe]));
+        }
+    }
+
+    #[test]
+    fn test_struct_repr_layout() {
+        let source = "struct D{ a:i8, b:i32, c:i8 } @repr(C) struct C{ a:i8, b:i32, c:i8 }
+            le test()->i32{
+                var d = D{ a:1, b:2, c:3 };
+                var c = C{ a:4, b:5, c:6 };
+                var members = d.a as i32 + d.b * 10 + d.c as i32 * 100 + c.a as i32 + c.b * 10 + c.c as i32 * 100;
+                ret (layout(D).size * 100 + layout(C).size) as i32 * 1000 + members;
+            }";
+        let ir = compile_source(source).unwrap();
+        assert!(ir.contains("%D = type <{ i32, i8, i8 }>"));
+        assert!(ir.contains("%C = type { i8, i32, i8 }"));
+        //默认布局没有填充，比`@repr(C)`更紧凑
+        assert_eq!(run_i32_function(source, "test"), 612000 + 321 + 654);
+    }
+
+    #[test]
+    fn test_constant_logic_folding() {
+        let ir = compile_source("decl le f()->bool; le test()->bool{ ret true && f(); }").unwrap();
+        assert!(ir.contains("call i1 @f()"));
+        assert!(!ir.contains("and i1"));
+        let ir = compile_source("decl le f()->bool; le test()->bool{ ret false && f(); }").unwrap();
+        assert!(!ir.contains("call i1 @f()"));
+        assert!(ir.contains("i1 false"));
+        let ir = compile_source("decl le f()->bool; le test()->bool{ ret true || f(); }").unwrap();
+        assert!(!ir.contains("call i1 @f()"));
+        let ir = compile_source("le test(a:bool)->bool{ ret !!a; }").unwrap();
+        assert!(!ir.contains("xor i1"));
+    }
+}
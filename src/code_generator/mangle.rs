@@ -0,0 +1,75 @@
+use crate::code_generator::builder::LEBasicTypeEnum;
+
+/// 名字重整的前缀
+const MANGLE_PREFIX: &str = "_LE";
+
+/// 根据函数名和参数类型生成重整后的符号名，用于区分重载的函数
+/// 格式为前缀加上长度前缀的函数名，再依次加上长度前缀的参数类型名
+/// ```text
+/// le add(a:i32,b:f64)  =>  _LE3add3i323f64
+/// ```
+pub fn mangle(name: &str, param_types: &[LEBasicTypeEnum]) -> String {
+    let mut mangled = format!("{}{}{}", MANGLE_PREFIX, name.len(), name);
+    for param_type in param_types.iter() {
+        let type_name = param_type.to_string();
+        mangled.push_str(&format!("{}{}", type_name.len(), type_name));
+    }
+    mangled
+}
+
+/// 从重整后的符号名中还原原本的函数名，不是重整过的符号名则返回None
+pub fn demangle(mangled: &str) -> Option<String> {
+    let rest = mangled.strip_prefix(MANGLE_PREFIX)?;
+    let digits_len = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+    let name_len = rest[..digits_len].parse::<usize>().ok()?;
+    rest.get(digits_len..digits_len + name_len).map(|name| name.to_string())
+}
+
+/// 从重整后的符号名中还原函数名和所有参数类型名，不是重整过的符号名或格式不完整时返回None
+pub fn demangle_signature(mangled: &str) -> Option<(String, Vec<String>)> {
+    let mut rest = mangled.strip_prefix(MANGLE_PREFIX)?;
+    let mut parts = vec![];
+    while !rest.is_empty() {
+        let digits_len = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+        let len = rest[..digits_len].parse::<usize>().ok()?;
+        parts.push(rest.get(digits_len..digits_len + len)?.to_string());
+        rest = &rest[digits_len + len..];
+    }
+    if parts.is_empty() {
+        return None;
+    }
+    let name = parts.remove(0);
+    Some((name, parts))
+}
+
+#[cfg(test)]
+mod test {
+    use inkwell::context::Context;
+
+    use crate::code_generator::builder::LEBasicType;
+    use crate::code_generator::context::LEContext;
+    use crate::code_generator::mangle::{demangle, demangle_signature, mangle};
+
+    #[test]
+    fn test_mangle_overloads() {
+        let context = Context::create();
+        let le_context = LEContext::new(&context);
+        let int_overload = mangle("add", &[le_context.i32_type().to_le_type_enum()]);
+        let float_overload = mangle("add", &[le_context.double_type().to_le_type_enum()]);
+        assert_ne!(int_overload, float_overload);
+        assert_eq!(demangle(&int_overload), Some("add".to_string()));
+        assert_eq!(demangle(&float_overload), Some("add".to_string()));
+        assert_eq!(demangle("add"), None);
+    }
+
+    #[test]
+    fn test_demangle_signature() {
+        let context = Context::create();
+        let le_context = LEContext::new(&context);
+        let mangled = mangle("add", &[le_context.i32_type().to_le_type_enum(), le_context.double_type().to_le_type_enum()]);
+        assert_eq!(demangle_signature(&mangled), Some(("add".to_string(), vec!["i32".to_string(), "f64".to_string()])));
+        //长度前缀超出符号名时不是合法的重整名
+        assert_eq!(demangle_signature("_LE3add9i32"), None);
+        assert_eq!(demangle_signature("add"), None);
+    }
+}
@@ -1,5 +1,6 @@
 pub mod generator;
 pub mod builder;
 pub mod context;
+pub mod mangle;
 
 pub type Result<T> = std::result::Result<T, crate::error::CompileError>;
@@ -18,7 +18,7 @@ use crate::ast::Ast;
 use crate::code_generator::generator::CodeGenerator;
 // use crate::code_generator::generator::CodeGenerator;
 use crate::driver::target::{initialize_target_machine, optimize_number_to_level};
-use crate::error::{LEError, Result};
+use crate::error::{LEError, Result, Warning};
 use crate::lexer;
 use crate::optimizer::Optimizer;
 
@@ -26,8 +26,10 @@ use crate::optimizer::Optimizer;
 
 mod target;
 
-pub fn compile_with_config(config: &Args, source: &str) -> Result<()> {
+/// 按配置编译源代码，成功时返回编译过程中产生的警告
+pub fn compile_with_config(config: &Args, source: &str) -> Result<Vec<Warning>> {
     let output_path = &config.output_path;
+    let mut warnings = vec![];
 
     let context = Context::create();
     let module = context.create_module("main");
@@ -54,21 +56,23 @@ pub fn compile_with_config(config: &Args, source: &str) -> Result<()> {
                         config.input_path.to_str().unwrap().to_string(),
                     )
                     .map_err(|e| LEError::IOError { other: Box::new(e) })?;
-                    Ok(())
+                    Ok(warnings)
                 };
             }
+            //后端优化与目标代码生成的设置，类型的大小和对齐依赖目标机器的数据布局
+            let target_machine = initialize_target_machine(config);
+            module.set_triple(&target_machine.get_triple());
+            module.set_data_layout(&target_machine.get_target_data().get_data_layout());
+
             //类型检查和LLVM IR生成
             let mut code_generator = CodeGenerator::create(&context);
-            code_generator.compile(&module, &ast)?;
+            code_generator.target_data = target_machine.get_target_data();
+            warnings = code_generator.compile(&module, &ast)?;
 
             //前端优化
-            let optimizer = Optimizer::new(&module, optimize_number_to_level(config.optimization));
+            let optimizer = Optimizer::new(&module, optimize_number_to_level(config.optimization.speed_level()), config.optimization.size_level());
             optimizer.run_on_module(&module);
 
-            //后端优化与目标代码生成的设置
-            let target_machine = initialize_target_machine(config);
-            module.set_triple(&target_machine.get_triple());
-
             //运行LLVM后端并输出编译结果
             match config.output_format {
                 OutputFormatEnum::IR => {
@@ -126,5 +130,5 @@ pub fn compile_with_config(config: &Args, source: &str) -> Result<()> {
         }
     }
 
-    Ok(())
+    Ok(warnings)
 }
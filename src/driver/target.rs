@@ -1,7 +1,7 @@
 use inkwell::OptimizationLevel;
-use inkwell::targets::{CodeModel, InitializationConfig, RelocMode, TargetMachine};
+use inkwell::targets::{CodeModel, InitializationConfig, RelocMode, TargetMachine, TargetTriple};
 
-use crate::arg_parser::Args;
+use crate::arg_parser::{Args, CodeModelEnum, RelocModeEnum};
 
 pub fn optimize_number_to_level(number: usize) -> OptimizationLevel {
     match number {
@@ -12,7 +12,33 @@ pub fn optimize_number_to_level(number: usize) -> OptimizationLevel {
         _ => { unreachable!() }
     }
 }
+pub fn code_model_to_llvm(model: CodeModelEnum) -> CodeModel {
+    match model {
+        CodeModelEnum::DEFAULT => { CodeModel::Default }
+        CodeModelEnum::SMALL => { CodeModel::Small }
+        CodeModelEnum::MEDIUM => { CodeModel::Medium }
+        CodeModelEnum::LARGE => { CodeModel::Large }
+    }
+}
 
+/// 将命令行中的重定位模式转换为LLVM的重定位模式，
+/// 未指定时在要求位置无关代码的平台上默认使用PIC
+pub fn reloc_mode_for_target(mode: RelocModeEnum, target_triple: &TargetTriple) -> RelocMode {
+    match mode {
+        RelocModeEnum::DEFAULT => {
+            if requires_pic(target_triple) { RelocMode::PIC } else { RelocMode::Default }
+        }
+        RelocModeEnum::STATIC => { RelocMode::Static }
+        RelocModeEnum::PIC => { RelocMode::PIC }
+        RelocModeEnum::DYNAMIC_NO_PIC => { RelocMode::DynamicNoPic }
+    }
+}
+
+/// macOS和aarch64的Windows只支持位置无关代码
+fn requires_pic(target_triple: &TargetTriple) -> bool {
+    let triple = target_triple.as_str().to_string_lossy();
+    triple.contains("apple") || (triple.starts_with("aarch64") && triple.contains("windows"))
+}
 
 pub fn initialize_target_machine(config: &Args) -> TargetMachine {
     inkwell::targets::Target::initialize_native(&InitializationConfig {
@@ -27,14 +53,65 @@ pub fn initialize_target_machine(config: &Args) -> TargetMachine {
     let target = inkwell::targets::Target::get_first().unwrap();
     let cpu = inkwell::targets::TargetMachine::get_host_cpu_name();
     let cpu_features = inkwell::targets::TargetMachine::get_host_cpu_features();
-    let reloc = RelocMode::Default;
-    let model = CodeModel::Default;
+    let reloc = reloc_mode_for_target(config.relocation_model, &target_triple);
+    let model = code_model_to_llvm(config.code_model);
     target.create_target_machine(
         &target_triple,
         cpu.to_str().unwrap(),
         cpu_features.to_str().unwrap(),
-        optimize_number_to_level(config.optimization),
+        optimize_number_to_level(config.optimization.speed_level()),
         reloc,
         model,
     ).unwrap()
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod test {
+    use inkwell::AddressSpace;
+    use inkwell::context::Context;
+    use inkwell::module::Linkage;
+    use inkwell::targets::FileType;
+
+    use crate::arg_parser::{Args, CodeModelEnum, OptimizeLevel, OutputFormatEnum, RelocModeEnum};
+    use crate::driver::target::initialize_target_machine;
+
+    fn config_with(relocation_model: RelocModeEnum, code_model: CodeModelEnum) -> Args {
+        Args {
+            optimization: OptimizeLevel::O0,
+            output_format: OutputFormatEnum::OBJ,
+            output_path: "./a.out".into(),
+            relocation_model,
+            code_model,
+            input_path: "./a.le".into(),
+        }
+    }
+
+    /// 生成一个读取外部全局变量的函数的目标文件，不同重定位模式下的访问方式不同
+    fn emit_object(config: &Args) -> Vec<u8> {
+        let context = Context::create();
+        let module = context.create_module("test");
+        let i32_type = context.i32_type();
+        let global = module.add_global(i32_type, Some(AddressSpace::Generic), "external_value");
+        global.set_linkage(Linkage::External);
+        let function = module.add_function("read_external", i32_type.fn_type(&[], false), None);
+        let builder = context.create_builder();
+        builder.position_at_end(context.append_basic_block(function, ""));
+        let value = builder.build_load(global.as_pointer_value(), "");
+        builder.build_return(Some(&value));
+        let target_machine = initialize_target_machine(config);
+        module.set_triple(&target_machine.get_triple());
+        target_machine.write_to_memory_buffer(&module, FileType::Object).unwrap().as_slice().to_vec()
+    }
+
+    #[test]
+    fn test_emit_pic_object() {
+        let pic_object = emit_object(&config_with(RelocModeEnum::PIC, CodeModelEnum::DEFAULT));
+        assert!(!pic_object.is_empty());
+        assert_ne!(pic_object, emit_object(&config_with(RelocModeEnum::STATIC, CodeModelEnum::DEFAULT)));
+    }
+
+    #[test]
+    fn test_emit_large_code_model_object() {
+        let large_object = emit_object(&config_with(RelocModeEnum::DEFAULT, CodeModelEnum::LARGE));
+        assert!(!large_object.is_empty());
+    }
+}
@@ -15,3 +15,33 @@ pub const TYPE_MISMATCHED: &str = "E0014";
 pub const NO_SUCH_MEMBER: &str = "E0015";
 pub const NOT_ALLOW_ZERO_LENGTH_ARRAY: &str = "E0016";
 pub const INVALID_TYPE_CAST: &str = "E0017";
+pub const RETURN_TYPE_MISMATCH: &str = "E0018";
+pub const LINK_ERROR: &str = "E0019";
+pub const UNKNOWN_ATTRIBUTE: &str = "E0020";
+pub const ARGUMENT_COUNT_MISMATCH: &str = "E0021";
+pub const ASSIGNMENT_IN_CONDITION: &str = "E0022";
+pub const FUNCTION_NOT_FOUND: &str = "E0023";
+pub const BREAK_OUTSIDE_LOOP: &str = "E0024";
+pub const INTEGER_LITERAL_OUT_OF_RANGE: &str = "E0025";
+pub const CONTINUE_OUTSIDE_LOOP: &str = "E0026";
+pub const DUPLICATE_FUNCTION: &str = "E0027";
+pub const DUPLICATE_STRUCT_MEMBER: &str = "E0028";
+pub const ARRAY_LENGTH_MISMATCH: &str = "E0029";
+pub const NAME_COLLISION: &str = "E0030";
+pub const ARRAY_INDEX_OUT_OF_BOUNDS: &str = "E0031";
+pub const INVALID_MEMBER_ACCESS: &str = "E0032";
+pub const EXPRESSION_IS_NOT_CONSTANT: &str = "E0033";
+pub const FEATURE_UNAVAILABLE_IN_FREESTANDING: &str = "E0034";
+pub const EXPRESSION_TOO_DEEP: &str = "E0035";
+pub const TARGET_ERROR: &str = "E0036";
+pub const NUMBER_LITERAL_OVERFLOW: &str = "E0037";
+pub const STRUCT_TOO_COMPLEX: &str = "E0038";
+pub const STATIC_ASSERTION_FAILED: &str = "E0039";
+pub const BREAK_VALUE_IN_LOOP_STATEMENT: &str = "E0040";
+pub const LOOP_EXPRESSION_WITHOUT_VALUE: &str = "E0041";
+pub const FUNCTION_POINTER_CALL_CONVENTION: &str = "E0042";
+pub const NO_MATCHING_OVERLOAD: &str = "E0043";
+pub const INVALID_VECTOR_LENGTH: &str = "E0044";
+pub const CALL_CONVENTION_CHANGED: &str = "E0045";
+pub const UNREACHABLE_STATEMENT: &str = "W0001";
+pub const SELF_ASSIGNMENT: &str = "W0002";
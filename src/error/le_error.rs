@@ -37,6 +37,7 @@ pub enum TokenType {
     Identifier,
     NumberLiteral,
     StringLiteral,
+    At,
 }
 
 impl TokenType {
@@ -64,6 +65,7 @@ impl TokenType {
             TokenType::Identifier => { "Identifier" }
             TokenType::NumberLiteral => { "Number" }
             TokenType::StringLiteral => { "String" }
+            TokenType::At => { "@" }
         }
     }
 }
@@ -108,6 +110,14 @@ pub enum SyntaxError {
     },
     #[error("array size must be an integer.")]
     ArraySizeMustBeInteger,
+    #[error("integer literal `{literal}` is too large for u64.")]
+    NumberLiteralOverflow {
+        literal: String,
+    },
+    #[error("expression is nested too deeply, the limit is {limit}.")]
+    ExpressionTooDeep {
+        limit: usize,
+    },
 }
 
 impl SyntaxError {
@@ -203,6 +213,141 @@ pub enum CompileError {
         from: String,
         to: String,
     },
+
+    #[error("function should return type `{expect}`, but return `{found}`")]
+    ReturnTypeMismatch {
+        expect: String,
+        found: String,
+    },
+
+    #[error("failed to link modules: {message}")]
+    LinkError {
+        message: String,
+    },
+
+    #[error("unknown attribute `{attribute}`")]
+    UnknownAttribute {
+        attribute: String,
+    },
+
+    #[error("function `{function}` takes {expect} arguments, but {found} arguments were supplied")]
+    ArgumentCountMismatch {
+        function: String,
+        expect: usize,
+        found: usize,
+    },
+
+    #[error("assignment expression can not be used as a condition")]
+    AssignmentInCondition,
+
+    #[error("cannot find function `{name}` in this scope")]
+    FunctionNotFound {
+        name: String,
+        suggestion: Option<String>,
+    },
+
+    #[error("`break` outside of a loop")]
+    BreakOutsideLoop,
+
+    #[error("literal `{literal}` out of range for `{ty}`")]
+    IntegerLiteralOutOfRange {
+        literal: String,
+        ty: String,
+    },
+
+    #[error("`continue` outside of a loop")]
+    ContinueOutsideLoop,
+
+    #[error("function `{name}` is defined multiple times")]
+    DuplicateFunction {
+        name: String,
+    },
+
+    #[error("field `{member}` is already declared in struct `{struct_name}`")]
+    DuplicateStructMember {
+        struct_name: String,
+        member: String,
+    },
+
+    #[error("expected an array with {expect} elements, found one with {found} elements")]
+    ArrayLengthMismatch {
+        expect: u32,
+        found: u32,
+    },
+
+    #[error("name `{name}` is used by both a type and a function")]
+    NameCollision {
+        name: String,
+    },
+
+    #[error("index {index} is out of bounds for an array of length {length}")]
+    ArrayIndexOutOfBounds {
+        index: i64,
+        length: u32,
+    },
+
+    #[error("expect a member name after `.` on type `{left_type}`, but found {member}")]
+    InvalidMemberAccess {
+        left_type: String,
+        member: String,
+    },
+
+    #[error("expect a constant expression, but expression is not")]
+    ExpressionIsNotConstant,
+
+    #[error("`{feature}` requires the C runtime, which is not available in freestanding mode")]
+    FeatureUnavailableInFreestanding {
+        feature: String,
+    },
+
+    #[error("expression is nested too deeply, the limit is {limit}")]
+    ExpressionTooDeep {
+        limit: usize,
+    },
+
+    #[error("failed to generate code for target: {message}")]
+    TargetError {
+        message: String,
+    },
+
+    #[error("structure `{name}` has too many {kind}, the limit is {limit}")]
+    StructTooComplex {
+        name: String,
+        kind: String,
+        limit: usize,
+    },
+
+    #[error("static assertion failed: {message}")]
+    StaticAssertionFailed {
+        message: String,
+    },
+
+    #[error("`break` with a value is only allowed in a loop expression")]
+    BreakValueInLoopStatement,
+
+    #[error("loop expression may finish without a value")]
+    LoopExpressionWithoutValue,
+
+    #[error("cannot take the address of function `{function}` which does not use the C calling convention")]
+    FunctionPointerCallConvention {
+        function: String,
+    },
+
+    #[error("no overload of function `{name}` accepts arguments of types ({found})")]
+    NoMatchingOverload {
+        name: String,
+        found: String,
+    },
+
+    #[error("invalid vector length `{len}`")]
+    InvalidVectorLength {
+        len: u64,
+    },
+
+    #[error("cannot change the calling convention of function `{function}` which is already called")]
+    CallConventionChanged {
+        function: String,
+    },
 }
 
 impl CompileError {
@@ -238,6 +383,13 @@ impl LEError {
         Self::CompileError { compile_error: error, position }
     }
 
+    pub fn position_mut(&mut self) -> Option<&mut Position> {
+        match self {
+            LEError::SyntaxError { position, .. } | LEError::CompileError { position, .. } => { Some(position) }
+            LEError::IOError { .. } => { None }
+        }
+    }
+
     pub fn to_error_report_colored<'s>(&self, src: &'s str) -> ReportBuilder<(&'s str, Range<usize>)> {
         let code_color = Color::White;
         let label_color = Color::Green;
@@ -285,6 +437,27 @@ impl LEError {
                             )
                             .with_help(format!("Considering change it to a `{}`", "sign integer".fg(Color::Green)))
                     }
+                    SyntaxError::NumberLiteralOverflow { literal } => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::NUMBER_LITERAL_OVERFLOW)
+                            .with_message(syntax_error.to_string().fg(code_color))
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message(format!("Integer literal `{}` exceeds `{}`", literal.fg(loop_rainbow_color.next().unwrap()), u64::MAX.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            )
+                    }
+                    SyntaxError::ExpressionTooDeep { .. } => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::EXPRESSION_TOO_DEEP)
+                            .with_message(syntax_error.to_string().fg(code_color))
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message("the expression exceeds the nesting limit here")
+                                    .with_color(label_color)
+                            )
+                            .with_help("split the expression into several variables")
+                    }
                 }
             }
             LEError::CompileError { compile_error, position } => {
@@ -444,6 +617,291 @@ impl LEError {
                                     .with_color(label_color)
                             )
                     }
+                    CompileError::ReturnTypeMismatch { expect, found } => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::RETURN_TYPE_MISMATCH)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message(format!("return a value of type `{}` here, but function return type is `{}`",
+                                                          found.fg(loop_rainbow_color.next().unwrap()),
+                                                          expect.fg(loop_rainbow_color.next().unwrap()))
+                                    )
+                                    .with_color(label_color)
+                            )
+                            .with_help(format!("maybe you need a type cast to type `{}` ?", expect.fg(Color::Green)))
+                    }
+                    CompileError::LinkError { message } => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::LINK_ERROR)
+                            .with_message(compile_error.to_string())
+                            .with_note(message.fg(label_color))
+                    }
+                    CompileError::UnknownAttribute { attribute } => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::UNKNOWN_ATTRIBUTE)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message(format!("attribute `{}` is not supported here", attribute.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            )
+                    }
+                    CompileError::ArgumentCountMismatch { function, expect, found } => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::ARGUMENT_COUNT_MISMATCH)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message(format!("expect {} arguments, found {}",
+                                                          expect.to_string().fg(loop_rainbow_color.next().unwrap()),
+                                                          found.to_string().fg(loop_rainbow_color.next().unwrap()))
+                                    )
+                                    .with_color(label_color)
+                            )
+                    }
+                    CompileError::AssignmentInCondition => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::ASSIGNMENT_IN_CONDITION)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message("assignment used as condition here")
+                                    .with_color(label_color)
+                            )
+                            .with_help(format!("maybe you want to compare with `{}` ?", "==".fg(Color::Green)))
+                    }
+                    CompileError::FunctionNotFound { name, suggestion } => {
+                        let report = Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::FUNCTION_NOT_FOUND)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message(format!("function `{}` is not defined", name.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            );
+                        if let Some(suggestion) = suggestion {
+                            report.with_help(format!("a function with a similar name exists: `{}`", suggestion.fg(Color::Green)))
+                        } else {
+                            report
+                        }
+                    }
+                    CompileError::BreakOutsideLoop => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::BREAK_OUTSIDE_LOOP)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message("cannot `break` outside of a loop")
+                                    .with_color(label_color)
+                            )
+                    }
+                    CompileError::IntegerLiteralOutOfRange { literal, ty } => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::INTEGER_LITERAL_OUT_OF_RANGE)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message(format!("the literal `{}` does not fit into the type `{}`", literal, ty))
+                                    .with_color(label_color)
+                            )
+                    }
+                    CompileError::ContinueOutsideLoop => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::CONTINUE_OUTSIDE_LOOP)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message("cannot `continue` outside of a loop")
+                                    .with_color(label_color)
+                            )
+                    }
+                    CompileError::DuplicateFunction { name } => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::DUPLICATE_FUNCTION)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message(format!("`{}` redefined here with the same parameter types", name))
+                                    .with_color(label_color)
+                            )
+                    }
+                    CompileError::DuplicateStructMember { struct_name, member } => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::DUPLICATE_STRUCT_MEMBER)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message(format!("`{}` declared again in `{}`", member, struct_name))
+                                    .with_color(label_color)
+                            )
+                    }
+                    CompileError::ArrayLengthMismatch { expect, found } => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::ARRAY_LENGTH_MISMATCH)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message(format!("expected {} elements, found {}", expect, found))
+                                    .with_color(label_color)
+                            )
+                    }
+                    CompileError::NameCollision { name } => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::NAME_COLLISION)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message(format!("`{}` already names a type or function", name))
+                                    .with_color(label_color)
+                            )
+                    }
+                    CompileError::ArrayIndexOutOfBounds { index, length } => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::ARRAY_INDEX_OUT_OF_BOUNDS)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message(format!("index `{}` is not in `0..{}`", index, length))
+                                    .with_color(label_color)
+                            )
+                    }
+                    CompileError::InvalidMemberAccess { left_type, member } => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::INVALID_MEMBER_ACCESS)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message(format!("{} is not a member of `{}`", member, left_type.fg(loop_rainbow_color.next().unwrap())))
+                                    .with_color(label_color)
+                            )
+                            .with_help("members are accessed by name, like `value.member`")
+                    }
+                    CompileError::ExpressionIsNotConstant => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::EXPRESSION_IS_NOT_CONSTANT)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message("this value can not be computed at compile time")
+                                    .with_color(label_color)
+                            )
+                    }
+                    CompileError::FeatureUnavailableInFreestanding { feature } => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::FEATURE_UNAVAILABLE_IN_FREESTANDING)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message(format!("`{}` is used here", feature))
+                                    .with_color(label_color)
+                            )
+                            .with_help("declare the runtime function yourself with `decl`")
+                    }
+                    CompileError::ExpressionTooDeep { .. } => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::EXPRESSION_TOO_DEEP)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message("this expression exceeds the nesting limit")
+                                    .with_color(label_color)
+                            )
+                            .with_help("split the expression into several variables")
+                    }
+                    CompileError::TargetError { message } => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::TARGET_ERROR)
+                            .with_message(compile_error.to_string())
+                            .with_note(message.fg(label_color))
+                    }
+                    CompileError::StructTooComplex { .. } => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::STRUCT_TOO_COMPLEX)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message("this structure exceeds the limit")
+                                    .with_color(label_color)
+                            )
+                            .with_help("split the structure into several smaller structures")
+                    }
+                    CompileError::StaticAssertionFailed { .. } => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::STATIC_ASSERTION_FAILED)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message("this condition is false at compile time")
+                                    .with_color(label_color)
+                            )
+                    }
+                    CompileError::BreakValueInLoopStatement => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::BREAK_VALUE_IN_LOOP_STATEMENT)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message("the value of this loop is not used")
+                                    .with_color(label_color)
+                            )
+                            .with_help("remove the value, or use the loop as an expression")
+                    }
+                    CompileError::LoopExpressionWithoutValue => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::LOOP_EXPRESSION_WITHOUT_VALUE)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message("the loop can finish here without a value")
+                                    .with_color(label_color)
+                            )
+                            .with_help("a loop expression must be `while(true)` and leave only through `break value;`")
+                    }
+                    CompileError::FunctionPointerCallConvention { .. } => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::FUNCTION_POINTER_CALL_CONVENTION)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message("function pointers are always called with the C calling convention")
+                                    .with_color(label_color)
+                            )
+                            .with_help("wrap the function in a `le` function with the default calling convention")
+                    }
+                    CompileError::NoMatchingOverload { .. } => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::NO_MATCHING_OVERLOAD)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message("none of the overloads has these parameter types")
+                                    .with_color(label_color)
+                            )
+                            .with_help("convert the arguments with `as` to match one of the overloads")
+                    }
+                    CompileError::InvalidVectorLength { .. } => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::INVALID_VECTOR_LENGTH)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message("vector type declared here")
+                                    .with_color(label_color)
+                            )
+                            .with_help(format!("the length of a vector must be between 1 and {}", u32::MAX))
+                    }
+                    CompileError::CallConventionChanged { .. } => {
+                        Report::build(ReportKind::Error, src, position.range.start())
+                            .with_code(error_list::CALL_CONVENTION_CHANGED)
+                            .with_message(compile_error.to_string())
+                            .with_label(
+                                Label::new((src, position.range.clone()))
+                                    .with_message("the existing calls use the calling convention of the old definition")
+                                    .with_color(label_color)
+                            )
+                            .with_help("keep the calling convention attribute of the old definition")
+                    }
                 }
             }
             LEError::IOError { other } => {
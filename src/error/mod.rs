@@ -1,6 +1,8 @@
 pub use le_error::*;
+pub use warning::*;
 
 mod le_error;
+mod warning;
 pub mod error_list;
 
 pub type Result<T> = std::result::Result<T, LEError>;
\ No newline at end of file
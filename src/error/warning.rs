@@ -0,0 +1,64 @@
+use std::ops::Range;
+
+use ariadne::{Color, Label, Report, ReportBuilder, ReportKind};
+use thiserror::Error;
+
+use crate::lexer::Position;
+
+use super::error_list;
+
+/// 不影响编译结果的警告，编译成功时与生成的模块一起返回
+#[derive(Error, Debug, Clone)]
+pub enum Warning {
+    #[error("unreachable statement")]
+    UnreachableStatement {
+        position: Position,
+    },
+
+    #[error("assigning a value to itself has no effect")]
+    SelfAssignment {
+        position: Position,
+    },
+}
+
+impl Warning {
+    pub fn position(&self) -> &Position {
+        match self {
+            Warning::UnreachableStatement { position } | Warning::SelfAssignment { position } => { position }
+        }
+    }
+
+    pub fn position_mut(&mut self) -> &mut Position {
+        match self {
+            Warning::UnreachableStatement { position } | Warning::SelfAssignment { position } => { position }
+        }
+    }
+
+    pub fn to_warning_report_colored<'s>(&self, src: &'s str) -> ReportBuilder<(&'s str, Range<usize>)> {
+        let label_color = Color::Yellow;
+        let position = self.position();
+        match self {
+            Warning::UnreachableStatement { .. } => {
+                Report::build(ReportKind::Warning, src, position.range.start)
+                    .with_code(error_list::UNREACHABLE_STATEMENT)
+                    .with_message(self.to_string())
+                    .with_label(
+                        Label::new((src, position.range.clone()))
+                            .with_message("this statement is never executed")
+                            .with_color(label_color)
+                    )
+                    .with_help("the block already ends with `ret`, `break`, `continue` or `unreachable` before it")
+            }
+            Warning::SelfAssignment { .. } => {
+                Report::build(ReportKind::Warning, src, position.range.start)
+                    .with_code(error_list::SELF_ASSIGNMENT)
+                    .with_message(self.to_string())
+                    .with_label(
+                        Label::new((src, position.range.clone()))
+                            .with_message("both sides of this assignment are the same place")
+                            .with_color(label_color)
+                    )
+            }
+        }
+    }
+}
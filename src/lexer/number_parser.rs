@@ -14,30 +14,74 @@ fn integer(input: &str) -> IResult<&str, u64> {
     for byte in input.as_bytes() {
         if is_digit(*byte) {
             counter += 1;
-        } else if *byte == b'.' {
+        } else if matches!(*byte, b'.' | b'e' | b'E') {
             return Err(Err::Incomplete(Needed::new(1)));
+        } else {
+            break;
+        }
+    }
+    if counter == 0 {
+        return Err(Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Digit)));
+    }
+    //超出u64范围的整数无法表示，交给parse报告溢出
+    match input[..counter].parse::<u64>() {
+        Ok(number) => { Ok((&input[counter..], number)) }
+        Err(_) => { Err(Err::Failure(nom::error::Error::new(input, nom::error::ErrorKind::TooLarge))) }
+    }
+}
+
+/// 解析去掉`0x`前缀的十六进制浮点数，如`1.8p3`表示`1.5 * 2^3`，返回值和类型后缀，指数超出i32范围时返回None
+fn hex_float(input: &str) -> Option<(f64, Option<String>)> {
+    let (mantissa, exponent) = input.split_at(input.find(|c| matches!(c, 'p' | 'P')).unwrap());
+    let (exponent, suffix) = split_suffix(&exponent[1..]);
+    let (integer_part, fraction_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let mut value = 0.0;
+    for digit in integer_part.chars().chain(fraction_part.chars()) {
+        value = value * 16.0 + digit.to_digit(16).unwrap() as f64;
+    }
+    let exponent = exponent.parse::<i32>().ok()?.checked_sub(4 * i32::try_from(fraction_part.len()).ok()?)?;
+    Some((value * 2f64.powi(exponent), suffix))
+}
+
+fn parse(input: &str) -> Option<(Number, usize)> {
+    match integer(input) {
+        Ok((remain, number)) => { Some((Number::Integer(number, None), remain.len())) }
+        Err(Err::Failure(_)) => { None }
+        Err(_) => {
+            let (remain, number) = double::<_, nom::error::Error<&str>>(input).unwrap();
+            Some((Number::Float(number, None), remain.len()))
         }
     }
-    Ok((&input[counter..], input[..counter].parse::<u64>().unwrap()))
 }
 
-fn parse(input: &str) -> (Number, usize) {
-    if let Ok((remain, number)) = integer(input) {
-        (Number::Integer(number), remain.len())
-    } else {
-        let (remain, number) = double::<_, nom::error::Error<&str>>(input).unwrap();
-        (Number::Float(number), remain.len())
+/// 将字面量拆分为数字部分和类型后缀部分，如`5i64`拆分为`5`和`i64`
+fn split_suffix(input: &str) -> (&str, Option<String>) {
+    match input.find(|c| matches!(c, 'i' | 'u' | 'f')) {
+        Some(index) => { (&input[..index], Some(input[index..].to_string())) }
+        None => { (input, None) }
     }
 }
 
-pub fn parse_number(input: &mut Lexer<LogosToken>) -> Number {
-    let result = parse(input.slice());
-    result.0
+/// 解析数字字面量，整数超出u64范围或十六进制浮点数的指数超出i32范围时返回字面量原文
+pub fn parse_number(input: &mut Lexer<LogosToken>) -> std::result::Result<Number, String> {
+    if let Some(hex) = input.slice().strip_prefix("0x").or_else(|| input.slice().strip_prefix("0X")) {
+        let (value, suffix) = hex_float(hex).ok_or_else(|| input.slice().to_string())?;
+        return Ok(Number::Float(value, suffix));
+    }
+    let (digits, suffix) = split_suffix(input.slice());
+    match parse(digits).map(|(number, _)| number) {
+        Some(Number::Integer(i, _)) => { Ok(Number::Integer(i, suffix)) }
+        Some(Number::Float(f, _)) => { Ok(Number::Float(f, suffix)) }
+        None => { Err(input.slice().to_string()) }
+    }
 }
 
 #[allow(unused)]
 mod test {
-    use crate::lexer::number_parser::parse;
+    use crate::ast::parse_program;
+    use crate::error::{LEError, SyntaxError};
+    use crate::lexer::{LELexer, LEToken, Number};
+    use crate::lexer::number_parser::{hex_float, parse};
 
     #[test]
     fn test_parse_number() {
@@ -45,4 +89,38 @@ mod test {
             eprintln!("{:?}", parse(num))
         }
     }
+
+    #[test]
+    fn test_parse_exponent_float() {
+        assert_eq!(hex_float("1p4"), Some((16.0, None)));
+        assert_eq!(hex_float("1.8p3"), Some((12.0, None)));
+        assert_eq!(hex_float("1p-1f32"), Some((0.5, Some("f32".into()))));
+        assert_eq!(hex_float("1p99999999999"), None);
+        assert_eq!(parse("1.5e-2").unwrap().0, Number::Float(0.015, None));
+        let lexer = LELexer::new(".5").unwrap();
+        assert_eq!(lexer.current(), Some(LEToken::NumberLiteral(Number::Float(0.5, None))));
+        let lexer = LELexer::new("0x1p4").unwrap();
+        assert_eq!(lexer.current(), Some(LEToken::NumberLiteral(Number::Float(16.0, None))));
+        let lexer = LELexer::new("1e+").unwrap();
+        assert_eq!(lexer.current(), Some(LEToken::MalformedNumberLiteral("1e+".into())));
+        //指数超出范围的十六进制浮点数作为格式错误的字面量，由语法分析在其位置报错
+        let lexer = LELexer::new("0x1p99999999999").unwrap();
+        assert_eq!(lexer.current(), Some(LEToken::MalformedNumberLiteral("0x1p99999999999".into())));
+        let source = "le test()->f64{ ret 0x1p99999999999; }";
+        match parse_program(source) {
+            Err(LEError::SyntaxError { syntax_error: SyntaxError::UnexpectToken { .. }, position }) => { assert_eq!(&source[position.range], "0x1p99999999999"); }
+            other => panic!("expect UnexpectToken, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_integer_overflow() {
+        assert_eq!(parse("18446744073709551615").unwrap().0, Number::Integer(u64::MAX, None));
+        assert!(parse("18446744073709551616").is_none());
+        let literal = "123456789012345678901234567890";
+        let mut lexer = LELexer::new(literal).unwrap();
+        assert_eq!(lexer.current(), Some(LEToken::OverflowNumberLiteral(literal.into())));
+        let error = lexer.consume_number_literal().unwrap_err();
+        assert!(matches!(error, LEError::SyntaxError { syntax_error: SyntaxError::NumberLiteralOverflow { .. }, .. }));
+    }
 }
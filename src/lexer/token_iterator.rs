@@ -11,14 +11,15 @@ use crate::lexer::LEToken::Semicolon;
 use crate::lexer::number_parser::parse_number;
 
 fn record_span(lexer: &mut Lexer<LogosToken>) {
-    let token_start = lexer.span().start;
+    let token_start = lexer.extras.base_offset + lexer.span().start;
     let token_range = lexer.slice().len();
     lexer.extras.last_pos = lexer.extras.current_pos.clone();
     lexer.extras.current_pos.range = (token_start..token_start + token_range);
 }
 
 fn parse_string_literal_token(s: &str) -> Option<String> {
-    Some(s.into())
+    //去掉两侧的引号
+    Some(s[1..s.len() - 1].into())
 }
 
 #[derive(Debug, Clone)]
@@ -37,6 +38,8 @@ impl Position {
 pub struct Extra {
     current_pos: Position,
     last_pos: Position,
+    /// 源码在合并编译的所有源文件中的起始偏移，token的位置都加上这个偏移
+    base_offset: usize,
 }
 
 
@@ -77,6 +80,24 @@ pub enum LogosToken {
     #[token("ret", | lex | record_span(lex))]
     Return,
 
+    #[token("asm", | lex | record_span(lex))]
+    Asm,
+
+    #[token("volatile", | lex | record_span(lex))]
+    Volatile,
+
+    #[token("impl", | lex | record_span(lex))]
+    Implement,
+
+    #[token("break", | lex | record_span(lex))]
+    Break,
+
+    #[token("continue", | lex | record_span(lex))]
+    Continue,
+
+    #[token("unreachable", | lex | record_span(lex))]
+    Unreachable,
+
     #[token("true", | lex | record_span(lex))]
     True,
 
@@ -95,6 +116,12 @@ pub enum LogosToken {
     #[token(".", | lex | record_span(lex))]
     Dot,
 
+    #[token("..", | lex | record_span(lex))]
+    DoubleDot,
+
+    #[token("?", | lex | record_span(lex))]
+    Question,
+
     #[token(";", | lex | record_span(lex))]
     Semicolon,
 
@@ -128,6 +155,9 @@ pub enum LogosToken {
     #[token("+", | lex | record_span(lex))]
     Plus,
 
+    #[token("++", | lex | record_span(lex))]
+    Concat,
+
     #[token("-", | lex | record_span(lex))]
     Sub,
 
@@ -176,6 +206,34 @@ pub enum LogosToken {
     #[token("^", | lex | record_span(lex))]
     Xor,
 
+    #[token("&", | lex | record_span(lex))]
+    BitAnd,
+
+    #[token("|", | lex | record_span(lex))]
+    BitOr,
+
+    #[token("<<", | lex | record_span(lex))]
+    ShiftLeft,
+
+    #[token(">>", | lex | record_span(lex))]
+    ShiftRight,
+
+    /// 复合赋值运算符，如`+=`，值为对应的二元运算符
+    #[token("+=", | lex | {record_span(lex); Operator::Plus})]
+    #[token("-=", | lex | {record_span(lex); Operator::Sub})]
+    #[token("*=", | lex | {record_span(lex); Operator::Mul})]
+    #[token("/=", | lex | {record_span(lex); Operator::Div})]
+    #[token("%=", | lex | {record_span(lex); Operator::Mod})]
+    #[token("&=", | lex | {record_span(lex); Operator::BitAnd})]
+    #[token("|=", | lex | {record_span(lex); Operator::BitOr})]
+    #[token("^=", | lex | {record_span(lex); Operator::Xor})]
+    #[token("<<=", | lex | {record_span(lex); Operator::ShiftLeft})]
+    #[token(">>=", | lex | {record_span(lex); Operator::ShiftRight})]
+    CompoundAssign(Operator),
+
+    #[token("@", | lex | record_span(lex))]
+    At,
+
     #[regex(r"[\s]+", logos::skip)]
     WhiteCharacter,
 
@@ -185,11 +243,19 @@ pub enum LogosToken {
     #[regex("[a-zA-Z_]+[a-zA-Z_0-9]*", | lex | {record_span(lex); lex.slice().to_string()})]
     Identifier(String),
 
-    #[regex(r#""[^\n]*""#, | lex | {record_span(lex); parse_string_literal_token(lex.slice())})]
+    #[regex(r#""[^"\n]*""#, | lex | {record_span(lex); parse_string_literal_token(lex.slice())})]
     StringLiteral(String),
 
-    #[regex(r#"[0-9]*(\.[0-9]+)?"#, | lex | {record_span(lex); parse_number(lex)})]
-    NumberLiteral(Number),
+    /// 超出u64范围的整数字面量解析为Err，由语法分析报告溢出
+    #[regex(r#"[0-9]*(\.[0-9]+)?|[0-9]+(\.[0-9]+)?([iu](8|16|32|64)|f(32|64))"#, | lex | {record_span(lex); Some(parse_number(lex))})]
+    NumberLiteral(std::result::Result<Number, String>),
+
+    #[regex(r#"[0-9]+(\.[0-9]+)?[eE][+-]?[0-9]+(f(32|64))?|0[xX][0-9a-fA-F]+(\.[0-9a-fA-F]*)?[pP][+-]?[0-9]+(f(32|64))?"#, | lex | {record_span(lex); Some(parse_number(lex))})]
+    ExponentNumberLiteral(std::result::Result<Number, String>),
+
+    /// 指数部分不完整的数字字面量，如`1e+`、`0x1.8`，交给语法分析报错，指数超出范围的十六进制浮点数也作为此类字面量
+    #[regex(r#"[0-9]+(\.[0-9]+)?[eE][+-]?|0[xX][0-9a-fA-F]+(\.[0-9a-fA-F]*)?([pP][+-]?)?"#, | lex | {record_span(lex); lex.slice().to_string()})]
+    MalformedNumberLiteral(String),
 
     #[error]
     Error,
@@ -216,6 +282,18 @@ pub enum KeyWord {
     StructureDeclare,
 
     Ref,
+
+    Asm,
+
+    Volatile,
+
+    Implement,
+
+    Break,
+
+    Continue,
+
+    Unreachable,
 }
 
 impl Display for KeyWord {
@@ -231,6 +309,12 @@ impl Display for KeyWord {
             KeyWord::While => { "while" }
             KeyWord::StructureDeclare => { "struct" }
             KeyWord::Ref => { "ref" }
+            KeyWord::Asm => { "asm" }
+            KeyWord::Volatile => { "volatile" }
+            KeyWord::Implement => { "impl" }
+            KeyWord::Break => { "break" }
+            KeyWord::Continue => { "continue" }
+            KeyWord::Unreachable => { "unreachable" }
         };
         f.write_str(str)
     }
@@ -276,6 +360,16 @@ pub enum Operator {
     Mod,
 
     Cast,
+
+    Concat,
+
+    BitAnd,
+
+    BitOr,
+
+    ShiftLeft,
+
+    ShiftRight,
 }
 
 impl Display for Operator {
@@ -289,7 +383,7 @@ impl Display for Operator {
             Operator::Equal => { "==" }
             Operator::NotEqual => { "!=" }
             Operator::GreaterThan => { ">" }
-            Operator::LessThan => { "<=" }
+            Operator::LessThan => { "<" }
             Operator::GreaterOrEqualThan => { ">=" }
             Operator::LessOrEqualThan => { "<=" }
             Operator::Dot => { "." }
@@ -300,6 +394,11 @@ impl Display for Operator {
             Operator::Rev => { "~" }
             Operator::Mod => { "%" }
             Operator::Cast => { "as" }
+            Operator::Concat => { "++" }
+            Operator::BitAnd => { "&" }
+            Operator::BitOr => { "|" }
+            Operator::ShiftLeft => { "<<" }
+            Operator::ShiftRight => { ">>" }
         };
         f.write_str(s)
     }
@@ -307,16 +406,25 @@ impl Display for Operator {
 
 #[derive(Debug, PartialEq, Clone)]
 #[allow(dead_code)]
+/// 数字字面量，第二个字段是字面量后缀指定的类型名，如`5i64`中的`i64`
 pub enum Number {
-    Integer(u64),
-    Float(f64),
+    Integer(u64, Option<String>),
+    Float(f64, Option<String>),
+}
+
+impl Number {
+    pub fn suffix(&self) -> Option<&str> {
+        match self {
+            Number::Integer(_, suffix) | Number::Float(_, suffix) => { suffix.as_deref() }
+        }
+    }
 }
 
 impl Display for Number {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Number::Integer(i) => { f.write_fmt(format_args!("{}", i)) }
-            Number::Float(float) => { f.write_fmt(format_args!("{}", float)) }
+            Number::Integer(i, suffix) => { f.write_fmt(format_args!("{}{}", i, suffix.as_deref().unwrap_or(""))) }
+            Number::Float(float, suffix) => { f.write_fmt(format_args!("{}{}", float, suffix.as_deref().unwrap_or(""))) }
         }
     }
 }
@@ -330,6 +438,11 @@ pub enum LEToken {
 
     NumberLiteral(Number),
 
+    MalformedNumberLiteral(String),
+
+    /// 超出u64范围的整数字面量
+    OverflowNumberLiteral(String),
+
     StringLiteral(String),
 
     Identifier(String),
@@ -355,6 +468,15 @@ pub enum LEToken {
     SingleArrow,
 
     DoubleArrow,
+
+    DoubleDot,
+
+    Question,
+
+    At,
+
+    /// 复合赋值运算符，值为赋值前进行的二元运算
+    CompoundAssign(Operator),
 }
 
 impl Display for LEToken {
@@ -363,6 +485,8 @@ impl Display for LEToken {
             LEToken::KeyWord(k) => { Display::fmt(k, f) }
             LEToken::Operator(o) => { Display::fmt(o, f) }
             LEToken::NumberLiteral(n) => { Display::fmt(n, f) }
+            LEToken::MalformedNumberLiteral(n) => { Display::fmt(n, f) }
+            LEToken::OverflowNumberLiteral(n) => { Display::fmt(n, f) }
             LEToken::StringLiteral(s) => { Display::fmt(s, f) }
             LEToken::Identifier(i) => { Display::fmt(i, f) }
             LEToken::Colon => { f.write_str(":") }
@@ -376,6 +500,10 @@ impl Display for LEToken {
             LEToken::LeftBrace => { f.write_str("}") }
             LEToken::SingleArrow => { f.write_str("->") }
             LEToken::DoubleArrow => { f.write_str("=>") }
+            LEToken::DoubleDot => { f.write_str("..") }
+            LEToken::Question => { f.write_str("?") }
+            LEToken::At => { f.write_str("@") }
+            LEToken::CompoundAssign(o) => { write!(f, "{}=", o) }
         }
     }
 }
@@ -407,7 +535,10 @@ impl From<LogosToken> for LEToken {
             LogosToken::Assign => { Self::Operator(Operator::Assign) }
             LogosToken::Equal => { Self::Operator(Operator::Equal) }
             LogosToken::StringLiteral(literal) => { Self::StringLiteral(literal) }
-            LogosToken::NumberLiteral(num) => { Self::NumberLiteral(num) }
+            LogosToken::NumberLiteral(Ok(num)) | LogosToken::ExponentNumberLiteral(Ok(num)) => { Self::NumberLiteral(num) }
+            LogosToken::NumberLiteral(Err(literal)) => { Self::OverflowNumberLiteral(literal) }
+            //带指数的字面量只有十六进制浮点数的指数超出范围时解析失败
+            LogosToken::MalformedNumberLiteral(literal) | LogosToken::ExponentNumberLiteral(Err(literal)) => { Self::MalformedNumberLiteral(literal) }
             LogosToken::Identifier(identifier) => { Self::Identifier(identifier) }
             LogosToken::GreaterThan => { Self::Operator(Operator::GreaterThan) }
             LogosToken::LessThan => { Self::Operator(Operator::LessThan) }
@@ -428,7 +559,22 @@ impl From<LogosToken> for LEToken {
             LogosToken::Mod => { Self::Operator(Operator::Mod) }
             LogosToken::NotEqual => { Self::Operator(Operator::NotEqual) }
             LogosToken::DoubleArrow => { Self::DoubleArrow }
+            LogosToken::DoubleDot => { Self::DoubleDot }
+            LogosToken::Question => { Self::Question }
             LogosToken::Cast => { Self::Operator(Operator::Cast) }
+            LogosToken::Concat => { Self::Operator(Operator::Concat) }
+            LogosToken::BitAnd => { Self::Operator(Operator::BitAnd) }
+            LogosToken::BitOr => { Self::Operator(Operator::BitOr) }
+            LogosToken::ShiftLeft => { Self::Operator(Operator::ShiftLeft) }
+            LogosToken::ShiftRight => { Self::Operator(Operator::ShiftRight) }
+            LogosToken::CompoundAssign(op) => { Self::CompoundAssign(op) }
+            LogosToken::At => { Self::At }
+            LogosToken::Asm => { Self::KeyWord(KeyWord::Asm) }
+            LogosToken::Volatile => { Self::KeyWord(KeyWord::Volatile) }
+            LogosToken::Implement => { Self::KeyWord(KeyWord::Implement) }
+            LogosToken::Break => { Self::KeyWord(KeyWord::Break) }
+            LogosToken::Continue => { Self::KeyWord(KeyWord::Continue) }
+            LogosToken::Unreachable => { Self::KeyWord(KeyWord::Unreachable) }
             _ => { unreachable!("unknown character handling not implement yet") }
         }
     }
@@ -456,6 +602,8 @@ impl From<LogosToken> for LEToken {
 pub struct LELexer<'s> {
     inner: Lexer<'s, LogosToken>,
     current: Option<LEToken>,
+    /// 正在解析的表达式的嵌套层数，由语法分析器维护，用于限制AST的深度
+    nesting_depth: usize,
 }
 
 
@@ -472,9 +620,16 @@ impl<'s> Iterator for LELexer<'s> {
 
 impl<'s> LELexer<'s> {
     pub fn new(s: &'s str) -> Option<Self> {
+        Self::with_offset(s, 0)
+    }
+
+    /// 创建一个所有位置都从`base_offset`开始计算的词法分析器，用于把多个源文件的位置区分开
+    pub fn with_offset(s: &'s str, base_offset: usize) -> Option<Self> {
+        let start = Position { range: (base_offset..base_offset) };
         let mut s = Self {
-            inner: LogosToken::lexer_with_extras(s, Extra { current_pos: Position { range: (0..0) }, last_pos: Position { range: (0..0) } }),
+            inner: LogosToken::lexer_with_extras(s, Extra { current_pos: start.clone(), last_pos: start, base_offset }),
             current: None,
+            nesting_depth: 0,
         };
         s.next();
         Some(s)
@@ -482,11 +637,24 @@ impl<'s> LELexer<'s> {
     pub fn consume(&mut self) {
         self.next().unwrap();
     }
+
+    pub fn nesting_depth(&self) -> usize {
+        self.nesting_depth
+    }
+
+    pub fn set_nesting_depth(&mut self, depth: usize) {
+        self.nesting_depth = depth;
+    }
     /// 获取迭代器当前指向的token，如果不存在则返回None
     pub fn current(&self) -> Option<LEToken> {
         self.current.clone()
     }
 
+    ///获取迭代器当前指向的token在源码中的原文
+    pub fn current_slice(&self) -> &'s str {
+        self.inner.slice()
+    }
+
     ///获取迭代器当前指向的token的位置
     pub fn pos(&self) -> Position {
         self.inner.extras.current_pos.clone()
@@ -532,6 +700,8 @@ impl<'s> LELexer<'s> {
         if let Some(consume) = consume {
             if let LEToken::NumberLiteral(number) = consume {
                 Ok(number)
+            } else if let LEToken::OverflowNumberLiteral(literal) = consume {
+                Err(LEError::new_syntax_error(SyntaxError::NumberLiteralOverflow { literal }, current_pos))
             } else {
                 Err(LEError::new_syntax_error(SyntaxError::unexpect_token(vec![TokenType::NumberLiteral], consume), current_pos))
             }
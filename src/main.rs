@@ -30,7 +30,14 @@ fn read_args_and_compile() -> std::io::Result<()> {
         .with_color(atty::is(Stream::Stderr));
 
     match driver::compile_with_config(&args, &buffer) {
-        Ok(_) => {}
+        Ok(warnings) => {
+            for warning in warnings {
+                warning.to_warning_report_colored(src)
+                    .with_config(config)
+                    .finish()
+                    .eprint((src, Source::from(&buffer)))?;
+            }
+        }
         Err(err) => {
             err.to_error_report_colored(src)
                 .with_config(config)
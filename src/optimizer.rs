@@ -3,15 +3,20 @@ use inkwell::OptimizationLevel;
 use inkwell::passes::{PassManager, PassManagerBuilder};
 use inkwell::values::FunctionValue;
 
+/// 模块级优化中内联的阈值，与LLVM在O2下使用的默认值相同
+const INLINE_THRESHOLD: u32 = 225;
+
 pub struct Optimizer<'s> {
     pass_manager: PassManager<FunctionValue<'s>>,
 }
 
 impl<'s> Optimizer<'s> {
-    pub fn new(module: &Module<'s>, level: OptimizationLevel) -> Self {
+    /// size_level为0时不针对体积优化，1和2分别对应-Os和-Oz
+    pub fn new(module: &Module<'s>, level: OptimizationLevel, size_level: u32) -> Self {
         let pass_manager = PassManager::create(module);
         let builder = PassManagerBuilder::create();
         builder.set_optimization_level(level);
+        builder.set_size_level(size_level);
         builder.populate_function_pass_manager(&pass_manager);
         Self { pass_manager }
     }
@@ -19,6 +24,16 @@ impl<'s> Optimizer<'s> {
         self.pass_manager.run_on(function)
     }
 
+    /// 运行包括内联在内的模块级优化流程，带有inlinehint的函数更容易被内联
+    pub fn run_module_passes(module: &Module<'s>, level: OptimizationLevel) -> bool {
+        let pass_manager = PassManager::create(());
+        let builder = PassManagerBuilder::create();
+        builder.set_optimization_level(level);
+        builder.set_inliner_with_threshold(INLINE_THRESHOLD);
+        builder.populate_module_pass_manager(&pass_manager);
+        pass_manager.run_on(module)
+    }
+
     pub fn run_on_module(&self, module: &Module) {
         let mut current_function = module.get_first_function();
         while let Some(f) = current_function {